@@ -5,6 +5,8 @@ fn main() {
     let _ = fs::create_dir_all(&workdir);
     prost_build::Config::new()
         .out_dir(workdir)
+        //Decoding from `Bytes` lets tensor data borrow from the model buffer instead of copying
+        .bytes([".onnx.TensorProto.raw_data"])
         .compile_protos(&["protos/onnx/onnx.proto3"], &["protos/"])
         .expect("Failed to compile protos.");
 }
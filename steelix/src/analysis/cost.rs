@@ -0,0 +1,106 @@
+use crate::{
+    display::Device,
+    ir::{DType, Model, ModelSummary, OpGroup},
+};
+
+#[derive(thiserror::Error, Debug)]
+#[error("Device {device} has no throughput for {dt:?}.")]
+pub struct ThroughputError {
+    pub device: String,
+    pub dt: DType,
+}
+
+#[inline]
+pub(crate) fn node_flops(summary: &ModelSummary, node_id: usize) -> usize {
+    summary.node_costs.get(&node_id).map_or(0, |c| c.flops)
+}
+
+///Parameters of the constants feeding the nodes, each counted once
+pub(crate) fn weight_params(model: &Model, summary: &ModelSummary, nodes: &[usize]) -> usize {
+    let mut constants: Vec<usize> = nodes
+        .iter()
+        .flat_map(|&id| model.nodes[id].providers.iter().copied())
+        .filter(|&p| model.nodes[p].op.op_group() == OpGroup::Constant)
+        .collect();
+    constants.sort_unstable();
+    constants.dedup();
+    constants
+        .iter()
+        .filter_map(|p| summary.node_costs.get(p))
+        .map(|c| c.parameters)
+        .sum()
+}
+
+///Seconds the node takes at the peak throughput of the device.
+///Ops on integer or boolean tensors run at the single precision rate.
+pub(crate) fn node_latency(
+    model: &Model,
+    summary: &ModelSummary,
+    node_id: usize,
+    device: &Device,
+) -> Result<f64, ThroughputError> {
+    let flops = node_flops(summary, node_id);
+    if flops == 0 {
+        return Ok(0.);
+    }
+    let dt = model.nodes[node_id]
+        .outputs
+        .first()
+        .and_then(|t| summary.inferred_tensors.get(t))
+        .map_or(DType::F32, |vi| vi.dt);
+    let dt = match dt {
+        DType::I8 | DType::F16 | DType::F32 | DType::F64 => dt,
+        _ => DType::F32,
+    };
+    match device.flops_per_sec(dt).unwrap_or(0) {
+        0 => Err(ThroughputError {
+            device: device.name.clone(),
+            dt,
+        }),
+        rate => Ok(flops as f64 / rate as f64),
+    }
+}
+
+///Per node quantity a plot can be colored by
+#[derive(Clone)]
+pub enum CostMetric {
+    Flops,
+    Params,
+    Bytes,           //written to the outputs of the node
+    Latency(Device), //seconds at the peak throughput of the device
+}
+
+impl CostMetric {
+    pub fn title(&self) -> String {
+        match self {
+            CostMetric::Flops => "FLOPs".to_string(),
+            CostMetric::Params => "Parameters".to_string(),
+            CostMetric::Bytes => "Output bytes".to_string(),
+            CostMetric::Latency(device) => format!("Latency on {}", device.name),
+        }
+    }
+}
+
+pub fn node_metric(
+    model: &Model,
+    summary: &ModelSummary,
+    node_id: usize,
+    metric: &CostMetric,
+) -> Result<f64, ThroughputError> {
+    let value = match metric {
+        CostMetric::Flops => node_flops(summary, node_id) as f64,
+        //Weights count towards the parameters of their consumers
+        CostMetric::Params => {
+            let own = summary.node_costs.get(&node_id).map_or(0, |c| c.parameters);
+            (own + weight_params(model, summary, &[node_id])) as f64
+        }
+        CostMetric::Bytes => model.nodes[node_id]
+            .outputs
+            .iter()
+            .filter_map(|t| summary.inferred_tensors.get(t))
+            .map(|vi| vi.size_in_bytes() as f64)
+            .sum(),
+        CostMetric::Latency(device) => node_latency(model, summary, node_id, device)?,
+    };
+    Ok(value)
+}
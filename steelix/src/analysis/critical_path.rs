@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use crate::{
+    display::Device,
+    ir::{Model, ModelSummary, OpGroup},
+};
+
+use super::cost::{node_flops, node_latency, ThroughputError};
+
+#[derive(thiserror::Error, Debug)]
+pub enum CriticalPathError {
+    #[error("Traversal order has not been built.")]
+    MissingTraversalOrder,
+    #[error(transparent)]
+    Throughput(#[from] ThroughputError),
+}
+
+///What each node on a path costs
+#[derive(Clone)]
+pub enum PathWeight {
+    Flops,
+    Latency(Device), //seconds at the peak throughput of the device
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CriticalPath {
+    pub path: Vec<usize>, //compute nodes from a graph input to a graph output
+    pub path_cost: f64,
+    pub total_cost: f64,
+    pub levels: HashMap<usize, usize>, //earliest level each compute node can run at
+    pub level_widths: Vec<usize>,      //number of nodes runnable at each level
+}
+
+impl CriticalPath {
+    ///Number of levels, the longest path counted in nodes
+    pub fn depth(&self) -> usize {
+        self.level_widths.len()
+    }
+
+    pub fn max_width(&self) -> usize {
+        self.level_widths.iter().copied().max().unwrap_or(0)
+    }
+
+    ///Speed-up over sequential execution with unlimited parallel units
+    pub fn speedup(&self) -> f64 {
+        if self.path_cost > 0. {
+            self.total_cost / self.path_cost
+        } else {
+            1.
+        }
+    }
+}
+
+///Finds the most expensive chain of dependent nodes, which bounds the latency of the model
+///however many ops run concurrently. Inputs, outputs and constants are free and not part of it.
+pub fn critical_path(
+    model: &Model,
+    summary: &ModelSummary,
+    weight: &PathWeight,
+) -> Result<CriticalPath, CriticalPathError> {
+    let order = model
+        .traversal_order
+        .as_ref()
+        .ok_or(CriticalPathError::MissingTraversalOrder)?;
+    let is_compute = |id: usize| {
+        !model.inputs.contains(&id)
+            && !model.outputs.contains(&id)
+            && model.nodes[id].op.op_group() != OpGroup::Constant
+    };
+
+    let mut result = CriticalPath::default();
+    //Cost of the most expensive path ending at each node, and the node before it
+    let mut dist: HashMap<usize, (f64, Option<usize>)> = HashMap::new();
+    for &id in order.iter().filter(|&&id| is_compute(id)) {
+        let cost = match weight {
+            PathWeight::Flops => node_flops(summary, id) as f64,
+            PathWeight::Latency(device) => node_latency(model, summary, id, device)?,
+        };
+        result.total_cost += cost;
+
+        let mut level = 0;
+        let mut best: (f64, Option<usize>) = (0., None);
+        for provider in model.nodes[id].providers.iter() {
+            if let Some(&(provider_cost, _)) = dist.get(provider) {
+                level = level.max(result.levels[provider] + 1);
+                if best.1.is_none() || provider_cost > best.0 {
+                    best = (provider_cost, Some(*provider));
+                }
+            }
+        }
+        dist.insert(id, (best.0 + cost, best.1));
+        result.levels.insert(id, level);
+        if result.level_widths.len() <= level {
+            result.level_widths.resize(level + 1, 0);
+        }
+        result.level_widths[level] += 1;
+    }
+
+    //Ties are broken by traversal order, so the path is stable between runs
+    let mut end = None;
+    for &id in order.iter() {
+        if let Some(&(cost, _)) = dist.get(&id) {
+            if end.map_or(true, |(best, _)| cost > best) {
+                end = Some((cost, id));
+            }
+        }
+    }
+    if let Some((cost, mut id)) = end {
+        result.path_cost = cost;
+        result.path.push(id);
+        while let Some(prev) = dist[&id].1 {
+            result.path.push(prev);
+            id = prev;
+        }
+        result.path.reverse();
+    }
+    Ok(result)
+}
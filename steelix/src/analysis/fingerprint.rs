@@ -0,0 +1,313 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use sha1_smol::Sha1;
+
+use crate::ir::{Model, ModelSummary, OpGroup};
+
+use super::stats::GraphStats;
+
+///Pseudo op counting the skip connections found by `graph_stats`
+const RESIDUAL: &str = "Residual";
+
+///Rounds of label refinement, so each label sees a few dozen hops around its node
+const MAX_ROUNDS: usize = 32;
+
+///Ops always compared, so a network is penalized for heavy compute its signature lacks
+const CORE_OPS: &[&str] = &[
+    "Conv",
+    "ConvTranspose",
+    "Gemm",
+    "MatMul",
+    "Softmax",
+    RESIDUAL,
+];
+
+///Op counts of a typical ONNX export of a known network, with BatchNormalization folded
+pub struct ArchitectureSignature {
+    pub name: &'static str,
+    pub ops: &'static [(&'static str, usize)],
+}
+
+pub const SIGNATURES: &[ArchitectureSignature] = &[
+    ArchitectureSignature {
+        name: "ResNet-18",
+        ops: &[
+            ("Conv", 20),
+            ("Relu", 17),
+            ("Add", 8),
+            ("MaxPool", 1),
+            ("GlobalAveragePool", 1),
+            ("Gemm", 1),
+            (RESIDUAL, 5),
+        ],
+    },
+    ArchitectureSignature {
+        name: "ResNet-34",
+        ops: &[
+            ("Conv", 36),
+            ("Relu", 33),
+            ("Add", 16),
+            ("MaxPool", 1),
+            ("GlobalAveragePool", 1),
+            ("Gemm", 1),
+            (RESIDUAL, 13),
+        ],
+    },
+    ArchitectureSignature {
+        name: "ResNet-50",
+        ops: &[
+            ("Conv", 53),
+            ("Relu", 49),
+            ("Add", 16),
+            ("MaxPool", 1),
+            ("GlobalAveragePool", 1),
+            ("Gemm", 1),
+            (RESIDUAL, 12),
+        ],
+    },
+    ArchitectureSignature {
+        name: "ResNet-101",
+        ops: &[
+            ("Conv", 104),
+            ("Relu", 100),
+            ("Add", 33),
+            ("MaxPool", 1),
+            ("GlobalAveragePool", 1),
+            ("Gemm", 1),
+            (RESIDUAL, 29),
+        ],
+    },
+    ArchitectureSignature {
+        name: "ResNet-152",
+        ops: &[
+            ("Conv", 155),
+            ("Relu", 151),
+            ("Add", 50),
+            ("MaxPool", 1),
+            ("GlobalAveragePool", 1),
+            ("Gemm", 1),
+            (RESIDUAL, 46),
+        ],
+    },
+    ArchitectureSignature {
+        name: "MobileNetV2",
+        ops: &[
+            ("Conv", 52),
+            ("Clip", 35),
+            ("Add", 10),
+            ("GlobalAveragePool", 1),
+            ("Gemm", 1),
+            (RESIDUAL, 10),
+        ],
+    },
+    ArchitectureSignature {
+        name: "EfficientNet-B0",
+        ops: &[
+            ("Conv", 81),
+            ("Sigmoid", 65),
+            ("Mul", 65),
+            ("Add", 9),
+            ("GlobalAveragePool", 17),
+            ("Gemm", 1),
+            (RESIDUAL, 9),
+        ],
+    },
+    ArchitectureSignature {
+        name: "BERT-base",
+        ops: &[
+            ("MatMul", 96),
+            ("Gemm", 1),
+            ("Softmax", 12),
+            ("Erf", 12),
+            ("Tanh", 1),
+            (RESIDUAL, 24),
+        ],
+    },
+    ArchitectureSignature {
+        name: "GPT-2",
+        ops: &[
+            ("MatMul", 25),
+            ("Gemm", 48),
+            ("Softmax", 12),
+            ("Tanh", 12),
+            ("Where", 12),
+            (RESIDUAL, 24),
+        ],
+    },
+    ArchitectureSignature {
+        name: "ViT-B/16",
+        ops: &[
+            ("Conv", 1),
+            ("MatMul", 72),
+            ("Gemm", 1),
+            ("Softmax", 12),
+            ("Erf", 12),
+            (RESIDUAL, 24),
+        ],
+    },
+    ArchitectureSignature {
+        name: "UNet",
+        ops: &[
+            ("Conv", 19),
+            ("ConvTranspose", 4),
+            ("Relu", 18),
+            ("MaxPool", 4),
+            ("Concat", 4),
+            (RESIDUAL, 4),
+        ],
+    },
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchitectureMatch {
+    pub name: &'static str,
+    pub score: f64, //between 0 and 1
+}
+
+#[derive(Debug, Clone)]
+pub struct Fingerprint {
+    pub hash: String,
+    pub matches: Vec<ArchitectureMatch>, //every signature, best first
+}
+
+impl Fingerprint {
+    pub fn best_match(&self) -> Option<&ArchitectureMatch> {
+        self.matches.first()
+    }
+}
+
+///Structural hash of the model and its similarity to known networks, counting the residual
+///connections found in `stats`
+pub fn fingerprint(model: &Model, summary: &ModelSummary, stats: &GraphStats) -> Fingerprint {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for node in model.nodes.iter() {
+        *counts.entry(&*node.name).or_insert(0) += 1;
+    }
+    counts.insert(RESIDUAL, stats.residuals.len());
+
+    let mut matches: Vec<ArchitectureMatch> = SIGNATURES
+        .iter()
+        .map(|signature| ArchitectureMatch {
+            name: signature.name,
+            score: similarity(signature, &counts),
+        })
+        .collect();
+    //Scores are ratios of counts, so never NaN
+    matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+
+    Fingerprint {
+        hash: structural_hash(model, summary),
+        matches,
+    }
+}
+
+///Weighted Jaccard similarity of the op counts, over the ops of the signature and the core ops
+fn similarity(signature: &ArchitectureSignature, counts: &HashMap<&str, usize>) -> f64 {
+    let expected: HashMap<&str, usize> = signature.ops.iter().copied().collect();
+    let keys: HashSet<&str> = expected
+        .keys()
+        .copied()
+        .chain(CORE_OPS.iter().copied())
+        .collect();
+
+    let (mut shared, mut total) = (0, 0);
+    for key in keys {
+        let expected = expected.get(key).copied().unwrap_or(0);
+        let actual = counts.get(key).copied().unwrap_or(0);
+        shared += expected.min(actual);
+        total += expected.max(actual);
+    }
+    if total == 0 {
+        return 0.;
+    }
+    shared as f64 / total as f64
+}
+
+fn digest(data: &[u8]) -> u64 {
+    let bytes = Sha1::from(data).digest().bytes();
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+///Finalizer of splitmix64, a cheap and stable mix for the labels of each refinement round
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+///Hash of the graph structure which ignores node and tensor names, node order and weight values.
+///Two files with the same hash contain the same network, possibly with different weights.
+///
+///Nodes are labelled by op type, domain and attributes, while graph inputs, outputs and constants
+///are labelled by their kind and the type and shape of their tensor. Labels are then refined with
+///those of their providers (in input order) and consumers until they stop distinguishing more
+///nodes, as in the Weisfeiler-Lehman isomorphism test, for at most `MAX_ROUNDS` rounds.
+pub fn structural_hash(model: &Model, summary: &ModelSummary) -> String {
+    let mut labels: Vec<u64> = model
+        .nodes
+        .iter()
+        .map(|node| digest(node_label(model, summary, node.id).as_bytes()))
+        .collect();
+
+    let distinct = |labels: &[u64]| labels.iter().collect::<HashSet<_>>().len();
+    let mut n_distinct = distinct(&labels);
+    for _ in 0..MAX_ROUNDS {
+        let refined: Vec<u64> = model
+            .nodes
+            .iter()
+            .map(|node| {
+                let providers = node
+                    .providers
+                    .iter()
+                    .fold(mix(labels[node.id]), |acc, &p| mix(acc ^ labels[p]));
+                //Consumers are unordered, so their labels are summed
+                let consumers = node
+                    .consumers
+                    .iter()
+                    .fold(0u64, |acc, &c| acc.wrapping_add(mix(labels[c])));
+                mix(providers ^ mix(consumers))
+            })
+            .collect();
+        labels = refined;
+        let refined_distinct = distinct(&labels);
+        if refined_distinct == n_distinct {
+            break;
+        }
+        n_distinct = refined_distinct;
+    }
+
+    labels.sort_unstable();
+    let data: Vec<u8> = labels.iter().flat_map(|l| l.to_le_bytes()).collect();
+    Sha1::from(data).digest().to_string()
+}
+
+///Label of a node which does not depend on any name
+fn node_label(model: &Model, summary: &ModelSummary, node_id: usize) -> String {
+    let node = &model.nodes[node_id];
+    let (kind, tensors) = if model.inputs.contains(&node_id) {
+        ("Input", &node.outputs)
+    } else if model.outputs.contains(&node_id) {
+        ("Output", &node.inputs)
+    } else if node.op.op_group() == OpGroup::Constant {
+        ("Constant", &node.outputs)
+    } else {
+        let mut attributes = node.attributes.clone();
+        attributes.sort();
+        let mut label = format!("{}\0{}", node.name, node.domain);
+        for (name, value) in attributes {
+            label.push_str(&format!("\0{}={}", name, value));
+        }
+        return label;
+    };
+    let mut label = kind.to_string();
+    for tensor in tensors.iter() {
+        if let Some(vi) = summary.inferred_tensors.get(tensor) {
+            label.push_str(&format!("\0{:?}{:?}", vi.dt, vi.dimensions));
+        }
+    }
+    label
+}
@@ -0,0 +1,17 @@
+mod cost;
+mod critical_path;
+mod fingerprint;
+mod pipeline;
+mod region;
+mod repeats;
+mod stats;
+mod tensor_parallel;
+
+pub use cost::{node_metric, CostMetric, ThroughputError};
+pub use critical_path::*;
+pub use fingerprint::*;
+pub use pipeline::*;
+pub use region::*;
+pub use repeats::*;
+pub use stats::*;
+pub use tensor_parallel::*;
@@ -0,0 +1,303 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    display::Device,
+    ir::{Model, ModelSummary, OpGroup},
+};
+
+use super::cost::{node_flops, node_latency, ThroughputError};
+
+#[derive(thiserror::Error, Debug)]
+pub enum PartitionError {
+    #[error("A pipeline needs at least one stage.")]
+    NoStages,
+    #[error("Cannot split {nodes} nodes into {stages} stages.")]
+    TooFewNodes { nodes: usize, stages: usize },
+    #[error("Expected 1 or {stages} devices, got {devices}.")]
+    DeviceCount { devices: usize, stages: usize },
+    #[error("Balancing by latency requires a device.")]
+    MissingDevice,
+    #[error("Traversal order has not been built.")]
+    MissingTraversalOrder,
+    #[error(transparent)]
+    Throughput(#[from] ThroughputError),
+}
+
+///The cost which stages are balanced by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceBy {
+    Flops,
+    ParamBytes, //bytes of the constants each stage holds
+    Latency,
+}
+
+impl FromStr for BalanceBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flops" => Ok(BalanceBy::Flops),
+            "param-bytes" => Ok(BalanceBy::ParamBytes),
+            "latency" => Ok(BalanceBy::Latency),
+            _ => Err(anyhow::anyhow!("Unknown balance metric: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PipelineStage {
+    pub nodes: Vec<usize>,     //compute nodes, in traversal order
+    pub constants: Vec<usize>, //constants read by the stage, duplicated if shared
+    pub flops: usize,
+    pub param_bytes: usize,
+    pub latency: Option<f64>, //seconds on the stage's device
+    pub device: Option<String>,
+    pub inputs: Vec<String>, //tensors received from earlier stages, including forwarded ones
+    pub outputs: Vec<String>, //tensors sent to later stages, or graph outputs
+}
+
+///Tensors which must be sent between stages `after_stage` and `after_stage + 1`.
+///Tensors skipping a stage are forwarded, and cross every cut in between.
+#[derive(Debug, Clone, Default)]
+pub struct Cut {
+    pub after_stage: usize,
+    pub tensors: Vec<(String, usize)>, //name and size in bytes
+}
+
+impl Cut {
+    pub fn bytes(&self) -> usize {
+        self.tensors.iter().map(|(_, bytes)| bytes).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PipelinePlan {
+    pub balance: BalanceBy,
+    pub stages: Vec<PipelineStage>,
+    pub cuts: Vec<Cut>,
+}
+
+///Splits the traversal order of the model into `n_stages` contiguous stages, minimizing the
+///cost of the most expensive stage.
+///
+///`devices` holds either one device used by every stage, or one device per stage.
+///Constants are placed in every stage which reads them.
+pub fn partition_pipeline(
+    model: &Model,
+    summary: &ModelSummary,
+    n_stages: usize,
+    balance: BalanceBy,
+    devices: &[Device],
+) -> Result<PipelinePlan, PartitionError> {
+    if n_stages == 0 {
+        return Err(PartitionError::NoStages);
+    }
+    if !devices.is_empty() && devices.len() != 1 && devices.len() != n_stages {
+        return Err(PartitionError::DeviceCount {
+            devices: devices.len(),
+            stages: n_stages,
+        });
+    }
+    if balance == BalanceBy::Latency && devices.is_empty() {
+        return Err(PartitionError::MissingDevice);
+    }
+    let stage_device = |stage: usize| devices.get(stage).or_else(|| devices.first());
+
+    let order = model
+        .traversal_order
+        .as_ref()
+        .ok_or(PartitionError::MissingTraversalOrder)?;
+    let is_constant = |id: usize| model.nodes[id].op.op_group() == OpGroup::Constant;
+    let compute: Vec<usize> = order
+        .iter()
+        .copied()
+        .filter(|id| !model.inputs.contains(id) && !model.outputs.contains(id) && !is_constant(*id))
+        .collect();
+    if compute.len() < n_stages {
+        return Err(PartitionError::TooFewNodes {
+            nodes: compute.len(),
+            stages: n_stages,
+        });
+    }
+
+    let flops = |id: usize| node_flops(summary, id);
+    let output_bytes = |id: usize| -> usize {
+        model.nodes[id]
+            .outputs
+            .iter()
+            .filter_map(|t| summary.inferred_tensors.get(t))
+            .map(|vi| vi.size_in_bytes())
+            .sum()
+    };
+    let latency = |id: usize, device: &Device| node_latency(model, summary, id, device);
+
+    //Constant bytes count towards the first node which reads them
+    let mut attributed = HashMap::new();
+    let mut placed = vec![false; model.nodes.len()];
+    for &id in compute.iter() {
+        for &provider in model.nodes[id].providers.iter() {
+            if is_constant(provider) && !placed[provider] {
+                placed[provider] = true;
+                *attributed.entry(id).or_insert(0) += output_bytes(provider);
+            }
+        }
+    }
+
+    //cost[k][i] is the cost of compute node i when placed on stage k
+    let n_costs = if balance == BalanceBy::Latency {
+        devices.len()
+    } else {
+        1
+    };
+    let costs = (0..n_costs)
+        .map(|k| {
+            compute
+                .iter()
+                .map(|&id| match balance {
+                    BalanceBy::Flops => Ok(flops(id) as f64),
+                    BalanceBy::ParamBytes => Ok(*attributed.get(&id).unwrap_or(&0) as f64),
+                    BalanceBy::Latency => latency(id, &devices[k]),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let cost = |stage: usize, idx: usize| costs[stage.min(costs.len() - 1)][idx];
+
+    let boundaries = balance_stages(compute.len(), n_stages, cost);
+
+    let mut stage_of = HashMap::new();
+    let mut stages = vec![];
+    for (k, range) in boundaries.windows(2).enumerate() {
+        let nodes = compute[range[0]..range[1]].to_vec();
+        let mut stage = PipelineStage {
+            flops: nodes.iter().map(|&id| flops(id)).sum(),
+            device: stage_device(k).map(|d| d.name.clone()),
+            ..Default::default()
+        };
+        if let Some(device) = stage_device(k) {
+            //Only an error when balancing by latency, which has already failed above
+            stage.latency = nodes
+                .iter()
+                .map(|&id| latency(id, device))
+                .sum::<Result<f64, _>>()
+                .ok();
+        }
+        for &id in nodes.iter() {
+            stage_of.insert(id, k);
+            for &provider in model.nodes[id].providers.iter() {
+                if is_constant(provider) && !stage.constants.contains(&provider) {
+                    stage.constants.push(provider);
+                    stage.param_bytes += output_bytes(provider);
+                }
+            }
+        }
+        stage.nodes = nodes;
+        stages.push(stage);
+    }
+
+    //Tensors produced in one stage and read in a later one
+    let mut cuts: Vec<Cut> = (0..n_stages - 1)
+        .map(|after_stage| Cut {
+            after_stage,
+            ..Default::default()
+        })
+        .collect();
+    for &consumer in compute.iter() {
+        let consumer_stage = stage_of[&consumer];
+        for (producer, tensor) in model.input_tensors(consumer) {
+            let producer_stage = match stage_of.get(&producer) {
+                Some(&s) if s < consumer_stage => s,
+                _ => continue,
+            };
+            if stages[consumer_stage].inputs.contains(&tensor) {
+                continue;
+            }
+            stages[consumer_stage].inputs.push(tensor.clone());
+            if !stages[producer_stage].outputs.contains(&tensor) {
+                stages[producer_stage].outputs.push(tensor.clone());
+            }
+            //Stages in between pass the tensor on unchanged
+            for stage in stages[producer_stage + 1..consumer_stage].iter_mut() {
+                if !stage.inputs.contains(&tensor) {
+                    stage.inputs.push(tensor.clone());
+                }
+                if !stage.outputs.contains(&tensor) {
+                    stage.outputs.push(tensor.clone());
+                }
+            }
+            let bytes = summary
+                .inferred_tensors
+                .get(&tensor)
+                .map_or(0, |vi| vi.size_in_bytes());
+            for cut in cuts[producer_stage..consumer_stage].iter_mut() {
+                if !cut.tensors.iter().any(|(name, _)| *name == tensor) {
+                    cut.tensors.push((tensor.clone(), bytes));
+                }
+            }
+        }
+    }
+    for &output in model.outputs.iter() {
+        for (producer, tensor) in model.input_tensors(output) {
+            if let Some(&stage) = stage_of.get(&producer) {
+                if !stages[stage].outputs.contains(&tensor) {
+                    stages[stage].outputs.push(tensor);
+                }
+            }
+        }
+    }
+
+    Ok(PipelinePlan {
+        balance,
+        stages,
+        cuts,
+    })
+}
+
+///Splits `n` items into `n_stages` non empty contiguous ranges, minimizing the largest
+///stage cost. Returns the `n_stages + 1` range boundaries.
+///
+///Binary searches the bottleneck, each candidate is checked by greedily filling stages.
+fn balance_stages(n: usize, n_stages: usize, cost: impl Fn(usize, usize) -> f64) -> Vec<usize> {
+    let fill = |limit: f64| -> Option<Vec<usize>> {
+        let mut boundaries = vec![0];
+        let mut start = 0;
+        for stage in 0..n_stages {
+            let stages_left = n_stages - stage - 1;
+            if stages_left == 0 {
+                let load: f64 = (start..n).map(|i| cost(stage, i)).sum();
+                return (load <= limit).then(|| {
+                    boundaries.push(n);
+                    boundaries
+                });
+            }
+            let mut end = start;
+            let mut load = 0.;
+            //Every stage takes at least one node, and leaves one for each stage after it
+            while end < n - stages_left && (end == start || load + cost(stage, end) <= limit) {
+                load += cost(stage, end);
+                end += 1;
+            }
+            if load > limit {
+                return None;
+            }
+            boundaries.push(end);
+            start = end;
+        }
+        unreachable!()
+    };
+
+    let max_cost = (0..n_stages)
+        .map(|stage| (0..n).map(|i| cost(stage, i)).sum::<f64>())
+        .fold(0., f64::max);
+    //Headroom for rounding, as sums of a subset may differ from the total in the last bit
+    let (mut lo, mut hi) = (0., max_cost * (1. + 1e-9));
+    for _ in 0..64 {
+        let mid = (lo + hi) / 2.;
+        if fill(mid).is_some() {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    fill(hi).expect("The total cost always fits in a single stage.")
+}
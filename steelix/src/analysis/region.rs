@@ -0,0 +1,115 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::ir::{Model, OpGroup};
+
+#[derive(thiserror::Error, Debug)]
+pub enum RegionError {
+    #[error("No node or tensor is named {0}.")]
+    UnknownName(String),
+    #[error("No path leads from {from} to {to}.")]
+    Disconnected { from: String, to: String },
+}
+
+///Part of a model to look at on its own. Names are of nodes or tensors, nodes first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Region {
+    ///Nodes downstream of every `from` and upstream of every `to`. Either side may be empty,
+    ///leaving that side of the model unbounded. A tensor bounds the range at its consumers,
+    ///or at its producer as a `to`.
+    Range { from: Vec<String>, to: Vec<String> },
+    ///Nodes at most `depth` edges away from `center`, in either direction
+    Around { center: String, depth: usize },
+}
+
+///Nodes named `name`, or else the producer (`downstream` false) or consumers (`downstream`
+///true) of the tensor named `name`
+fn resolve(model: &Model, name: &str, downstream: bool) -> Result<Vec<usize>, RegionError> {
+    let nodes: Vec<usize> = model
+        .nodes
+        .iter()
+        .filter(|n| n.onnx_name == name)
+        .map(|n| n.id)
+        .collect();
+    if !nodes.is_empty() {
+        return Ok(nodes);
+    }
+    let tensors: Vec<usize> = model
+        .nodes
+        .iter()
+        .filter(|n| match downstream {
+            true => n.inputs.iter().any(|t| t == name),
+            false => n.outputs.iter().any(|t| t == name),
+        })
+        .map(|n| n.id)
+        .collect();
+    if tensors.is_empty() {
+        return Err(RegionError::UnknownName(name.to_string()));
+    }
+    Ok(tensors)
+}
+
+///Nodes reachable from `start` through `next`, including `start`, up to `depth` steps away
+fn reach(
+    model: &Model,
+    start: &[usize],
+    depth: usize,
+    next: impl Fn(usize) -> Vec<usize>,
+) -> HashSet<usize> {
+    let mut seen: HashSet<usize> = start.iter().copied().collect();
+    let mut queue: VecDeque<(usize, usize)> = start.iter().map(|&id| (id, 0)).collect();
+    while let Some((id, d)) = queue.pop_front() {
+        if d == depth {
+            continue;
+        }
+        for n in next(id) {
+            //Constants are never drawn, so they do not count as a step
+            if model.nodes[n].op.op_group() != OpGroup::Constant && seen.insert(n) {
+                queue.push_back((n, d + 1));
+            }
+        }
+    }
+    seen
+}
+
+///Ids of the nodes in the region, in id order. Constants are left out, as plots do not
+///draw them.
+pub fn select_region(model: &Model, region: &Region) -> Result<Vec<usize>, RegionError> {
+    let downstream = |id: usize| model.nodes[id].consumers.clone();
+    let upstream = |id: usize| model.nodes[id].providers.clone();
+
+    let selected = match region {
+        Region::Range { from, to } => {
+            let mut selected: HashSet<usize> = model.nodes.iter().map(|n| n.id).collect();
+            for name in from.iter() {
+                let below = reach(model, &resolve(model, name, true)?, usize::MAX, downstream);
+                selected.retain(|id| below.contains(id));
+            }
+            for name in to.iter() {
+                let above = reach(model, &resolve(model, name, false)?, usize::MAX, upstream);
+                selected.retain(|id| above.contains(id));
+            }
+            if selected.is_empty() {
+                return Err(RegionError::Disconnected {
+                    from: from.join(", "),
+                    to: to.join(", "),
+                });
+            }
+            selected
+        }
+        Region::Around { center, depth } => {
+            let start = resolve(model, center, true)?;
+            reach(model, &start, *depth, |id| {
+                let mut next = downstream(id);
+                next.extend(upstream(id));
+                next
+            })
+        }
+    };
+
+    let mut selected: Vec<usize> = selected
+        .into_iter()
+        .filter(|&id| model.nodes[id].op.op_group() != OpGroup::Constant)
+        .collect();
+    selected.sort_unstable();
+    Ok(selected)
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use crate::ir::{Model, ModelSummary, OpCost, OpGroup};
+
+use super::{cost::weight_params, StatsError};
+
+///Longest block considered when searching for repeated blocks
+const MAX_BLOCK_LEN: usize = 256;
+
+///Windows hashed per compute node over the whole search, so long graphs stay linear
+const WORK_PER_NODE: usize = 4 * MAX_BLOCK_LEN;
+
+///A block of nodes repeated back to back, such as the layers of a transformer. Every repetition
+///has the same ops, attributes and weight shapes as the first, wired the same way: nodes read
+///from the same positions in their block, and each tensor from outside the block is read by the
+///same nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepeatedSubgraph {
+    pub instances: Vec<Vec<usize>>, //node ids of each repetition, aligned with the first
+}
+
+impl RepeatedSubgraph {
+    pub fn count(&self) -> usize {
+        self.instances.len()
+    }
+
+    ///Nodes of a single repetition
+    pub fn len(&self) -> usize {
+        self.instances[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances[0].is_empty()
+    }
+
+    ///Op types of a single repetition, in traversal order
+    pub fn ops<'a>(&self, model: &'a Model) -> Vec<&'a str> {
+        self.instances[0]
+            .iter()
+            .map(|&id| &*model.nodes[id].name)
+            .collect()
+    }
+
+    ///Cost of a single repetition, including the weights it reads
+    pub fn cost(&self, model: &Model, summary: &ModelSummary) -> OpCost {
+        let cost = self.instances[0]
+            .iter()
+            .filter_map(|id| summary.node_costs.get(id))
+            .fold(OpCost::default(), |acc, c| OpCost {
+                flops: acc.flops + c.flops,
+                parameters: acc.parameters + c.parameters,
+            });
+        OpCost {
+            parameters: cost.parameters + weight_params(model, summary, &self.instances[0]),
+            ..cost
+        }
+    }
+}
+
+///Finds the repeated blocks of the model, largest first. Candidates are runs of identical node
+///signatures in the traversal order, kept only where the wiring of each repetition matches the
+///first. Each block found is excluded from the next search, so blocks never overlap. Once the
+///search has hashed `WORK_PER_NODE` windows per node, only shorter blocks are tried.
+pub fn repeated_subgraphs(
+    model: &Model,
+    summary: &ModelSummary,
+) -> Result<Vec<RepeatedSubgraph>, StatsError> {
+    let order = model
+        .traversal_order
+        .as_ref()
+        .ok_or(StatsError::MissingTraversalOrder)?;
+    let is_constant = |id: usize| model.nodes[id].op.op_group() == OpGroup::Constant;
+    let compute: Vec<usize> = order
+        .iter()
+        .copied()
+        .filter(|id| !model.inputs.contains(id) && !model.outputs.contains(id) && !is_constant(*id))
+        .collect();
+    let position: HashMap<usize, usize> =
+        compute.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+    //Nodes with the same op, attributes and weight shapes share a signature
+    let mut signature_ids: HashMap<String, u64> = HashMap::new();
+    let signatures: Vec<u64> = compute
+        .iter()
+        .map(|&id| {
+            let node = &model.nodes[id];
+            let mut signature = format!("{}\0{}\0{:?}", node.name, node.domain, node.attributes);
+            for &provider in node.providers.iter().filter(|&&p| is_constant(p)) {
+                for output in model.nodes[provider].outputs.iter() {
+                    let dims = summary
+                        .inferred_tensors
+                        .get(output)
+                        .map(|vi| &vi.dimensions);
+                    signature.push_str(&format!("\0{:?}", dims));
+                }
+            }
+            let next = signature_ids.len() as u64 + 1;
+            *signature_ids.entry(signature).or_insert(next)
+        })
+        .collect();
+
+    //Whether the block of `len` nodes at `b` is wired like the one at `a`. Providers from outside
+    //the blocks must pair up one to one, such as the previous layer, a shared mask or weights.
+    let same_wiring = |a: usize, b: usize, len: usize| {
+        let mut external: HashMap<usize, usize> = HashMap::new();
+        let mut external_rev: HashMap<usize, usize> = HashMap::new();
+        (0..len).all(|i| {
+            let x = &model.nodes[compute[a + i]];
+            let y = &model.nodes[compute[b + i]];
+            signatures[a + i] == signatures[b + i]
+                && x.providers.len() == y.providers.len()
+                && x.providers.iter().zip(y.providers.iter()).all(|(px, py)| {
+                    let inside = |p: &usize, start: usize| {
+                        position
+                            .get(p)
+                            .filter(|&&pos| pos >= start && pos < start + len)
+                            .map(|pos| pos - start)
+                    };
+                    match (inside(px, a), inside(py, b)) {
+                        (Some(i), Some(j)) => i == j,
+                        (None, None) => {
+                            is_constant(*px) == is_constant(*py)
+                                && *external.entry(*px).or_insert(*py) == *py
+                                && *external_rev.entry(*py).or_insert(*px) == *px
+                        }
+                        _ => false,
+                    }
+                })
+        })
+    };
+
+    //Segments keep their best run, so only the pieces left around a block are searched again
+    let mut budget = WORK_PER_NODE * compute.len();
+    let mut search = |from: usize, to: usize| {
+        let n = to - from;
+        let max_len = MAX_BLOCK_LEN.min(n / 2).min(budget / n.max(1));
+        budget -= max_len * n;
+        best_run(&signatures[from..to], max_len, |a, b, len| {
+            same_wiring(from + a, from + b, len)
+        })
+        .map(|(len, count, start)| (len, count, from + start))
+    };
+
+    let mut blocks = vec![];
+    let mut segments = vec![(0, compute.len(), search(0, compute.len()))];
+    loop {
+        let candidate = segments
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &(_, _, run))| run.map(|run| (i, run)))
+            .max_by_key(|&(_, (len, count, _))| len * count);
+        let (segment, (len, count, start)) = match candidate {
+            Some(candidate) => candidate,
+            None => break,
+        };
+        let (from, to, _) = segments.remove(segment);
+        for (from, to) in [(from, start), (start + len * count, to)] {
+            segments.push((from, to, search(from, to)));
+        }
+        blocks.push(RepeatedSubgraph {
+            instances: (0..count)
+                .map(|k| compute[start + k * len..start + (k + 1) * len].to_vec())
+                .collect(),
+        });
+    }
+    blocks.sort_by_key(|b| std::cmp::Reverse(b.len() * b.count()));
+    Ok(blocks)
+}
+
+///Block covering the most of `signatures` when repeated back to back, as its length, number of
+///repetitions and start, for blocks of up to `max_len` nodes. Runs are compared by a rolling
+///hash, then checked by `matches`.
+fn best_run(
+    signatures: &[u64],
+    max_len: usize,
+    matches: impl Fn(usize, usize, usize) -> bool,
+) -> Option<(usize, usize, usize)> {
+    const BASE: u64 = 1_000_003;
+    let mut best: Option<(usize, usize, usize)> = None;
+    for len in 2..=max_len {
+        let high = (1..len).fold(1u64, |acc, _| acc.wrapping_mul(BASE));
+        let mut hash = signatures[..len]
+            .iter()
+            .fold(0u64, |acc, &s| acc.wrapping_mul(BASE).wrapping_add(s));
+        let mut hashes = vec![hash];
+        for i in len..signatures.len() {
+            hash = hash
+                .wrapping_sub(signatures[i - len].wrapping_mul(high))
+                .wrapping_mul(BASE)
+                .wrapping_add(signatures[i]);
+            hashes.push(hash);
+        }
+
+        //Back to back repetitions of the window at each start, counted from the end
+        let mut runs = vec![1; hashes.len()];
+        for start in (0..hashes.len().saturating_sub(len)).rev() {
+            if hashes[start + len] == hashes[start] {
+                runs[start] = runs[start + len] + 1;
+            }
+        }
+
+        for (start, &count) in runs.iter().enumerate() {
+            let coverage = |count: usize| match best {
+                Some((best_len, best_count, _)) => count * len > best_count * best_len,
+                None => true,
+            };
+            if count < 2 || !coverage(count) {
+                continue;
+            }
+            //Only runs which would win are checked, as checking is linear in their size
+            let verified = (1..count)
+                .take_while(|k| matches(start, start + k * len, len))
+                .count()
+                + 1;
+            if verified > 1 && coverage(verified) {
+                best = Some((len, verified, start));
+            }
+        }
+    }
+    best
+}
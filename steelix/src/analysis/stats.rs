@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ir::{Model, OpGroup};
+
+use super::RepeatedSubgraph;
+
+#[derive(thiserror::Error, Debug)]
+pub enum StatsError {
+    #[error("Traversal order has not been built.")]
+    MissingTraversalOrder,
+}
+
+///Ops which merge a skip connection back into the main branch. Multiplicative merges, as in
+///Swish or squeeze-and-excitation, are gates rather than skips.
+const SKIP_MERGES: &[&str] = &["Add", "Sum", "Concat"];
+
+///An edge which skips over other nodes, such as the identity branch of a residual block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResidualConnection {
+    pub from: usize,
+    pub to: usize,
+    pub span: usize, //nodes on the longest path skipped by the connection
+}
+
+///Topology of the graph, ignoring constants
+#[derive(Debug, Clone, Default)]
+pub struct GraphStats {
+    pub nodes: usize, //compute nodes, excluding graph inputs and outputs
+    pub inputs: usize,
+    pub outputs: usize,
+    pub constants: usize,
+    pub edges: usize,
+    pub depth: usize,
+    pub max_fan_in: Option<(usize, usize)>, //node id and number of providers
+    pub max_fan_out: Option<(usize, usize)>, //node id and number of consumers
+    pub branch_points: Vec<usize>,          //nodes with more than one consumer
+    pub merge_points: Vec<usize>,           //nodes with more than one provider
+    pub residuals: Vec<ResidualConnection>,
+    pub repeated_block: Option<RepeatedSubgraph>, //largest of the repeated subgraphs
+}
+
+///Topology of the model, with the largest of the `repeats` found by `repeated_subgraphs`
+pub fn graph_stats(model: &Model, repeats: &[RepeatedSubgraph]) -> Result<GraphStats, StatsError> {
+    let order = model
+        .traversal_order
+        .as_ref()
+        .ok_or(StatsError::MissingTraversalOrder)?;
+    let reachable: HashSet<usize> = order.iter().copied().collect();
+    let is_constant = |id: usize| model.nodes[id].op.op_group() == OpGroup::Constant;
+    let is_compute =
+        |id: usize| !model.inputs.contains(&id) && !model.outputs.contains(&id) && !is_constant(id);
+    let providers = |id: usize| -> Vec<usize> {
+        let mut providers: Vec<usize> = model.nodes[id]
+            .providers
+            .iter()
+            .copied()
+            .filter(|&p| !is_constant(p))
+            .collect();
+        providers.sort_unstable();
+        providers.dedup();
+        providers
+    };
+    let consumers = |id: usize| -> Vec<usize> {
+        let mut consumers: Vec<usize> = model.nodes[id]
+            .consumers
+            .iter()
+            .copied()
+            .filter(|c| reachable.contains(c))
+            .collect();
+        consumers.sort_unstable();
+        consumers.dedup();
+        consumers
+    };
+
+    let mut stats = GraphStats {
+        inputs: model.inputs.len(),
+        outputs: model.outputs.len(),
+        ..Default::default()
+    };
+    let mut levels: HashMap<usize, usize> = HashMap::new();
+    for &id in order.iter() {
+        if is_constant(id) {
+            stats.constants += 1;
+            continue;
+        }
+        let providers = providers(id);
+        let consumers = consumers(id);
+        stats.edges += providers.len();
+
+        if is_compute(id) {
+            stats.nodes += 1;
+            let level = providers
+                .iter()
+                .filter_map(|p| levels.get(p).map(|l| l + 1))
+                .max()
+                .unwrap_or(0);
+            levels.insert(id, level);
+            stats.depth = stats.depth.max(level + 1);
+        }
+        if stats.max_fan_in.map_or(true, |(_, n)| providers.len() > n) {
+            stats.max_fan_in = Some((id, providers.len()));
+        }
+        if stats.max_fan_out.map_or(true, |(_, n)| consumers.len() > n) {
+            stats.max_fan_out = Some((id, consumers.len()));
+        }
+        if consumers.len() > 1 {
+            stats.branch_points.push(id);
+        }
+        if providers.len() > 1 {
+            stats.merge_points.push(id);
+        }
+    }
+
+    //An edge from a branch point to an Add, Sum or Concat is a skip connection if there is a
+    //longer path between the two
+    let position: HashMap<usize, usize> =
+        order.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    for &from in stats.branch_points.iter() {
+        for to in consumers(from) {
+            if !stats.merge_points.contains(&to) || !SKIP_MERGES.contains(&&*model.nodes[to].name) {
+                continue;
+            }
+            //Longest path from `from` to each node, in edges
+            let mut dist: HashMap<usize, usize> = HashMap::from([(from, 0)]);
+            for &id in order[position[&from] + 1..=position[&to]].iter() {
+                if let Some(d) = providers(id).iter().filter_map(|p| dist.get(p)).max() {
+                    dist.insert(id, d + 1);
+                }
+            }
+            if dist[&to] > 1 {
+                stats.residuals.push(ResidualConnection {
+                    from,
+                    to,
+                    span: dist[&to] - 1,
+                });
+            }
+        }
+    }
+
+    stats.repeated_block = repeats.first().cloned();
+    Ok(stats)
+}
@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use crate::ir::{Model, ModelSummary, OpGroup};
+
+use super::cost::node_flops;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ShardingError {
+    #[error("The degree of tensor parallelism must be at least 1.")]
+    InvalidDegree,
+    #[error("Traversal order has not been built.")]
+    MissingTraversalOrder,
+}
+
+///Ops which are applied independently to each element, and so preserve a sharded layout
+const ELEMENTWISE: &[&str] = &[
+    "Abs",
+    "Add",
+    "Clip",
+    "Cos",
+    "Div",
+    "Dropout",
+    "Elu",
+    "Erf",
+    "Exp",
+    "Gelu",
+    "HardSigmoid",
+    "HardSwish",
+    "LeakyRelu",
+    "Log",
+    "Mul",
+    "Neg",
+    "Not",
+    "Pow",
+    "Relu",
+    "Sigmoid",
+    "Sin",
+    "Sqrt",
+    "Sub",
+    "Sum",
+    "Tanh",
+];
+
+///How a node is executed across devices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShardKind {
+    ///Weight split along the output features, producing a sharded output
+    Column,
+    ///Weight split along the input features of a sharded input, producing partial sums
+    Row,
+    ///Elementwise op applied to each shard
+    Elementwise,
+    ///Every device computes the whole node
+    Replicated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CollectiveKind {
+    AllReduce,
+    AllGather,
+}
+
+///Communication required between devices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collective {
+    pub kind: CollectiveKind,
+    pub node_id: usize, //node producing the tensor
+    pub tensor: String,
+    pub bytes: usize, //size of the whole tensor
+}
+
+impl Collective {
+    ///Bytes each device sends when implemented as a ring
+    pub fn bytes_per_device(&self, degree: usize) -> f64 {
+        let ring = (degree - 1) as f64 / degree as f64 * self.bytes as f64;
+        match self.kind {
+            CollectiveKind::AllReduce => 2. * ring, //reduce-scatter then all-gather
+            CollectiveKind::AllGather => ring,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TensorParallelPlan {
+    pub degree: usize,
+    pub shards: HashMap<usize, ShardKind>, //strategy of every compute node
+    pub collectives: Vec<Collective>,
+    pub flops_per_device: usize,
+    pub param_bytes_per_device: usize,
+    pub total_flops: usize,
+    pub total_param_bytes: usize,
+}
+
+impl TensorParallelPlan {
+    pub fn traffic_per_device(&self) -> f64 {
+        self.collectives
+            .iter()
+            .map(|c| c.bytes_per_device(self.degree))
+            .sum()
+    }
+}
+
+///Shards MatMul, Gemm and Conv nodes with constant weights across `degree` devices, as in
+///Megatron-LM: a column parallel node followed by a row parallel node needs a single all-reduce.
+///
+///Elementwise ops keep tensors sharded, any other op gathers its sharded inputs first.
+///Nodes whose features are not divisible by the degree are replicated.
+pub fn shard_tensor_parallel(
+    model: &Model,
+    summary: &ModelSummary,
+    degree: usize,
+) -> Result<TensorParallelPlan, ShardingError> {
+    if degree == 0 {
+        return Err(ShardingError::InvalidDegree);
+    }
+    let order = model
+        .traversal_order
+        .as_ref()
+        .ok_or(ShardingError::MissingTraversalOrder)?;
+    let is_constant = |id: usize| model.nodes[id].op.op_group() == OpGroup::Constant;
+    let bytes = |tensor: &str| {
+        summary
+            .inferred_tensors
+            .get(tensor)
+            .map_or(0, |vi| vi.size_in_bytes())
+    };
+    let dims = |tensor: &str| {
+        summary
+            .inferred_tensors
+            .get(tensor)
+            .map(|vi| &vi.dimensions)
+    };
+    //The feature axis a node's tensor would be split along
+    let feature_axis = |tensor: &str, op: &str| {
+        let rank = dims(tensor)?.len();
+        match op {
+            "Conv" => (rank > 1).then(|| 1),
+            _ => rank.checked_sub(1),
+        }
+    };
+    let features = |tensor: &str, op: &str| dims(tensor)?.get(feature_axis(tensor, op)?).copied();
+
+    let mut sharded = HashMap::new(); //whether each node's output is sharded
+    let mut sharded_axis = HashMap::new(); //the axis sharded outputs are split along
+    let mut shards = HashMap::new();
+    let mut collectives = vec![];
+    //Whether every consumer of a constant holds only a shard of it
+    let mut constant_sharded: HashMap<usize, bool> = HashMap::new();
+    let mut flops_per_device = 0;
+    let mut total_flops = 0;
+
+    let gather = |sharded: &HashMap<usize, bool>, collectives: &mut Vec<Collective>, node_id| {
+        for (producer, tensor) in model.input_tensors(node_id) {
+            if sharded.get(&producer) == Some(&true)
+                && !collectives.iter().any(|c: &Collective| c.tensor == tensor)
+            {
+                collectives.push(Collective {
+                    kind: CollectiveKind::AllGather,
+                    node_id: producer,
+                    bytes: bytes(&tensor),
+                    tensor,
+                });
+            }
+        }
+    };
+
+    for &node_id in order.iter() {
+        if model.outputs.contains(&node_id) {
+            gather(&sharded, &mut collectives, node_id);
+            continue;
+        }
+        if model.inputs.contains(&node_id) || is_constant(node_id) {
+            continue;
+        }
+        let node = &model.nodes[node_id];
+        let op = &*node.name;
+        let activations: Vec<usize> = node
+            .providers
+            .iter()
+            .copied()
+            .filter(|&p| !is_constant(p))
+            .collect();
+        let is_sharded = |id: &usize| sharded.get(id) == Some(&true);
+        let output = node.outputs.first().map(String::as_str).unwrap_or_default();
+        let input = model
+            .input_tensors(node_id)
+            .into_iter()
+            .next()
+            .map(|(_, tensor)| tensor)
+            .unwrap_or_default();
+        let divisible = |tensor: &str| features(tensor, op).map_or(false, |f| f % degree == 0);
+
+        let has_weight = node.providers.get(1).map_or(false, |&p| is_constant(p));
+        let kind = if matches!(op, "MatMul" | "Gemm" | "Conv") && has_weight {
+            match node.providers.first() {
+                Some(p) if is_sharded(p) && divisible(&input) => ShardKind::Row,
+                Some(p) if !is_sharded(p) && divisible(output) => ShardKind::Column,
+                _ => ShardKind::Replicated,
+            }
+        } else if ELEMENTWISE.contains(&op)
+            && !activations.is_empty()
+            && activations.iter().all(is_sharded)
+        {
+            ShardKind::Elementwise
+        } else {
+            ShardKind::Replicated
+        };
+
+        match kind {
+            ShardKind::Row => collectives.push(Collective {
+                kind: CollectiveKind::AllReduce,
+                node_id,
+                tensor: output.to_string(),
+                bytes: bytes(output),
+            }),
+            ShardKind::Replicated => gather(&sharded, &mut collectives, node_id),
+            _ => {}
+        }
+        let axis = match kind {
+            ShardKind::Column => feature_axis(output, op),
+            ShardKind::Elementwise => activations
+                .iter()
+                .find_map(|p| sharded_axis.get(p).copied()),
+            _ => None,
+        };
+        //Whether a constant broadcast against the output has the whole sharded axis.
+        //Scalars and vectors broadcast along the axis are read in full by every device.
+        let has_axis = |constant: usize| {
+            let (axis, output_dims) = match (axis, dims(output)) {
+                (Some(axis), Some(output_dims)) => (axis, output_dims),
+                _ => return false,
+            };
+            let constant_dims = match model.nodes[constant].outputs.first().and_then(|t| dims(t)) {
+                Some(constant_dims) => constant_dims,
+                None => return false,
+            };
+            let offset = output_dims.len().saturating_sub(constant_dims.len());
+            axis >= offset
+                && output_dims[axis] > 1
+                && constant_dims[axis - offset] == output_dims[axis]
+        };
+        for (idx, &provider) in node.providers.iter().enumerate() {
+            if is_constant(provider) {
+                //The bias of a row parallel node is added once the partial sums are reduced
+                let split = match kind {
+                    ShardKind::Column => true,
+                    ShardKind::Elementwise => has_axis(provider),
+                    ShardKind::Row => idx == 1,
+                    ShardKind::Replicated => false,
+                };
+                *constant_sharded.entry(provider).or_insert(true) &= split;
+            }
+        }
+        if let Some(axis) = axis {
+            sharded_axis.insert(node_id, axis);
+        }
+
+        let flops = node_flops(summary, node_id);
+        total_flops += flops;
+        flops_per_device += match kind {
+            ShardKind::Replicated => flops,
+            _ => flops / degree,
+        };
+        sharded.insert(
+            node_id,
+            matches!(kind, ShardKind::Column | ShardKind::Elementwise),
+        );
+        shards.insert(node_id, kind);
+    }
+
+    let mut param_bytes_per_device = 0;
+    let mut total_param_bytes = 0;
+    for (&constant, &split) in constant_sharded.iter() {
+        let size: usize = model.nodes[constant].outputs.iter().map(|t| bytes(t)).sum();
+        total_param_bytes += size;
+        param_bytes_per_device += if split { size / degree } else { size };
+    }
+
+    Ok(TensorParallelPlan {
+        degree,
+        shards,
+        collectives,
+        flops_per_device,
+        param_bytes_per_device,
+        total_flops,
+        total_param_bytes,
+    })
+}
@@ -1,8 +1,53 @@
 use clap::{Arg, Command};
 
+fn weights_arg() -> Arg<'static> {
+    Arg::new("WEIGHTS")
+        .long("weights")
+        .help("How initializer data is loaded. `shapes-only` never reads large weights.")
+        .value_parser(["eager", "lazy", "shapes-only"])
+        .default_value("eager")
+        .takes_value(true)
+}
+
+fn keep_going_arg() -> Arg<'static> {
+    Arg::new("KEEP_GOING")
+        .long("keep-going")
+        .takes_value(false)
+        .help("Finish the analysis past failing nodes, reporting every diagnostic at the end.")
+}
+
+fn strict_arg() -> Arg<'static> {
+    Arg::new("STRICT")
+        .long("strict")
+        .takes_value(false)
+        .help("Exit with an error if any error was reported. Warnings do not count.")
+}
+
+fn edit_command(name: &'static str, about: &'static str) -> Command<'static> {
+    Command::new(name)
+        .about(about)
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("MODEL_PATH")
+                .short('m')
+                .long("model-path")
+                .help("Path to ONNX file to be edited.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::new("OUTPUT_PATH")
+                .short('o')
+                .long("output-path")
+                .help("Path where the edited ONNX file will be created.")
+                .takes_value(true)
+                .required(true),
+        )
+}
+
 pub fn build_cli() -> Command<'static> {
     let plot_subcommand = Command::new("plot")
-        .about("Plotter to plot ONNX files as SVG")
+        .about("Plotter to plot ONNX files as SVG or interactive HTML")
         .arg_required_else_help(true)
         .arg(
             Arg::new("MODEL_PATH")
@@ -24,14 +69,129 @@ pub fn build_cli() -> Command<'static> {
                 .takes_value(false)
                 .help("Open the SVG in the browser."),
         )
+        .arg(
+            Arg::new("CRITICAL_PATH")
+                .long("critical-path")
+                .takes_value(false)
+                .help("Highlight the critical path, weighted by FLOPs."),
+        )
         .arg(
             Arg::new("OUTPUT_PATH")
                 .short('o')
                 .long("output-path")
-                .help("Path where the SVG will be created")
-                .default_value("model.svg")
+                .help("Path where the plot will be created, model.<format> by default.")
                 .takes_value(true),
-        );
+        )
+        .arg(
+            Arg::new("FORMAT")
+                .long("format")
+                .help("svg, html (interactive viewer), mermaid, graphml, gexf or dot.")
+                .takes_value(true)
+                .value_parser(["svg", "html", "mermaid", "graphml", "gexf", "dot"])
+                .default_value("svg"),
+        )
+        .arg(
+            Arg::new("RENDERER")
+                .long("renderer")
+                .help("Lay the graph out with the builtin renderer, or with Graphviz dot.")
+                .takes_value(true)
+                .value_parser(["builtin", "dot"])
+                .default_value("builtin"),
+        )
+        .arg(
+            Arg::new("COLOR_BY")
+                .long("color-by")
+                .help("Color nodes by op group, or on a heat scale of their cost.")
+                .takes_value(true)
+                .value_parser(["group", "flops", "params", "bytes", "latency"])
+                .default_value("group"),
+        )
+        .arg(
+            Arg::new("THEME")
+                .long("theme")
+                .help("Colors, shapes and fonts of the plot: light, dark, print, or a TOML or JSON theme file.")
+                .takes_value(true)
+                .default_value("light"),
+        )
+        .arg(
+            Arg::new("DEVICE")
+                .long("device")
+                .help("Device profile for --color-by latency.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("CLUSTER")
+                .long("cluster")
+                .takes_value(false)
+                .help("Group nodes into nested clusters by the scopes of their names, e.g. /encoder/layer.3/."),
+        )
+        .arg(
+            Arg::new("COLLAPSE_DEPTH")
+                .long("collapse-depth")
+                .help("Collapse every name scope this many levels deep into a single node.")
+                .takes_value(true)
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("COLLAPSE_REPEATS")
+                .long("collapse-repeats")
+                .takes_value(false)
+                .help("Draw blocks repeated back to back, such as transformer layers, once with a repeat count."),
+        )
+        .arg(
+            Arg::new("CONSTANTS")
+                .long("constants")
+                .help("Show weights and constants listed on the nodes reading them, or as nodes of their own.")
+                .takes_value(true)
+                .value_parser(["hidden", "inline", "nodes"])
+                .default_value("hidden"),
+        )
+        .arg(
+            Arg::new("ATTRIBUTES")
+                .long("attributes")
+                .takes_value(false)
+                .help("Add key attributes, such as kernel_shape, strides, axis and perm, to node labels."),
+        )
+        .arg(
+            Arg::new("FROM")
+                .long("from")
+                .help("Only plot nodes downstream of this node or tensor. May be repeated.")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("TO")
+                .long("to")
+                .help("Only plot nodes upstream of this node or tensor. May be repeated.")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("AROUND")
+                .long("around")
+                .help("Only plot the nodes near this node or tensor.")
+                .takes_value(true)
+                .conflicts_with_all(&["FROM", "TO"]),
+        )
+        .arg(
+            Arg::new("DEPTH")
+                .long("depth")
+                .help("How many edges away from --around nodes are plotted.")
+                .takes_value(true)
+                .requires("AROUND")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("2"),
+        )
+        .arg(
+            Arg::new("SCALE_EDGES")
+                .long("scale-edges")
+                .takes_value(false)
+                .conflicts_with("DISABLE_SHAPES")
+                .help("Draw edges thicker the more bytes their tensor holds."),
+        )
+        .arg(weights_arg())
+        .arg(keep_going_arg())
+        .arg(strict_arg());
 
     let summary_command = Command::new("summary")
         .about("Summary of model operations and their cost")
@@ -42,6 +202,249 @@ pub fn build_cli() -> Command<'static> {
                 .help("Path to ONNX file for inference.")
                 .takes_value(true)
                 .required(true),
+        )
+        .arg(
+            Arg::new("FORMAT")
+                .long("format")
+                .help("Output format: table, json, csv (one row per node) or markdown.")
+                .takes_value(true)
+                .value_parser(["table", "json", "csv", "markdown"])
+                .default_value("table"),
+        )
+        .arg(weights_arg())
+        .arg(keep_going_arg())
+        .arg(strict_arg());
+
+    let infer_shapes_command = Command::new("infer-shapes")
+        .about("Write inferred shapes into the value_info of a copy of the model")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("MODEL_PATH")
+                .short('m')
+                .long("model-path")
+                .help("Path to ONNX file to be annotated.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::new("OUTPUT_PATH")
+                .short('o')
+                .long("output-path")
+                .help("Path where the annotated ONNX file will be created.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(weights_arg())
+        .arg(keep_going_arg())
+        .arg(strict_arg());
+
+    let edit_command = Command::new("edit")
+        .about("Edit a copy of the model")
+        .arg_required_else_help(true)
+        .subcommand_required(true)
+        .subcommand(
+            edit_command("set-shape", "Change the shapes of graph inputs")
+                .arg(
+                    Arg::new("SHAPE")
+                        .long("shape")
+                        .help("New shape of an input, e.g. `input=N,3,224,224`. `?` marks an unknown dimension.")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                )
+                .arg(
+                    Arg::new("BATCH_SIZE")
+                        .long("batch-size")
+                        .help("New leading dimension of every graph input and output.")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            edit_command("rename", "Rename tensors and nodes")
+                .arg(
+                    Arg::new("TENSOR")
+                        .long("tensor")
+                        .help("Tensor to rename, as `old=new`.")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                )
+                .arg(
+                    Arg::new("NODE")
+                        .long("node")
+                        .help("Node to rename, as `old=new`.")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                ),
+        )
+        .subcommand(
+            edit_command(
+                "extract",
+                "Extract the subgraph between a set of input and output tensors",
+            )
+            .arg(
+                Arg::new("INPUTS")
+                    .long("inputs")
+                    .help("Comma separated tensors which become the inputs of the new model.")
+                    .takes_value(true)
+                    .use_value_delimiter(true)
+                    .required(true),
+            )
+            .arg(
+                Arg::new("OUTPUTS")
+                    .long("outputs")
+                    .help("Comma separated tensors which become the outputs of the new model.")
+                    .takes_value(true)
+                    .use_value_delimiter(true)
+                    .required(true),
+            )
+            .arg(weights_arg())
+            .arg(keep_going_arg()),
+        );
+
+    let pipeline_command = Command::new("pipeline")
+        .about("Split the model into balanced pipeline-parallel stages")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("MODEL_PATH")
+                .short('m')
+                .long("model-path")
+                .help("Path to ONNX file to be partitioned.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::new("STAGES")
+                .short('n')
+                .long("stages")
+                .help("Number of pipeline stages.")
+                .value_parser(clap::value_parser!(usize))
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::new("BALANCE")
+                .long("balance")
+                .help("Cost the stages are balanced by.")
+                .value_parser(["flops", "param-bytes", "latency"])
+                .default_value("flops")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("DEVICE")
+                .long("device")
+                .help("Device profile of every stage, or of each stage if repeated once per stage.")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("OUTPUT_DIR")
+                .short('o')
+                .long("output-dir")
+                .help("Directory in which each stage is written as its own ONNX file.")
+                .takes_value(true),
+        )
+        .arg(weights_arg())
+        .arg(keep_going_arg())
+        .arg(strict_arg());
+
+    let shard_command = Command::new("shard")
+        .about("Estimate the cost of tensor-parallel sharding across devices")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("MODEL_PATH")
+                .short('m')
+                .long("model-path")
+                .help("Path to ONNX file to be sharded.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::new("DEGREE")
+                .short('d')
+                .long("degree")
+                .help("Number of devices each sharded node is split across.")
+                .value_parser(clap::value_parser!(usize))
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(weights_arg())
+        .arg(keep_going_arg())
+        .arg(strict_arg());
+
+    let critical_path_command = Command::new("critical-path")
+        .about("Critical path, depth and width of the model, and the speed-up from running ops concurrently")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("MODEL_PATH")
+                .short('m')
+                .long("model-path")
+                .help("Path to ONNX file to be analyzed.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::new("DEVICE")
+                .long("device")
+                .help("Weight nodes by their estimated latency on this device, rather than FLOPs.")
+                .takes_value(true),
+        )
+        .arg(weights_arg())
+        .arg(keep_going_arg())
+        .arg(strict_arg());
+
+    let stats_command = Command::new("stats")
+        .about("Graph statistics: depth, fan-in and fan-out, branches, residual connections and repeated blocks")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("MODEL_PATH")
+                .short('m')
+                .long("model-path")
+                .help("Path to ONNX file to be analyzed.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(weights_arg())
+        .arg(keep_going_arg())
+        .arg(strict_arg());
+
+    let fingerprint_command = Command::new("fingerprint")
+        .about("Structural hash of each model and the known architecture it resembles, to find files holding the same network")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("MODEL_PATH")
+                .short('m')
+                .long("model-path")
+                .help("Path to ONNX file to be analyzed, repeat to compare several models.")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .required(true),
+        )
+        .arg(weights_arg())
+        .arg(keep_going_arg())
+        .arg(strict_arg());
+
+    let print_command = Command::new("print")
+        .about("Print the model in the ONNX textual syntax")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("MODEL_PATH")
+                .short('m')
+                .long("model-path")
+                .help("Path to ONNX file to be printed.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::new("OUTPUT_PATH")
+                .short('o')
+                .long("output-path")
+                .help("Write the text to this file rather than stdout.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("FULL")
+                .long("full")
+                .takes_value(false)
+                .help("Print the values of every initializer, however large."),
         );
 
     Command::new("steelix")
@@ -52,6 +455,14 @@ pub fn build_cli() -> Command<'static> {
         )
         .subcommand(plot_subcommand)
         .subcommand(summary_command)
+        .subcommand(infer_shapes_command)
+        .subcommand(edit_command)
+        .subcommand(pipeline_command)
+        .subcommand(shard_command)
+        .subcommand(critical_path_command)
+        .subcommand(stats_command)
+        .subcommand(fingerprint_command)
+        .subcommand(print_command)
         .subcommand_required(true)
         .arg_required_else_help(true)
 }
@@ -0,0 +1,144 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::ir::{Model, ModelSummary, OpCost, OpGroup};
+
+use super::{dtype_name, RenderableGraph, TensorReport};
+
+///Attributes worth reading off the plot, in the order they are listed
+const KEY_ATTRIBUTES: &[&str] = &[
+    "kernel_shape",
+    "strides",
+    "pads",
+    "dilations",
+    "group",
+    "axis",
+    "axes",
+    "perm",
+];
+
+///How the plot shows initializers and Constant nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstantDisplay {
+    Hidden,
+    Inline, //listed on the node reading them
+    Nodes,  //drawn as nodes of their own
+}
+
+impl FromStr for ConstantDisplay {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hidden" => Ok(ConstantDisplay::Hidden),
+            "inline" => Ok(ConstantDisplay::Inline),
+            "nodes" => Ok(ConstantDisplay::Nodes),
+            _ => Err(anyhow::anyhow!("Unknown constant display: {}", s)),
+        }
+    }
+}
+
+///Name of the tensor, with its dtype and shape when known, e.g. `w: f32 768x3072`
+fn describe_tensor(summary: Option<&ModelSummary>, name: &str) -> String {
+    match summary.and_then(|s| s.inferred_tensors.get(name)) {
+        Some(vi) => format!(
+            "{}: {} {}",
+            name,
+            dtype_name(vi.dt),
+            TensorReport::from(vi).shape_string()
+        ),
+        None => name.to_string(),
+    }
+}
+
+impl RenderableGraph {
+    ///Shows the constants read by each drawn node. Inline, they are listed below the label of
+    ///the node with their dtype and shape. As nodes, each is drawn beside its consumers and
+    ///nested in the scope of the first.
+    pub fn show_constants(
+        &mut self,
+        model: &Model,
+        summary: Option<&ModelSummary>,
+        display: ConstantDisplay,
+    ) {
+        let mut drawn: Vec<(usize, usize)> =
+            self.node_ids.iter().map(|(&id, &nd)| (id, nd)).collect();
+        drawn.sort_unstable();
+        let is_constant = |id: &usize| model.nodes[*id].op.op_group() == OpGroup::Constant;
+
+        match display {
+            ConstantDisplay::Hidden => {}
+            ConstantDisplay::Inline => {
+                for (id, nd) in drawn {
+                    let node = &mut self.nodes[nd];
+                    for &provider in model.nodes[id].providers.iter().filter(|p| is_constant(p)) {
+                        node.constants.extend(
+                            model.nodes[provider]
+                                .outputs
+                                .iter()
+                                .map(|t| describe_tensor(summary, t)),
+                        );
+                    }
+                    if !node.constants.is_empty() {
+                        node.add_attribute(("shape", "record".to_string()));
+                    }
+                }
+            }
+            ConstantDisplay::Nodes => {
+                let mut constant_nds: HashMap<usize, usize> = HashMap::new();
+                let style = self.theme.style(Some(OpGroup::Constant), "Constant");
+                for (id, nd) in drawn {
+                    for &provider in model.nodes[id].providers.iter().filter(|p| is_constant(p)) {
+                        let constant = &model.nodes[provider];
+                        let from = match constant_nds.get(&provider) {
+                            Some(&from) => from,
+                            None => {
+                                let scope = self.nodes[nd].scope.clone();
+                                let label = constant
+                                    .outputs
+                                    .iter()
+                                    .map(|t| describe_tensor(summary, t))
+                                    .collect::<Vec<_>>()
+                                    .join("\n");
+                                let node = self.create_node(label);
+                                node.op_type = "Constant".to_string();
+                                node.name = constant.outputs.join(", ");
+                                node.scope = scope;
+                                //Weights count towards the parameters of their consumers
+                                node.cost = summary.map(|_| OpCost::default());
+                                node.apply_style(&style);
+                                let from = node.id;
+                                constant_nds.insert(provider, from);
+                                from
+                            }
+                        };
+                        let tensor = constant
+                            .outputs
+                            .iter()
+                            .find(|t| model.nodes[id].inputs.contains(t));
+                        let vi = tensor.and_then(|t| summary?.inferred_tensors.get(t));
+                        let label = vi.map_or_else(String::new, |vi| vi.dimensions.to_string());
+                        self.create_edge(label, from, nd);
+                        let edge = self.edges.last_mut().unwrap();
+                        edge.tensor = tensor.cloned().unwrap_or_default();
+                        edge.bytes = vi.map(|vi| vi.size_in_bytes());
+                    }
+                }
+                self.node_ids.extend(constant_nds);
+            }
+        }
+    }
+
+    ///Adds the key attributes of each drawn node, such as strides and axes, to its label
+    pub fn label_attributes(&mut self, model: &Model) {
+        for (&id, &nd) in self.node_ids.iter() {
+            let attributes = &model.nodes[id].attributes;
+            for key in KEY_ATTRIBUTES.iter() {
+                if let Some((_, value)) = attributes.iter().find(|(name, _)| name == key) {
+                    let label = &mut self.nodes[nd].label;
+                    label.push('\n');
+                    label.push_str(&format!("{}={}", key, value));
+                }
+            }
+        }
+    }
+}
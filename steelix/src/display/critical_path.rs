@@ -0,0 +1,89 @@
+use human_repr::{HumanCount, HumanDuration};
+
+use crate::{
+    analysis::{CriticalPath, PathWeight},
+    ir::Model,
+};
+use tabled::{object::Rows, Alignment, Modify, Panel, Style, Table, Tabled};
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct ParallelismEntry {
+    metric: String,
+    value: String,
+}
+
+pub fn parallelism_table(critical_path: &CriticalPath, weight: &PathWeight) -> Table {
+    let cost = |cost: f64| match weight {
+        PathWeight::Flops => (cost as usize).human_count_bare().to_string(),
+        PathWeight::Latency(_) => cost.human_duration().to_string(),
+    };
+    let widths = critical_path
+        .level_widths
+        .iter()
+        .map(|w| w.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let entries = vec![
+        ParallelismEntry {
+            metric: "Critical path".to_string(),
+            value: cost(critical_path.path_cost),
+        },
+        ParallelismEntry {
+            metric: "Total".to_string(),
+            value: cost(critical_path.total_cost),
+        },
+        ParallelismEntry {
+            metric: "Depth".to_string(),
+            value: critical_path.depth().to_string(),
+        },
+        ParallelismEntry {
+            metric: "Max width".to_string(),
+            value: critical_path.max_width().to_string(),
+        },
+        ParallelismEntry {
+            metric: "Width per level".to_string(),
+            value: widths,
+        },
+    ];
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(format!(
+            "{:.2}x theoretical speed-up from inter-op parallelism",
+            critical_path.speedup()
+        )))
+        .to_owned()
+}
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct PathEntry {
+    level: usize,
+    op: String,
+    node: String,
+}
+
+pub fn critical_path_table(model: &Model, critical_path: &CriticalPath) -> Table {
+    let entries: Vec<PathEntry> = critical_path
+        .path
+        .iter()
+        .map(|id| PathEntry {
+            level: critical_path.levels[id],
+            op: model.nodes[*id].name.clone(),
+            node: model.nodes[*id].onnx_name.clone(),
+        })
+        .collect();
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(format!(
+            "{} nodes on the critical path",
+            critical_path.path.len()
+        )))
+        .to_owned()
+}
@@ -55,19 +55,20 @@ impl Display for Iterations {
 }
 
 impl Device {
-    pub fn calculate_its(&self, dt: DType, flops: usize) -> Result<Iterations, DeviceError> {
-        let flops_per_sec = match dt {
-            DType::I8 => self.stats.tops,
-            DType::F16 => self.stats.half,
-            DType::F32 => self.stats.single,
-            DType::F64 => self.stats.double,
-            _ => {
-                return Err(DeviceError::NumberFormatError(
-                    "Invalid data type provided.".to_string(),
-                ))
-            }
-        };
+    pub fn flops_per_sec(&self, dt: DType) -> Result<usize, DeviceError> {
+        match dt {
+            DType::I8 => Ok(self.stats.tops),
+            DType::F16 => Ok(self.stats.half),
+            DType::F32 => Ok(self.stats.single),
+            DType::F64 => Ok(self.stats.double),
+            _ => Err(DeviceError::NumberFormatError(
+                "Invalid data type provided.".to_string(),
+            )),
+        }
+    }
 
+    pub fn calculate_its(&self, dt: DType, flops: usize) -> Result<Iterations, DeviceError> {
+        let flops_per_sec = self.flops_per_sec(dt)?;
         Ok(Iterations((flops_per_sec / flops) as f64))
     }
 }
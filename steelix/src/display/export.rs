@@ -0,0 +1,281 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use super::{escape_xml, Edge, Node, RenderableGraph, Scope};
+
+///Shape carried by an edge, empty without shape inference
+fn edge_shape(label: &str) -> &str {
+    label.trim()
+}
+
+///Mermaid only accepts entities inside quoted labels
+fn mermaid_label(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('\n', "<br>")
+}
+
+///Mermaid flowchart of the graph, for embedding in Markdown
+pub fn to_mermaid(graph: &RenderableGraph) -> String {
+    let mut out = String::from("flowchart TD\n");
+    //Nodes drawn alike share a class, keyed by its style
+    let mut classes: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for node in graph.nodes.iter() {
+        let label = mermaid_label(&node.text());
+        match node.attribute("shape") {
+            Some("box" | "record" | "rect" | "octagon") => {
+                writeln!(out, "    N{}[\"{}\"]", node.id, label)
+            }
+            Some("diamond") => writeln!(out, "    N{}{{\"{}\"}}", node.id, label),
+            Some("hexagon") => writeln!(out, "    N{}{{{{\"{}\"}}}}", node.id, label),
+            Some("parallelogram") => writeln!(out, "    N{}[/\"{}\"/]", node.id, label),
+            Some("trapezium") => writeln!(out, "    N{}[/\"{}\"\\]", node.id, label),
+            _ => writeln!(out, "    N{}([\"{}\"])", node.id, label),
+        }
+        .unwrap();
+        let attribute = |name: &str, default: &'static str| node.attribute(name).unwrap_or(default);
+        let mut style = format!(
+            "fill:{},stroke:{},color:{}",
+            attribute("fillcolor", "white"),
+            attribute("color", "black"),
+            attribute("fontcolor", "black")
+        );
+        if attribute("style", "").contains("dashed") {
+            style.push_str(",stroke-dasharray:5 3");
+        }
+        classes.entry(style).or_default().push(node.id);
+    }
+    for edge in graph.edges.iter() {
+        let shape = edge_shape(&edge.label);
+        if shape.is_empty() {
+            writeln!(out, "    N{} --> N{}", edge.from, edge.to).unwrap();
+        } else {
+            writeln!(
+                out,
+                "    N{} -->|\"{}\"| N{}",
+                edge.from,
+                mermaid_label(shape),
+                edge.to
+            )
+            .unwrap();
+        }
+    }
+
+    mermaid_subgraphs(&mut out, &graph.scopes(), &mut 0, 1);
+    for (i, (style, ids)) in classes.iter().enumerate() {
+        writeln!(out, "    classDef group{} {}", i, style).unwrap();
+        let ids: Vec<String> = ids.iter().map(|id| format!("N{}", id)).collect();
+        writeln!(out, "    class {} group{}", ids.join(","), i).unwrap();
+    }
+    for node in graph.nodes.iter().filter(|n| n.highlighted) {
+        writeln!(out, "    style N{} stroke:red,stroke-width:3px", node.id).unwrap();
+    }
+    for (i, _) in graph
+        .edges
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.highlighted)
+    {
+        writeln!(out, "    linkStyle {} stroke:red,stroke-width:3px", i).unwrap();
+    }
+    out
+}
+
+///Nested scopes as subgraphs, which Mermaid lets readers collapse
+fn mermaid_subgraphs(out: &mut String, scope: &Scope, subgraphs: &mut usize, indent: usize) {
+    let pad = "    ".repeat(indent);
+    for child in scope.children.iter() {
+        writeln!(
+            out,
+            "{}subgraph S{}[\"{}\"]",
+            pad,
+            subgraphs,
+            mermaid_label(&child.name)
+        )
+        .unwrap();
+        *subgraphs += 1;
+        for nd in child.nodes.iter() {
+            writeln!(out, "{}    N{}", pad, nd).unwrap();
+        }
+        mermaid_subgraphs(out, child, subgraphs, indent + 1);
+        writeln!(out, "{}end", pad).unwrap();
+    }
+}
+
+///Attributes exported with each node and edge, typed by `NODE_KEYS` and `EDGE_KEYS`
+type Attributes = Vec<(&'static str, String)>;
+
+fn node_attributes(node: &Node) -> Attributes {
+    let mut attributes = vec![
+        ("label", node.label.clone()),
+        ("op_type", node.op_type.clone()),
+        ("name", node.name.clone()),
+        ("highlighted", node.highlighted.to_string()),
+    ];
+    if !node.scope.is_empty() {
+        attributes.push(("scope", node.scope.join("/")));
+    }
+    if !node.constants.is_empty() {
+        attributes.push(("constants", node.constants.join("; ")));
+    }
+    if let Some(cost) = node.cost {
+        attributes.push(("flops", cost.flops.to_string()));
+        attributes.push(("params", cost.parameters.to_string()));
+    }
+    attributes
+}
+
+fn edge_attributes(edge: &Edge) -> Attributes {
+    let mut attributes = vec![
+        ("tensor", edge.tensor.clone()),
+        ("highlighted", edge.highlighted.to_string()),
+    ];
+    let shape = edge_shape(&edge.label);
+    if !shape.is_empty() {
+        attributes.push(("shape", shape.to_string()));
+    }
+    if let Some(bytes) = edge.bytes {
+        attributes.push(("bytes", bytes.to_string()));
+    }
+    attributes
+}
+
+const NODE_KEYS: &[(&str, &str)] = &[
+    ("label", "string"),
+    ("op_type", "string"),
+    ("name", "string"),
+    ("highlighted", "boolean"),
+    ("scope", "string"),
+    ("constants", "string"),
+    ("flops", "long"),
+    ("params", "long"),
+];
+
+const EDGE_KEYS: &[(&str, &str)] = &[
+    ("tensor", "string"),
+    ("highlighted", "boolean"),
+    ("shape", "string"),
+    ("bytes", "long"),
+];
+
+///GraphML document of the graph, readable by networkx and yEd.
+///
+///Nodes carry their label, op type, ONNX name, name scope, the constants listed on them and,
+///with shape inference, FLOPs and parameters.
+///Edges carry the name, shape and size in bytes of their tensor.
+pub fn to_graphml(graph: &RenderableGraph) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+    );
+    for (owner, keys) in [("node", NODE_KEYS), ("edge", EDGE_KEYS)] {
+        for (name, ty) in keys.iter() {
+            writeln!(
+                out,
+                "  <key id=\"{}_{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                owner, name, owner, name, ty
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("  <graph id=\"model\" edgedefault=\"directed\">\n");
+    for node in graph.nodes.iter() {
+        writeln!(out, "    <node id=\"N{}\">", node.id).unwrap();
+        for (name, value) in node_attributes(node) {
+            writeln!(
+                out,
+                "      <data key=\"node_{}\">{}</data>",
+                name,
+                escape_xml(&value)
+            )
+            .unwrap();
+        }
+        out.push_str("    </node>\n");
+    }
+    for (i, edge) in graph.edges.iter().enumerate() {
+        writeln!(
+            out,
+            "    <edge id=\"e{}\" source=\"N{}\" target=\"N{}\">",
+            i, edge.from, edge.to
+        )
+        .unwrap();
+        for (name, value) in edge_attributes(edge) {
+            writeln!(
+                out,
+                "      <data key=\"edge_{}\">{}</data>",
+                name,
+                escape_xml(&value)
+            )
+            .unwrap();
+        }
+        out.push_str("    </edge>\n");
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+///GEXF document of the graph, readable by Gephi and networkx, with the attributes of
+///`to_graphml`
+pub fn to_gexf(graph: &RenderableGraph) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n",
+    );
+    out.push_str("  <meta>\n    <creator>steelix</creator>\n  </meta>\n");
+    out.push_str("  <graph mode=\"static\" defaultedgetype=\"directed\">\n");
+    for (class, keys) in [("node", NODE_KEYS), ("edge", EDGE_KEYS)] {
+        writeln!(out, "    <attributes class=\"{}\">", class).unwrap();
+        for (name, ty) in keys.iter() {
+            writeln!(
+                out,
+                "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>",
+                name, name, ty
+            )
+            .unwrap();
+        }
+        out.push_str("    </attributes>\n");
+    }
+
+    let attvalues = |out: &mut String, attributes: Attributes| {
+        out.push_str("        <attvalues>\n");
+        for (name, value) in attributes {
+            writeln!(
+                out,
+                "          <attvalue for=\"{}\" value=\"{}\"/>",
+                name,
+                escape_xml(&value)
+            )
+            .unwrap();
+        }
+        out.push_str("        </attvalues>\n");
+    };
+    out.push_str("    <nodes>\n");
+    for node in graph.nodes.iter() {
+        writeln!(
+            out,
+            "      <node id=\"N{}\" label=\"{}\">",
+            node.id,
+            escape_xml(&node.label)
+        )
+        .unwrap();
+        attvalues(&mut out, node_attributes(node));
+        out.push_str("      </node>\n");
+    }
+    out.push_str("    </nodes>\n    <edges>\n");
+    for (i, edge) in graph.edges.iter().enumerate() {
+        writeln!(
+            out,
+            "      <edge id=\"{}\" source=\"N{}\" target=\"N{}\" label=\"{}\">",
+            i,
+            edge.from,
+            edge.to,
+            escape_xml(edge_shape(&edge.label))
+        )
+        .unwrap();
+        attvalues(&mut out, edge_attributes(edge));
+        out.push_str("      </edge>\n");
+    }
+    out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+    out
+}
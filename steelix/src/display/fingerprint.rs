@@ -0,0 +1,82 @@
+use crate::analysis::Fingerprint;
+use tabled::{object::Rows, Alignment, Modify, Panel, Style, Table, Tabled};
+
+///Score below which a model is not said to look like any known architecture
+const MIN_MATCH: f64 = 0.5;
+
+///Number of signatures listed in the summary
+const TOP_MATCHES: usize = 3;
+
+pub fn describe_match(fingerprint: &Fingerprint) -> String {
+    match fingerprint.best_match() {
+        Some(best) if best.score >= MIN_MATCH => {
+            format!("looks like {} ({:.0}% match)", best.name, best.score * 100.)
+        }
+        _ => "no known architecture matched".to_string(),
+    }
+}
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct MatchEntry {
+    architecture: String,
+    #[tabled(rename = "Match")]
+    score: String,
+}
+
+pub fn fingerprint_table(fingerprint: &Fingerprint) -> Table {
+    let entries: Vec<MatchEntry> = fingerprint
+        .matches
+        .iter()
+        .take(TOP_MATCHES)
+        .map(|m| MatchEntry {
+            architecture: m.name.to_string(),
+            score: format!("{:.0}%", m.score * 100.),
+        })
+        .collect();
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(format!(
+            "Structure {}, {}",
+            &fingerprint.hash[..12],
+            describe_match(fingerprint)
+        )))
+        .to_owned()
+}
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct StructureEntry {
+    model: String,
+    structure_hash: String,
+    architecture: String,
+}
+
+///Compares the structure of several models, keyed by path
+pub fn structures_table(fingerprints: &[(String, Fingerprint)]) -> Table {
+    let entries: Vec<StructureEntry> = fingerprints
+        .iter()
+        .map(|(model, fingerprint)| StructureEntry {
+            model: model.clone(),
+            structure_hash: fingerprint.hash.clone(),
+            architecture: describe_match(fingerprint),
+        })
+        .collect();
+    let mut hashes: Vec<&str> = fingerprints.iter().map(|(_, f)| &*f.hash).collect();
+    hashes.sort_unstable();
+    hashes.dedup();
+    let footer = match hashes.len() {
+        1 if fingerprints.len() > 1 => "Every model has the same structure".to_string(),
+        n => format!("{} distinct structures", n),
+    };
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(footer))
+        .to_owned()
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use human_repr::{HumanCount, HumanDuration};
+
+use crate::{
+    analysis::{node_metric, CostMetric, ThroughputError},
+    ir::{Model, ModelSummary},
+};
+
+use super::RenderableGraph;
+
+///Yellow to dark red, from ColorBrewer's YlOrRd
+const HEAT_RAMP: [[u8; 3]; 8] = [
+    [0xff, 0xff, 0xcc],
+    [0xff, 0xed, 0xa0],
+    [0xfe, 0xd9, 0x76],
+    [0xfe, 0xb2, 0x4c],
+    [0xfd, 0x8d, 0x3c],
+    [0xfc, 0x4e, 0x2a],
+    [0xe3, 0x1a, 0x1c],
+    [0xb1, 0x00, 0x26],
+];
+
+///Fill of nodes which cost nothing under the metric
+const NO_COST: &str = "white";
+
+const MAX_EDGE_WIDTH: f64 = 8.;
+
+///Color of `t` in [0, 1] along the heat ramp, as a hex string
+pub fn heat_color(t: f64) -> String {
+    let t = t.clamp(0., 1.) * (HEAT_RAMP.len() - 1) as f64;
+    let i = (t.floor() as usize).min(HEAT_RAMP.len() - 2);
+    let f = t - i as f64;
+    let (a, b) = (HEAT_RAMP[i], HEAT_RAMP[i + 1]);
+    let channel = |c: usize| (a[c] as f64 + (b[c] as f64 - a[c] as f64) * f).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(0), channel(1), channel(2))
+}
+
+///Evenly spaced colors of the ramp, for drawing legends
+pub(crate) fn heat_stops(n: usize) -> Vec<String> {
+    (0..n)
+        .map(|i| heat_color(i as f64 / (n.max(2) - 1) as f64))
+        .collect()
+}
+
+///Scale of the node colors, with its extremes already formatted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Legend {
+    pub title: String,
+    pub min: String,
+    pub max: String,
+}
+
+fn format_metric(value: f64, metric: &CostMetric) -> String {
+    match metric {
+        CostMetric::Flops | CostMetric::Params => (value as usize).human_count_bare().to_string(),
+        CostMetric::Bytes => (value as usize).human_count_bytes().to_string(),
+        CostMetric::Latency(_) => value.human_duration().to_string(),
+    }
+}
+
+impl RenderableGraph {
+    ///Fills compute nodes with a heat color of their cost, on a log scale since costs
+    ///span orders of magnitude. Inputs and outputs keep their group color.
+    pub fn color_by_cost(
+        &mut self,
+        model: &Model,
+        summary: &ModelSummary,
+        metric: &CostMetric,
+    ) -> Result<(), ThroughputError> {
+        //Collapsed scopes stand for several model nodes, and show their total
+        let mut values: HashMap<usize, f64> = HashMap::new();
+        for (&id, &nd) in self.node_ids.iter() {
+            if model.inputs.contains(&id) || model.outputs.contains(&id) {
+                continue;
+            }
+            *values.entry(nd).or_default() += node_metric(model, summary, id, metric)?;
+        }
+
+        let positive = values.values().copied().filter(|&v| v > 0.);
+        let lo = positive.clone().fold(f64::INFINITY, f64::min);
+        let hi = positive.fold(0., f64::max);
+        for (nd, value) in values {
+            let color = if value > 0. {
+                let t = if hi > lo {
+                    (value.ln() - lo.ln()) / (hi.ln() - lo.ln())
+                } else {
+                    1.
+                };
+                heat_color(t)
+            } else {
+                NO_COST.to_string()
+            };
+            //Heat colors are light, so text stays dark whatever the theme
+            self.nodes[nd].add_attribute(("fillcolor", color));
+            self.nodes[nd].add_attribute(("fontcolor", "black".to_string()));
+        }
+
+        self.legend = (hi > 0.).then(|| Legend {
+            title: format!("{} (log scale)", metric.title()),
+            min: format_metric(lo, metric),
+            max: format_metric(hi, metric),
+        });
+        Ok(())
+    }
+
+    ///Widens each edge with the square root of the bytes it carries, relative to the largest
+    ///tensor. Edges of unknown size keep the default width.
+    pub fn scale_edges(&mut self) {
+        let max = self.edges.iter().filter_map(|e| e.bytes).max().unwrap_or(0);
+        if max == 0 {
+            return;
+        }
+        for edge in self.edges.iter_mut() {
+            if let Some(bytes) = edge.bytes {
+                edge.width = 1. + (MAX_EDGE_WIDTH - 1.) * (bytes as f64 / max as f64).sqrt();
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use serde::Serialize;
+
+use crate::ir::{Model, ModelSummary, OpGroup};
+
+use super::{dtype_name, escape_xml, Node, RenderableGraph, TensorReport};
+
+const VIEWER_TEMPLATE: &str = include_str!("viewer.html");
+
+///A tensor read or written by a node, dtype and shape are absent without shape inference
+#[derive(Debug, Clone, Serialize)]
+struct TensorDetails {
+    name: String,
+    dtype: Option<String>,
+    shape: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct InitializerDetails {
+    name: String,
+    dtype: Option<String>,
+    shape: Option<String>,
+    elements: Option<usize>,
+    bytes: Option<usize>,
+}
+
+///Everything the inspector panel shows for a node, keyed by the id of the node in the SVG
+#[derive(Debug, Clone, Serialize)]
+struct NodeDetails {
+    id: String,
+    name: String,
+    op_type: String,
+    domain: String,
+    attributes: Vec<(String, String)>,
+    inputs: Vec<TensorDetails>,
+    outputs: Vec<TensorDetails>,
+    initializers: Vec<InitializerDetails>,
+    flops: Option<usize>,
+    params: Option<usize>,
+}
+
+fn node_details(
+    model: &Model,
+    summary: Option<&ModelSummary>,
+    id: usize,
+    nd: usize,
+) -> NodeDetails {
+    let node = &model.nodes[id];
+    let tensor = |name: &String| {
+        let report = summary
+            .and_then(|s| s.inferred_tensors.get(name))
+            .map(TensorReport::from);
+        TensorDetails {
+            name: name.clone(),
+            dtype: report.as_ref().map(|r| r.dtype.clone()),
+            shape: report.as_ref().map(TensorReport::shape_string),
+        }
+    };
+
+    let initializers = node
+        .providers
+        .iter()
+        .map(|&p| &model.nodes[p])
+        .filter(|provider| provider.op.op_group() == OpGroup::Constant)
+        .flat_map(|provider| provider.outputs.iter())
+        .map(|name| {
+            let vi = summary.and_then(|s| s.inferred_tensors.get(name));
+            InitializerDetails {
+                name: name.clone(),
+                dtype: vi.map(|vi| dtype_name(vi.dt)),
+                shape: vi.map(|vi| TensorReport::from(vi).shape_string()),
+                elements: vi.map(|vi| vi.dimensions.iter().product()),
+                bytes: vi.map(|vi| vi.size_in_bytes()),
+            }
+        })
+        .collect();
+
+    let cost = summary.and_then(|s| s.node_costs.get(&id));
+    NodeDetails {
+        id: format!("N{}", nd),
+        name: node.onnx_name.clone(),
+        op_type: node.name.clone(),
+        domain: node.domain.clone(),
+        attributes: node.attributes.clone(),
+        inputs: node
+            .inputs
+            .iter()
+            .filter(|i| !i.is_empty())
+            .map(tensor)
+            .collect(),
+        outputs: node.outputs.iter().map(tensor).collect(),
+        initializers,
+        flops: cost.map(|c| c.flops),
+        params: cost.map(|c| c.parameters),
+    }
+}
+
+///A collapsed name scope only has the total cost of its nodes
+fn scope_details(node: &Node) -> NodeDetails {
+    NodeDetails {
+        id: format!("N{}", node.id),
+        name: node.name.clone(),
+        op_type: node.op_type.clone(),
+        domain: String::new(),
+        attributes: vec![],
+        inputs: vec![],
+        outputs: vec![],
+        initializers: vec![],
+        flops: node.cost.map(|c| c.flops),
+        params: node.cost.map(|c| c.parameters),
+    }
+}
+
+///Wraps a rendered SVG of the graph into a single offline HTML page, with pan and zoom, search
+///by node name or op type, and a panel showing the details of the clicked node.
+///
+///Nodes of the SVG are matched to the model through their `<title>`, which holds the DOT id of
+///the node with either renderer.
+pub fn render_html(
+    svg: &str,
+    title: &str,
+    graph: &RenderableGraph,
+    model: &Model,
+    summary: Option<&ModelSummary>,
+) -> String {
+    let mut ids: Vec<(&usize, &usize)> = graph.node_ids.iter().collect();
+    ids.sort_unstable_by_key(|&(&id, &nd)| (nd, id));
+    ids.dedup_by_key(|(_, nd)| **nd);
+    let details: Vec<NodeDetails> = ids
+        .into_iter()
+        .map(|(&id, &nd)| match &graph.nodes[nd] {
+            scope if scope.op_type == "Scope" => scope_details(scope),
+            _ => node_details(model, summary, id, nd),
+        })
+        .collect();
+    //A closing tag inside the data would end the script element early
+    let details = serde_json::to_string(&details)
+        .expect("Node details are always serializable.")
+        .replace("</", "<\\/");
+
+    //The XML prolog and doctype are not allowed inside HTML
+    let svg = svg.find("<svg").map_or(svg, |start| &svg[start..]);
+    fill_template(
+        VIEWER_TEMPLATE,
+        &[
+            ("{{TITLE}}", &escape_xml(title)),
+            ("{{SVG}}", svg),
+            ("{{NODES}}", &details),
+        ],
+    )
+}
+
+///Substitutes each placeholder in a single pass, so substituted text is never scanned again
+fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match values.iter().find(|(key, _)| rest.starts_with(key)) {
+            Some((key, value)) => {
+                out.push_str(value);
+                rest = &rest[key.len()..];
+            }
+            None => {
+                out.push_str("{{");
+                rest = &rest[2..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,537 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+};
+
+use super::RenderableGraph;
+
+pub(crate) const FONT_SIZE: f64 = 14.;
+pub(crate) const LINE_HEIGHT: f64 = 18.;
+const CHAR_WIDTH: f64 = 7.8; //average advance of the sans-serif font at FONT_SIZE
+const RANK_SEP: f64 = 48.;
+const NODE_SEP: f64 = 28.;
+const DUMMY_WIDTH: f64 = 16.; //room for edges running past a layer
+const MARGIN: f64 = 12.;
+pub(crate) const CLUSTER_PAD: f64 = 8.; //between a cluster and its contents
+pub(crate) const CLUSTER_LABEL_HEIGHT: f64 = 16.;
+const ORDERING_SWEEPS: usize = 16;
+const POSITIONING_SWEEPS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+///Box of a node, around its center
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeBox {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgePath {
+    pub points: Vec<Point>, //from the source to the target
+}
+
+impl EdgePath {
+    ///Middle of the path, where the label is drawn
+    pub fn midpoint(&self) -> Point {
+        let mid = self.points.len() / 2;
+        let (a, b) = (self.points[mid - 1], self.points[mid]);
+        Point {
+            x: (a.x + b.x) / 2.,
+            y: (a.y + b.y) / 2.,
+        }
+    }
+}
+
+///Positions of the nodes and edges of a `RenderableGraph`, indexed like the graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub nodes: Vec<NodeBox>,
+    pub edges: Vec<EdgePath>,
+    pub width: f64,
+    pub height: f64,
+}
+
+///Size of a node, from its label and shape
+pub(crate) fn node_size(label: &str, shape: &str) -> (f64, f64) {
+    let lines = label.lines().count().max(1) as f64;
+    let text_width =
+        label.lines().map(|l| l.chars().count()).max().unwrap_or(0) as f64 * CHAR_WIDTH;
+    let text_height = lines * LINE_HEIGHT;
+    match shape {
+        "box" | "record" | "rect" => ((text_width + 20.).max(54.), text_height + 16.),
+        //The text fits in the middle half of a diamond
+        "diamond" => ((text_width * 2. + 8.).max(54.), (text_height * 2.).max(36.)),
+        _ => (
+            (text_width * std::f64::consts::SQRT_2 + 8.).max(54.),
+            (text_height * std::f64::consts::SQRT_2).max(36.),
+        ),
+    }
+}
+
+struct Vertex {
+    width: f64,
+    height: f64,
+    layer: usize,
+    x: f64,
+    up: Vec<usize>,     //neighbours in the layer above
+    down: Vec<usize>,   //neighbours in the layer below
+    scope: Vec<String>, //keys of the clusters the vertex is drawn in, see `cluster_keys`
+}
+
+///Lays the graph out top to bottom in layers, as in the Sugiyama framework:
+///
+///1. edges closing a cycle are reversed, so the graph is acyclic
+///2. nodes are assigned to layers by longest path, edges spanning several layers are split by
+///   dummy vertices
+///3. layers are reordered by the barycenter of their neighbours to reduce crossings, then
+///   nodes of the same cluster are made adjacent
+///4. nodes are placed as close as possible to their neighbours without overlapping
+pub fn layout(graph: &RenderableGraph) -> Layout {
+    let n = graph.nodes.len();
+    let edges: Vec<(usize, usize)> = graph.edges.iter().map(|e| (e.from, e.to)).collect();
+    let reversed = back_edges(n, &edges);
+    let dag: Vec<(usize, usize)> = edges
+        .iter()
+        .zip(reversed.iter())
+        .map(|(&(from, to), &rev)| if rev { (to, from) } else { (from, to) })
+        .collect();
+    let layers = assign_layers(n, &dag);
+
+    let mut vertices: Vec<Vertex> = graph
+        .nodes
+        .iter()
+        .zip(layers.iter())
+        .zip(graph.cluster_keys())
+        .map(|((node, &layer), scope)| {
+            let shape = node.attribute("shape").unwrap_or("ellipse");
+            let (width, height) = node_size(&node.text(), shape);
+            Vertex {
+                width,
+                height,
+                layer,
+                x: 0.,
+                up: vec![],
+                down: vec![],
+                scope,
+            }
+        })
+        .collect();
+
+    //Each edge becomes a chain of vertices, one per layer it crosses, from top to bottom
+    let chains: Vec<Vec<usize>> = dag
+        .iter()
+        .map(|&(from, to)| {
+            let mut chain = vec![from];
+            for layer in layers[from] + 1..layers[to] {
+                vertices.push(Vertex {
+                    width: DUMMY_WIDTH,
+                    height: 0.,
+                    layer,
+                    x: 0.,
+                    up: vec![],
+                    down: vec![],
+                    scope: vec![],
+                });
+                chain.push(vertices.len() - 1);
+            }
+            chain.push(to);
+            for pair in chain.windows(2) {
+                vertices[pair[0]].down.push(pair[1]);
+                vertices[pair[1]].up.push(pair[0]);
+            }
+            chain
+        })
+        .collect();
+
+    let n_layers = vertices.iter().map(|v| v.layer + 1).max().unwrap_or(0);
+    let mut order: Vec<Vec<usize>> = vec![vec![]; n_layers];
+    for (id, v) in vertices.iter().enumerate() {
+        order[v.layer].push(id);
+    }
+    minimize_crossings(&vertices, &mut order);
+    group_scopes(&vertices, &mut order);
+    assign_x(&mut vertices, &order);
+
+    //Layers are as tall as their tallest node, and far enough apart to close the clusters of
+    //one layer and open those of the next
+    let depth = vertices.iter().map(|v| v.scope.len()).max().unwrap_or(0);
+    let rank_sep = RANK_SEP + (2. * CLUSTER_PAD + CLUSTER_LABEL_HEIGHT) * depth as f64;
+    let mut layer_y = Vec::with_capacity(n_layers);
+    let mut y = MARGIN;
+    for layer in order.iter() {
+        let height = layer.iter().map(|&v| vertices[v].height).fold(0., f64::max);
+        layer_y.push(y + height / 2.);
+        y += height + rank_sep;
+    }
+    let height = (y - rank_sep + MARGIN).max(2. * MARGIN);
+
+    let min_x = vertices
+        .iter()
+        .map(|v| v.x - v.width / 2.)
+        .fold(f64::INFINITY, f64::min);
+    let shift = if min_x.is_finite() {
+        MARGIN - min_x
+    } else {
+        0.
+    };
+    for v in vertices.iter_mut() {
+        v.x += shift;
+    }
+    let width = vertices
+        .iter()
+        .map(|v| v.x + v.width / 2. + MARGIN)
+        .fold(2. * MARGIN, f64::max);
+
+    let nodes: Vec<NodeBox> = vertices[..n]
+        .iter()
+        .map(|v| NodeBox {
+            x: v.x,
+            y: layer_y[v.layer],
+            width: v.width,
+            height: v.height,
+        })
+        .collect();
+
+    let edges = chains
+        .iter()
+        .zip(reversed.iter())
+        .map(|(chain, &rev)| {
+            let (top, bottom) = (chain[0], chain[chain.len() - 1]);
+            let mut points = vec![Point {
+                x: vertices[top].x,
+                y: layer_y[vertices[top].layer] + vertices[top].height / 2.,
+            }];
+            points.extend(chain[1..chain.len() - 1].iter().map(|&d| Point {
+                x: vertices[d].x,
+                y: layer_y[vertices[d].layer],
+            }));
+            points.push(Point {
+                x: vertices[bottom].x,
+                y: layer_y[vertices[bottom].layer] - vertices[bottom].height / 2.,
+            });
+            if rev {
+                points.reverse();
+            }
+            EdgePath { points }
+        })
+        .collect();
+
+    Layout {
+        nodes,
+        edges,
+        width,
+        height,
+    }
+}
+
+///Edges which point back to a node on the current depth first search path
+fn back_edges(n: usize, edges: &[(usize, usize)]) -> Vec<bool> {
+    let mut out: Vec<Vec<(usize, usize)>> = vec![vec![]; n]; //target and edge index
+    for (idx, &(from, to)) in edges.iter().enumerate() {
+        out[from].push((to, idx));
+    }
+    let mut reversed = vec![false; edges.len()];
+    //0: unvisited, 1: on the stack, 2: done
+    let mut state = vec![0u8; n];
+    for root in 0..n {
+        if state[root] != 0 {
+            continue;
+        }
+        let mut stack = vec![(root, 0)];
+        state[root] = 1;
+        while let Some(&(v, next)) = stack.last() {
+            if let Some(&(to, idx)) = out[v].get(next) {
+                stack.last_mut().unwrap().1 += 1;
+                match state[to] {
+                    0 => {
+                        state[to] = 1;
+                        stack.push((to, 0));
+                    }
+                    1 => reversed[idx] = true,
+                    _ => {}
+                }
+            } else {
+                state[v] = 2;
+                stack.pop();
+            }
+        }
+    }
+    reversed
+}
+
+///Longest path layering, then sources are moved down to just above their first consumer so
+///inputs used late in the graph do not stretch long edges from the top
+fn assign_layers(n: usize, dag: &[(usize, usize)]) -> Vec<usize> {
+    let mut successors: Vec<Vec<usize>> = vec![vec![]; n];
+    let mut in_degree = vec![0; n];
+    for &(from, to) in dag.iter().filter(|(from, to)| from != to) {
+        successors[from].push(to);
+        in_degree[to] += 1;
+    }
+    let sources: Vec<usize> = (0..n).filter(|&v| in_degree[v] == 0).collect();
+
+    let mut layers = vec![0; n];
+    let mut queue: VecDeque<usize> = sources.iter().copied().collect();
+    while let Some(v) = queue.pop_front() {
+        for &s in successors[v].iter() {
+            layers[s] = layers[s].max(layers[v] + 1);
+            in_degree[s] -= 1;
+            if in_degree[s] == 0 {
+                queue.push_back(s);
+            }
+        }
+    }
+
+    for &source in sources.iter() {
+        if let Some(first) = successors[source].iter().map(|&s| layers[s]).min() {
+            layers[source] = first - 1;
+        }
+    }
+    layers
+}
+
+///Reorders each layer by the barycenter of its neighbours, sweeping down then up, and keeps
+///the ordering with the fewest crossings
+fn minimize_crossings(vertices: &[Vertex], order: &mut [Vec<usize>]) {
+    let mut position = vec![0.; vertices.len()];
+    let index = |order: &[Vec<usize>], position: &mut [f64]| {
+        for layer in order.iter() {
+            for (i, &v) in layer.iter().enumerate() {
+                position[v] = i as f64;
+            }
+        }
+    };
+    index(order, &mut position);
+    let mut best = order.to_vec();
+    let mut best_crossings = count_crossings(vertices, order, &position);
+
+    for sweep in 0..ORDERING_SWEEPS {
+        let downwards = sweep % 2 == 0;
+        let layers: Vec<usize> = if downwards {
+            (1..order.len()).collect()
+        } else {
+            (0..order.len().saturating_sub(1)).rev().collect()
+        };
+        for l in layers {
+            let barycenters: Vec<f64> = order[l]
+                .iter()
+                .map(|&v| {
+                    let neighbours = if downwards {
+                        &vertices[v].up
+                    } else {
+                        &vertices[v].down
+                    };
+                    if neighbours.is_empty() {
+                        position[v]
+                    } else {
+                        neighbours.iter().map(|&u| position[u]).sum::<f64>()
+                            / neighbours.len() as f64
+                    }
+                })
+                .collect();
+            let mut keyed: Vec<(f64, usize)> =
+                barycenters.into_iter().zip(order[l].clone()).collect();
+            keyed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+            order[l] = keyed.into_iter().map(|(_, v)| v).collect();
+            for (i, &v) in order[l].iter().enumerate() {
+                position[v] = i as f64;
+            }
+        }
+        let crossings = count_crossings(vertices, order, &position);
+        if crossings < best_crossings {
+            best_crossings = crossings;
+            best = order.to_vec();
+        }
+        if best_crossings == 0 {
+            break;
+        }
+    }
+    order.clone_from_slice(&best);
+}
+
+///Reorders each layer so the nodes of a scope are adjacent, with scopes placed at the mean
+///position of their nodes
+fn group_scopes(vertices: &[Vertex], order: &mut [Vec<usize>]) {
+    for layer in order.iter_mut() {
+        let mut positions: HashMap<&[String], (f64, usize)> = HashMap::new();
+        for (i, &v) in layer.iter().enumerate() {
+            let scope = &vertices[v].scope;
+            for depth in 1..=scope.len() {
+                let entry = positions.entry(&scope[..depth]).or_default();
+                entry.0 += i as f64;
+                entry.1 += 1;
+            }
+        }
+        //Outermost scope first, then the node itself
+        let mut keyed: Vec<(Vec<f64>, usize)> = layer
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                let scope = &vertices[v].scope;
+                let mut key: Vec<f64> = (1..=scope.len())
+                    .map(|depth| {
+                        let (sum, count) = positions[&scope[..depth]];
+                        sum / count as f64
+                    })
+                    .collect();
+                key.push(i as f64);
+                (key, v)
+            })
+            .collect();
+        keyed.sort_by(|a, b| {
+            a.0.iter()
+                .zip(b.0.iter())
+                .map(|(x, y)| x.partial_cmp(y).unwrap_or(Ordering::Equal))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| a.0.len().cmp(&b.0.len()))
+        });
+        *layer = keyed.into_iter().map(|(_, v)| v).collect();
+    }
+}
+
+///Crossings between consecutive layers, counted as inversions of the lower ends of the edges
+///sorted by their upper ends
+fn count_crossings(vertices: &[Vertex], order: &[Vec<usize>], position: &[f64]) -> usize {
+    let mut total = 0;
+    for layer in order.iter() {
+        let mut ends: Vec<(usize, usize)> = layer
+            .iter()
+            .flat_map(|&u| {
+                vertices[u]
+                    .down
+                    .iter()
+                    .map(move |&v| (position[u] as usize, position[v] as usize))
+            })
+            .collect();
+        ends.sort_unstable();
+        let lower: Vec<usize> = ends.into_iter().map(|(_, v)| v).collect();
+        total += inversions(lower);
+    }
+    total
+}
+
+fn inversions(mut values: Vec<usize>) -> usize {
+    if values.len() < 2 {
+        return 0;
+    }
+    let mut right = values.split_off(values.len() / 2);
+    let mut left = values;
+    let mut count = inversions(left.clone()) + inversions(right.clone());
+    left.sort_unstable();
+    right.sort_unstable();
+    let mut j = 0;
+    for &l in left.iter() {
+        while j < right.len() && right[j] < l {
+            j += 1;
+        }
+        count += j;
+    }
+    count
+}
+
+///Gap between neighbours of a layer, with room for the borders of the clusters between them
+fn separation(a: &Vertex, b: &Vertex) -> f64 {
+    let common = a
+        .scope
+        .iter()
+        .zip(b.scope.iter())
+        .take_while(|(x, y)| x == y)
+        .count();
+    NODE_SEP + CLUSTER_PAD * (a.scope.len() + b.scope.len() - 2 * common) as f64
+}
+
+///Places each vertex at the mean position of its neighbours, keeping the order of the layer
+///and the minimum separation, sweeping down, up, then using both layers
+fn assign_x(vertices: &mut [Vertex], order: &[Vec<usize>]) {
+    for layer in order.iter() {
+        let mut x = 0.;
+        for (i, &v) in layer.iter().enumerate() {
+            if i > 0 {
+                x += separation(&vertices[layer[i - 1]], &vertices[v]);
+            }
+            vertices[v].x = x + vertices[v].width / 2.;
+            x += vertices[v].width;
+        }
+    }
+
+    for sweep in 0..POSITIONING_SWEEPS {
+        let layers: Vec<usize> = if sweep % 2 == 0 {
+            (0..order.len()).collect()
+        } else {
+            (0..order.len()).rev().collect()
+        };
+        for l in layers {
+            let desired: Vec<f64> = order[l]
+                .iter()
+                .map(|&v| {
+                    let vertex = &vertices[v];
+                    let neighbours: Vec<usize> = if sweep + 1 == POSITIONING_SWEEPS {
+                        vertex
+                            .up
+                            .iter()
+                            .chain(vertex.down.iter())
+                            .copied()
+                            .collect()
+                    } else if sweep % 2 == 0 {
+                        vertex.up.clone()
+                    } else {
+                        vertex.down.clone()
+                    };
+                    if neighbours.is_empty() {
+                        vertex.x
+                    } else {
+                        neighbours.iter().map(|&u| vertices[u].x).sum::<f64>()
+                            / neighbours.len() as f64
+                    }
+                })
+                .collect();
+            let placed = place_layer(vertices, &order[l], &desired);
+            for (&v, x) in order[l].iter().zip(placed) {
+                vertices[v].x = x;
+            }
+        }
+    }
+}
+
+///Positions closest to `desired` in the least squares sense which keep the layer ordered and
+///separated. Subtracting the minimum offset of each vertex from the first turns the constraints
+///into a monotone sequence, fitted by pool adjacent violators.
+fn place_layer(vertices: &[Vertex], layer: &[usize], desired: &[f64]) -> Vec<f64> {
+    let mut offsets = Vec::with_capacity(layer.len());
+    let mut offset = 0.;
+    for (i, &v) in layer.iter().enumerate() {
+        if i > 0 {
+            let (u, v) = (&vertices[layer[i - 1]], &vertices[v]);
+            offset += (u.width + v.width) / 2. + separation(u, v);
+        }
+        offsets.push(offset);
+    }
+
+    //Blocks of (mean, size) which are placed together
+    let mut blocks: Vec<(f64, usize)> = vec![];
+    for (d, o) in desired.iter().zip(offsets.iter()) {
+        blocks.push((d - o, 1));
+        while blocks.len() > 1 && blocks[blocks.len() - 2].0 > blocks[blocks.len() - 1].0 {
+            let (mean_b, size_b) = blocks.pop().unwrap();
+            let (mean_a, size_a) = blocks.pop().unwrap();
+            let size = size_a + size_b;
+            blocks.push((
+                (mean_a * size_a as f64 + mean_b * size_b as f64) / size as f64,
+                size,
+            ));
+        }
+    }
+    blocks
+        .into_iter()
+        .flat_map(|(mean, size)| std::iter::repeat(mean).take(size))
+        .zip(offsets)
+        .map(|(base, offset)| base + offset)
+        .collect()
+}
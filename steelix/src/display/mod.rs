@@ -1,7 +1,36 @@
+mod constants;
+mod critical_path;
 mod device;
+mod export;
+mod fingerprint;
+mod heatmap;
+mod html;
+mod layout;
+mod pipeline;
 mod plotter;
+mod region;
+mod report;
+mod scope;
+mod sharding;
+mod stats;
 mod summary;
+mod svg;
+mod theme;
 
+pub use constants::*;
+pub use critical_path::*;
 pub use device::*;
+pub use export::*;
+pub use fingerprint::*;
+pub use heatmap::*;
+pub use html::*;
+pub use layout::*;
+pub use pipeline::*;
 pub use plotter::*;
+pub use report::*;
+pub use scope::*;
+pub use sharding::*;
+pub use stats::*;
 pub use summary::*;
+pub use svg::*;
+pub use theme::*;
@@ -0,0 +1,84 @@
+use human_repr::{HumanCount, HumanDuration};
+
+use crate::analysis::PipelinePlan;
+use tabled::{object::Rows, Alignment, Modify, Panel, Style, Table, Tabled};
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct StageEntry {
+    stage: usize,
+    device: String,
+    nodes: usize,
+    flops: String,
+    params: String,
+    latency: String,
+    receives: String,
+}
+
+pub fn pipeline_table(plan: &PipelinePlan) -> Table {
+    let entries: Vec<StageEntry> = plan
+        .stages
+        .iter()
+        .enumerate()
+        .map(|(idx, stage)| StageEntry {
+            stage: idx,
+            device: stage.device.clone().unwrap_or_else(|| "-".to_string()),
+            nodes: stage.nodes.len(),
+            flops: stage.flops.human_count_bare().to_string(),
+            params: stage.param_bytes.human_count_bytes().to_string(),
+            latency: stage
+                .latency
+                .map_or_else(|| "-".to_string(), |l| l.human_duration().to_string()),
+            receives: idx
+                .checked_sub(1)
+                .and_then(|prev| plan.cuts.get(prev))
+                .map_or(0, |cut| cut.bytes())
+                .human_count_bytes()
+                .to_string(),
+        })
+        .collect();
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(format!(
+            "{} stages balanced by {:?}",
+            plan.stages.len(),
+            plan.balance
+        )))
+        .to_owned()
+}
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct CutEntry {
+    cut: String,
+    tensor: String,
+    size: String,
+}
+
+pub fn cuts_table(plan: &PipelinePlan) -> Table {
+    let entries: Vec<CutEntry> = plan
+        .cuts
+        .iter()
+        .flat_map(|cut| {
+            cut.tensors.iter().map(move |(tensor, bytes)| CutEntry {
+                cut: format!("{} -> {}", cut.after_stage, cut.after_stage + 1),
+                tensor: tensor.clone(),
+                size: bytes.human_count_bytes().to_string(),
+            })
+        })
+        .collect();
+    let total: usize = plan.cuts.iter().map(|cut| cut.bytes()).sum();
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(format!(
+            "{} transferred per inference",
+            total.human_count_bytes()
+        )))
+        .to_owned()
+}
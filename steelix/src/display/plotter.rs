@@ -1,32 +1,117 @@
-use std::{collections::HashMap, io::Write};
+use std::{collections::HashMap, io::Write, str::FromStr};
 
-use crate::ir::{Model, ModelSummary, OpGroup, COLOUR_MAP, SHAPE_MAP};
+use crate::ir::{Model, ModelSummary, OpCost, OpGroup};
+
+use super::{escape_xml, heat_stops, name_scope, Legend, Repeat, Scope, Theme};
 
 type Nd = usize;
 
+///What `plot` writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlotFormat {
+    Svg,
+    Html, //interactive viewer around the SVG
+    Mermaid,
+    GraphMl,
+    Gexf,
+    Dot, //source, without running Graphviz
+}
+
+impl PlotFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PlotFormat::Svg => "svg",
+            PlotFormat::Html => "html",
+            PlotFormat::Mermaid => "mmd",
+            PlotFormat::GraphMl => "graphml",
+            PlotFormat::Gexf => "gexf",
+            PlotFormat::Dot => "dot",
+        }
+    }
+}
+
+///How the SVG is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Renderer {
+    Builtin,
+    Dot, //Graphviz, which must be installed
+}
+
+impl FromStr for Renderer {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "builtin" => Ok(Renderer::Builtin),
+            "dot" => Ok(Renderer::Dot),
+            _ => Err(anyhow::anyhow!("Unknown renderer: {}", s)),
+        }
+    }
+}
+
+impl FromStr for PlotFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "svg" => Ok(PlotFormat::Svg),
+            "html" => Ok(PlotFormat::Html),
+            "mermaid" => Ok(PlotFormat::Mermaid),
+            "graphml" => Ok(PlotFormat::GraphMl),
+            "gexf" => Ok(PlotFormat::Gexf),
+            "dot" => Ok(PlotFormat::Dot),
+            _ => Err(anyhow::anyhow!("Unknown plot format: {}", s)),
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct RenderableGraph {
     pub current_id: usize,
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
+    pub node_ids: HashMap<usize, Nd>, //model node id to rendered node id
+    pub legend: Option<Legend>,       //scale of the node colors, when they show a cost
+    pub clustered: bool,              //nodes are grouped by name scope
+    pub repeats: Vec<Repeat>,         //blocks drawn once for all their repetitions
+    pub theme: Theme,
 }
 
 #[derive(Clone, Debug)]
 pub struct Edge {
-    label: String, //COW
-    from: usize,
-    to: usize,
+    pub label: String, //COW
+    pub from: usize,
+    pub to: usize,
+    pub highlighted: bool,
+    pub tensor: String,       //name of the tensor carried by the edge, if known
+    pub bytes: Option<usize>, //size of the tensor, None without shape inference
+    pub width: f64,
 }
 
 impl Edge {
     pub fn new(label: String, from: usize, to: usize) -> Self {
-        Self { label, from, to }
+        Self {
+            label,
+            from,
+            to,
+            highlighted: false,
+            tensor: String::new(),
+            bytes: None,
+            width: 1.,
+        }
     }
 }
 
 impl RenderableGraph {
     pub fn new() -> Self {
-        Self::default()
+        Self::with_theme(Theme::light())
+    }
+
+    pub fn with_theme(theme: Theme) -> Self {
+        Self {
+            theme,
+            ..Self::default()
+        }
     }
 
     pub fn create_node(&mut self, label: String) -> &mut Node {
@@ -34,6 +119,12 @@ impl RenderableGraph {
             id: self.current_id,
             label,
             attributes: None,
+            highlighted: false,
+            op_type: String::new(),
+            name: String::new(),
+            cost: None,
+            scope: vec![],
+            constants: vec![],
         };
         self.nodes.push(n);
         self.current_id += 1;
@@ -44,106 +135,343 @@ impl RenderableGraph {
         self.edges.push(Edge::new(label, from, to));
     }
 
-    pub fn build_graph(model: Model, model_summary: Option<ModelSummary>) -> Self {
-        let mut g = RenderableGraph::new();
-        let mut offset = 0;
+    pub fn build_graph(model: &Model, model_summary: Option<&ModelSummary>) -> Self {
+        Self::build_graph_with(model, model_summary, &Theme::light())
+    }
+
+    ///Builds the graph with nodes styled by the theme. Ops steelix cannot analyse are flagged
+    ///with a warning sign.
+    pub fn build_graph_with(
+        model: &Model,
+        model_summary: Option<&ModelSummary>,
+        theme: &Theme,
+    ) -> Self {
+        let mut g = RenderableGraph::with_theme(theme.clone());
 
+        //Constants are not drawn, so the remaining nodes are renumbered
         for (op_idx, op_node) in model.nodes.iter().enumerate() {
             if op_node.op.op_group() == OpGroup::Constant {
-                offset += 1;
                 continue;
             }
 
-            let renderable_node = g.create_node(op_node.name.clone());
-            //TODO: move below to function
-            renderable_node.add_attribute((
-                "fillcolor",
-                COLOUR_MAP.get(&op_node.op.op_group()).unwrap_or(&"white"),
-            ));
-            renderable_node.add_attribute((
-                "shape",
-                SHAPE_MAP.get(&op_node.op.op_group()).unwrap_or(&"ellipse"),
-            ));
+            //Inputs and outputs are named after their tensor
+            let (op_type, name, scope) = if model.inputs.contains(&op_idx) {
+                ("Input".to_string(), op_node.name.clone(), vec![])
+            } else if model.outputs.contains(&op_idx) {
+                ("Output".to_string(), op_node.name.clone(), vec![])
+            } else {
+                let scope = name_scope(&op_node.onnx_name);
+                (op_node.name.clone(), op_node.onnx_name.clone(), scope)
+            };
+            //Outputs are placeholder ops, so they take no group style
+            let group = Some(op_node.op.op_group()).filter(|_| op_type != "Output");
+            let style = g.theme.style(group, &op_type);
+            let label = match group {
+                Some(OpGroup::Unimplemented) => format!("⚠ {}", op_node.name),
+                _ => op_node.name.clone(),
+            };
+
+            let renderable_node = g.create_node(label);
+            renderable_node.op_type = op_type;
+            renderable_node.name = name;
+            renderable_node.scope = scope;
+            renderable_node.cost =
+                model_summary.and_then(|summary| summary.node_costs.get(&op_idx).copied());
+            renderable_node.apply_style(&style);
+            let nd = renderable_node.id;
+            g.node_ids.insert(op_idx, nd);
+        }
+
+        for (op_idx, op_node) in model.nodes.iter().enumerate() {
+            if op_node.op.op_group() == OpGroup::Constant {
+                continue;
+            }
             op_node.providers.iter().for_each(|provider_id| {
                 if model.nodes[*provider_id].op.op_group() != OpGroup::Constant {
-                    let mut pid = *provider_id;
-                    let shape = if let Some(summary) = &model_summary {
-                        summary.output_shapes.get(&pid).unwrap().to_string()
+                    let pid = *provider_id;
+                    let shape = if let Some(summary) = model_summary {
+                        match summary.output_shapes.get(&pid) {
+                            Some(shape) if !summary.unknown_nodes.contains(&pid) => {
+                                shape.to_string()
+                            }
+                            _ => "  ?".to_string(),
+                        }
                     } else {
                         "".to_string()
                     };
-                    if *provider_id > offset {
-                        pid -= offset;
+                    g.create_edge(shape, g.node_ids[&pid], g.node_ids[&op_idx]);
+                    let tensor = model.nodes[pid]
+                        .outputs
+                        .iter()
+                        .find(|t| op_node.inputs.contains(t));
+                    if let (Some(tensor), Some(edge)) = (tensor, g.edges.last_mut()) {
+                        edge.tensor = tensor.clone();
+                        edge.bytes = model_summary
+                            .filter(|summary| !summary.unknown_nodes.contains(&pid))
+                            .and_then(|summary| summary.inferred_tensors.get(tensor))
+                            .map(|vi| vi.size_in_bytes());
                     }
-                    g.create_edge(shape, pid, op_idx - offset);
                 }
             });
         }
         g
     }
+
+    ///Marks a chain of model nodes, and the edges between them
+    pub fn highlight_path(&mut self, path: &[usize]) {
+        for id in path.iter() {
+            if let Some(&nd) = self.node_ids.get(id) {
+                self.nodes[nd].highlighted = true;
+            }
+        }
+        for pair in path.windows(2) {
+            if let (Some(&from), Some(&to)) =
+                (self.node_ids.get(&pair[0]), self.node_ids.get(&pair[1]))
+            {
+                self.edges
+                    .iter_mut()
+                    .filter(|e| e.from == from && e.to == to)
+                    .for_each(|e| e.highlighted = true);
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct Node {
     pub id: usize,
     pub label: String,
-    pub attributes: Option<HashMap<&'static str, &'static str>>,
+    pub attributes: Option<HashMap<&'static str, String>>,
+    pub highlighted: bool,
+    pub op_type: String,
+    pub name: String,           //name of the node in the ONNX graph, may be empty
+    pub cost: Option<OpCost>,   //None without shape inference
+    pub scope: Vec<String>,     //name scopes the node is nested in, outermost first
+    pub constants: Vec<String>, //weights and constants read by the node, listed below its label
 }
 
 impl Node {
-    pub fn add_attribute(&mut self, attribute: (&'static str, &'static str)) {
+    pub fn add_attribute(&mut self, attribute: (&'static str, String)) {
         if let Some(attr_map) = &mut self.attributes {
             attr_map.insert(attribute.0, attribute.1);
         } else {
             self.attributes = Some(HashMap::from([attribute]));
         }
     }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .as_ref()
+            .and_then(|a| a.get(name))
+            .map(String::as_str)
+    }
+
+    ///Label followed by the constants, as drawn
+    pub fn text(&self) -> String {
+        let mut lines = vec![self.label.as_str()];
+        lines.extend(self.constants.iter().map(String::as_str));
+        lines.join("\n")
+    }
 }
 
-pub fn render_to<W: Write>(output: &mut W, graph: RenderableGraph) {
-    dot3::render(&graph, output).unwrap()
+///Quotes a string for DOT, with newlines as centered line breaks
+fn dot_string(s: &str) -> String {
+    format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
 }
 
-impl<'a> dot3::Labeller<'a, Nd, Edge> for RenderableGraph {
-    fn graph_id(&'a self) -> dot3::Id<'a> {
-        dot3::Id::new("example2").unwrap()
-    }
-    fn node_id(&'a self, n: &Nd) -> dot3::Id<'a> {
-        dot3::Id::new(format!("N{}", n)).unwrap()
-    }
-    fn node_label<'b>(&'b self, n: &Nd) -> dot3::LabelText<'b> {
-        dot3::LabelText::LabelStr(self.nodes[*n].label.clone().into())
-    }
-    fn edge_label<'b>(&'b self, e: &Edge) -> dot3::LabelText<'b> {
-        dot3::LabelText::LabelStr(e.label.clone().into())
-    }
-    fn node_color(&'a self, _node: &Nd) -> Option<dot3::LabelText<'a>> {
-        Some(dot3::LabelText::LabelStr("black".into()))
-    }
+///Legend as an HTML-like label: the title, the ramp, then its extremes
+fn dot_legend(legend: &Legend) -> String {
+    let stops = heat_stops(8);
+    let cells: String = stops
+        .iter()
+        .map(|color| format!("<td bgcolor=\"{}\" width=\"18\" height=\"12\"></td>", color))
+        .collect();
+    format!(
+        "<<table border=\"0\" cellborder=\"0\" cellspacing=\"0\">\
+         <tr><td colspan=\"{n}\">{}</td></tr><tr>{}</tr>\
+         <tr><td colspan=\"{h}\" align=\"left\">{}</td><td colspan=\"{h}\" align=\"right\">{}</td></tr>\
+         </table>>",
+        escape_xml(&legend.title),
+        cells,
+        escape_xml(&legend.min),
+        escape_xml(&legend.max),
+        n = stops.len(),
+        h = stops.len() / 2,
+    )
+}
 
-    fn node_style(&'a self, _n: &Nd) -> dot3::Style {
-        dot3::Style::Filled
-    }
+///Record label with the node label above its constants, each constant left aligned
+fn dot_record(node: &Node) -> String {
+    let field = |s: &str| {
+        let mut escaped = String::new();
+        for c in s.chars() {
+            match c {
+                '\\' | '"' | '{' | '}' | '|' | '<' | '>' => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                '\n' => escaped.push_str("\\n"),
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    };
+    let constants: String = node
+        .constants
+        .iter()
+        .map(|c| format!("{}\\l", field(c)))
+        .collect();
+    format!("\"{{{}|{}}}\"", field(&node.label), constants)
+}
 
-    fn node_attrs(&'a self, n: &Nd) -> HashMap<&str, &str> {
-        self.nodes[*n]
-            .attributes
-            .clone()
-            .unwrap_or_else(|| HashMap::from([("fillcolor", "white")]))
+fn dot_node(node: &Node) -> String {
+    let label = match node.constants.is_empty() {
+        true => dot_string(&node.label),
+        false => dot_record(node),
+    };
+    let mut attrs = vec![
+        ("label", label),
+        (
+            "style",
+            match node.attribute("style") {
+                Some(style) => dot_string(&format!("filled,{}", style)),
+                None => "filled".to_string(),
+            },
+        ),
+        (
+            "fillcolor",
+            dot_string(node.attribute("fillcolor").unwrap_or("white")),
+        ),
+    ];
+    let mut extra: Vec<_> = node
+        .attributes
+        .iter()
+        .flatten()
+        .filter(|entry| !["fillcolor", "style", "color"].contains(entry.0))
+        .collect();
+    extra.sort_unstable();
+    attrs.extend(extra.into_iter().map(|(&k, v)| (k, dot_string(v))));
+    if node.highlighted {
+        attrs.push(("color", "red".to_string()));
+        attrs.push(("penwidth", "3".to_string()));
+    } else {
+        attrs.push((
+            "color",
+            dot_string(node.attribute("color").unwrap_or("black")),
+        ));
     }
+    format!("N{}[{}];", node.id, dot_attributes(&attrs))
 }
 
-impl<'a> dot3::GraphWalk<'a, Nd, Edge> for RenderableGraph {
-    fn nodes(&self) -> dot3::Nodes<'a, Nd> {
-        (0..self.nodes.len()).collect()
+///Writes the nodes of the scope, then each nested scope as a cluster
+fn dot_scope<W: Write>(
+    output: &mut W,
+    graph: &RenderableGraph,
+    scope: &Scope,
+    clusters: &mut usize,
+    indent: usize,
+) -> std::io::Result<()> {
+    let pad = "    ".repeat(indent);
+    for &nd in scope.nodes.iter() {
+        writeln!(output, "{}{}", pad, dot_node(&graph.nodes[nd]))?;
+    }
+    for child in scope.children.iter() {
+        writeln!(output, "{}subgraph cluster_{} {{", pad, clusters)?;
+        *clusters += 1;
+        let style = match child.repeat {
+            Some(_) => &graph.theme.repeat,
+            None => &graph.theme.cluster,
+        };
+        let mut attrs = vec![
+            ("label", dot_string(&child.name)),
+            ("tooltip", dot_string(&child.path)),
+        ];
+        let mut styles = vec!["rounded"];
+        if style.fill.is_some() {
+            styles.push("filled");
+        }
+        styles.extend(style.border_style.as_deref());
+        attrs.push(("style", dot_string(&styles.join(","))));
+        for (key, value) in [
+            ("fillcolor", &style.fill),
+            ("color", &style.border),
+            ("fontcolor", &style.font_color),
+        ] {
+            if let Some(value) = value {
+                attrs.push((key, dot_string(value)));
+            }
+        }
+        let attrs: String = attrs
+            .iter()
+            .map(|(k, v)| format!("{}={}; ", k, v))
+            .collect();
+        writeln!(output, "{}    {}", pad, attrs.trim_end())?;
+        dot_scope(output, graph, child, clusters, indent + 1)?;
+        writeln!(output, "{}}}", pad)?;
     }
-    fn edges(&'a self) -> dot3::Edges<'a, Edge> {
-        self.edges.clone().into_iter().collect()
+    Ok(())
+}
+
+pub fn render_to<W: Write>(output: &mut W, graph: &RenderableGraph) -> std::io::Result<()> {
+    let theme = &graph.theme;
+    writeln!(output, "digraph model {{")?;
+    writeln!(output, "    bgcolor={};", dot_string(theme.background()))?;
+    writeln!(
+        output,
+        "    edge[color={c}, fontcolor={c}];",
+        c = dot_string(theme.edge())
+    )?;
+    if let Some(font) = &theme.node.font {
+        writeln!(
+            output,
+            "    graph[fontname={f}]; edge[fontname={f}];",
+            f = dot_string(font)
+        )?;
     }
-    fn source(&self, e: &Edge) -> Nd {
-        e.from
+    dot_scope(output, graph, &graph.scopes(), &mut 0, 1)?;
+    for edge in graph.edges.iter() {
+        let mut attrs = vec![("label", dot_string(&edge.label))];
+        if edge.highlighted {
+            attrs.push(("color", "red".to_string()));
+            attrs.push(("style", "bold".to_string()));
+        }
+        if edge.width != 1. {
+            attrs.push(("penwidth", format!("{:.2}", edge.width)));
+        }
+        writeln!(
+            output,
+            "    N{} -> N{}[{}];",
+            edge.from,
+            edge.to,
+            dot_attributes(&attrs)
+        )?;
     }
-    fn target(&self, e: &Edge) -> Nd {
-        e.to
+    if let Some(legend) = &graph.legend {
+        writeln!(
+            output,
+            "    legend[shape=plaintext, label={}];",
+            dot_legend(legend)
+        )?;
     }
+    writeln!(output, "}}")
+}
+
+fn dot_attributes(attrs: &[(&str, String)]) -> String {
+    attrs
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+///DOT source of the graph, as passed to Graphviz
+pub fn to_dot(graph: &RenderableGraph) -> String {
+    let mut out = vec![];
+    render_to(&mut out, graph).expect("Writing to a Vec cannot fail.");
+    String::from_utf8(out).expect("DOT output is UTF-8.")
 }
@@ -0,0 +1,85 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{Edge, Node, RenderableGraph};
+
+type Nd = usize;
+
+impl RenderableGraph {
+    ///Keeps only the drawn nodes of the given model ids. Each tensor crossing into or out of
+    ///them is drawn as a dashed stub node, named after the tensor, in place of the nodes
+    ///left out.
+    pub fn restrict(&mut self, ids: &[usize]) {
+        let mut remap: HashMap<Nd, Nd> = HashMap::new();
+        let mut nodes: Vec<Node> = vec![];
+        let mut kept: Vec<Nd> = ids
+            .iter()
+            .filter_map(|id| self.node_ids.get(id).copied())
+            .collect();
+        kept.sort_unstable();
+        kept.dedup();
+        for nd in kept {
+            remap.insert(nd, nodes.len());
+            nodes.push(Node {
+                id: nodes.len(),
+                ..self.nodes[nd].clone()
+            });
+        }
+
+        //Stubs are shared by every edge carrying their tensor across the boundary
+        let mut stubs: HashMap<(bool, String), Nd> = HashMap::new();
+        let style = self.theme.style(None, "Stub");
+        let mut stub = |nodes: &mut Vec<Node>, outgoing: bool, edge: &Edge, outside: &Node| {
+            let name = match edge.tensor.is_empty() {
+                true => outside.name.clone(),
+                false => edge.tensor.clone(),
+            };
+            *stubs.entry((outgoing, name.clone())).or_insert_with(|| {
+                let mut node = Node {
+                    id: nodes.len(),
+                    label: name.clone(),
+                    attributes: None,
+                    highlighted: false,
+                    op_type: "Stub".to_string(),
+                    name,
+                    cost: None,
+                    scope: vec![],
+                    constants: vec![],
+                };
+                node.apply_style(&style);
+                nodes.push(node);
+                nodes.len() - 1
+            })
+        };
+
+        let mut edges: Vec<Edge> = vec![];
+        let mut seen: HashSet<(Nd, Nd)> = HashSet::new();
+        for edge in self.edges.drain(..) {
+            let (from, to, to_stub) = match (remap.get(&edge.from), remap.get(&edge.to)) {
+                (Some(&from), Some(&to)) => (from, to, false),
+                (None, Some(&to)) => {
+                    let outside = &self.nodes[edge.from];
+                    (stub(&mut nodes, false, &edge, outside), to, false)
+                }
+                (Some(&from), None) => {
+                    let outside = &self.nodes[edge.to];
+                    (from, stub(&mut nodes, true, &edge, outside), true)
+                }
+                (None, None) => continue,
+            };
+            //A stub stands for every consumer outside, so their edges merge.
+            //Parallel edges between drawn nodes, such as those of `Mul(x, x)`, are kept.
+            if !to_stub || seen.insert((from, to)) {
+                edges.push(Edge { from, to, ..edge });
+            }
+        }
+
+        self.node_ids = self
+            .node_ids
+            .iter()
+            .filter_map(|(&id, nd)| remap.get(nd).map(|&nd| (id, nd)))
+            .collect();
+        self.current_id = nodes.len();
+        self.nodes = nodes;
+        self.edges = edges;
+    }
+}
@@ -0,0 +1,514 @@
+use std::{collections::HashMap, fmt::Write, str::FromStr};
+
+use serde::Serialize;
+
+use crate::{
+    analysis::{Fingerprint, RepeatedSubgraph},
+    ir::{DType, Diagnostic, Model, ModelSummary, ShapeMismatch, ValueInfo},
+};
+
+use super::{block_name, Device};
+
+///Version of the report schema, bumped whenever a field is renamed, removed or changes meaning.
+///New fields, and new CSV columns appended at the end, do not change the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+///How the summary command prints its results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryFormat {
+    Table,
+    Json,
+    Csv, //one row per node
+    Markdown,
+}
+
+impl FromStr for SummaryFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(SummaryFormat::Table),
+            "json" => Ok(SummaryFormat::Json),
+            "csv" => Ok(SummaryFormat::Csv),
+            "markdown" => Ok(SummaryFormat::Markdown),
+            _ => Err(anyhow::anyhow!("Unknown summary format: {}", s)),
+        }
+    }
+}
+
+///A dimension is either static, or named by a dim_param of the model
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum DimReport {
+    Static(usize),
+    Symbolic(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TensorReport {
+    pub name: String,
+    pub dtype: String, //lowercase, e.g. "f32"
+    pub shape: Vec<DimReport>,
+}
+
+impl From<&ValueInfo> for TensorReport {
+    fn from(vi: &ValueInfo) -> Self {
+        let shape = vi
+            .dimensions
+            .iter()
+            .enumerate()
+            .map(|(axis, &dim)| match vi.dim_params.get(axis) {
+                Some(Some(param)) => DimReport::Symbolic(param.clone()),
+                _ => DimReport::Static(dim),
+            })
+            .collect();
+        Self {
+            name: vi.name.clone(),
+            dtype: dtype_name(vi.dt),
+            shape,
+        }
+    }
+}
+
+impl TensorReport {
+    ///Dimensions joined with `x`, e.g. `1x3x224x224`
+    pub(crate) fn shape_string(&self) -> String {
+        let dims: Vec<String> = self
+            .shape
+            .iter()
+            .map(|d| match d {
+                DimReport::Static(d) => d.to_string(),
+                DimReport::Symbolic(p) => p.clone(),
+            })
+            .collect();
+        dims.join("x")
+    }
+}
+
+pub(crate) fn dtype_name(dt: DType) -> String {
+    format!("{:?}", dt).to_lowercase()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeReport {
+    pub id: usize,
+    pub name: String, //name of the node in the ONNX graph, may be empty
+    pub op_type: String,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<TensorReport>,
+    pub flops: Option<usize>, //None if the node was never realized
+    pub params: Option<usize>,
+    pub shapes_known: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OpTypeReport {
+    pub op_type: String,
+    pub count: usize,
+    pub flops: usize,
+    pub params: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceReport {
+    pub name: String,
+    pub iterations_per_second: Option<f64>, //at single precision, None without FLOPs
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShapeMismatchReport {
+    pub node_id: usize,
+    pub op_type: String,
+    pub tensor: String,
+    pub inferred: Vec<usize>,
+    pub annotated: Vec<usize>,
+}
+
+impl From<&ShapeMismatch> for ShapeMismatchReport {
+    fn from(m: &ShapeMismatch) -> Self {
+        Self {
+            node_id: m.node_id,
+            op_type: m.op.clone(),
+            tensor: m.tensor.clone(),
+            inferred: m.inferred.to_vec(),
+            annotated: m.annotated.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiagnosticReport {
+    pub severity: String, //"warning" or "error"
+    pub stage: String,    //"parse" or "realize"
+    pub node_id: Option<usize>,
+    pub node_name: String,
+    pub op_type: String,
+    pub opset: Option<i64>,
+    pub message: String,
+}
+
+impl From<&Diagnostic> for DiagnosticReport {
+    fn from(d: &Diagnostic) -> Self {
+        Self {
+            severity: format!("{:?}", d.severity).to_lowercase(),
+            stage: format!("{:?}", d.stage).to_lowercase(),
+            node_id: d.node_id,
+            node_name: d.node_name.clone(),
+            op_type: d.op_type.clone(),
+            opset: d.opset,
+            message: d.message.clone(),
+        }
+    }
+}
+
+///A block repeated back to back, with the cost of a single repetition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepeatedBlockReport {
+    pub name: String, //name scope of the first repetition, or its ops
+    pub ops: Vec<String>,
+    pub count: usize,
+    pub nodes: Vec<Vec<usize>>, //node ids of each repetition
+    pub flops: usize,
+    pub params: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArchitectureMatchReport {
+    pub name: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArchitectureReport {
+    pub structure_hash: String,
+    pub matches: Vec<ArchitectureMatchReport>, //best first
+}
+
+///Everything the summary command reports, in a form which is stable across releases.
+///
+///The schema is independent of the internal types, so refactoring `ModelSummary` does not
+///break consumers. See `SCHEMA_VERSION` for what counts as a breaking change.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SummaryReport {
+    pub schema_version: u32,
+    pub steelix_version: String,
+    pub model: String,
+    pub total_flops: usize,
+    pub total_params: usize,
+    pub partial: bool, //some nodes had unknown shapes, so totals are lower bounds
+    pub inputs: Vec<TensorReport>,
+    pub outputs: Vec<TensorReport>,
+    pub op_types: Vec<OpTypeReport>, //most frequent first
+    pub nodes: Vec<NodeReport>,      //by node id
+    pub devices: Vec<DeviceReport>,
+    pub architecture: ArchitectureReport,
+    pub repeated_blocks: Vec<RepeatedBlockReport>, //largest first
+    pub hinted_nodes: Vec<usize>,
+    pub shape_mismatches: Vec<ShapeMismatchReport>,
+    pub diagnostics: Vec<DiagnosticReport>,
+}
+
+impl SummaryReport {
+    pub fn new(
+        model_name: &str,
+        model: &Model,
+        summary: &ModelSummary,
+        fingerprint: &Fingerprint,
+        repeats: &[RepeatedSubgraph],
+        devices: &[Device],
+    ) -> Self {
+        let tensor = |name: &str| summary.inferred_tensors.get(name).map(TensorReport::from);
+        //Graph inputs and outputs keep the symbolic dimensions of their declaration
+        let annotated = |name: &str| {
+            model
+                .value_info
+                .get(name)
+                .map(TensorReport::from)
+                .or_else(|| tensor(name))
+        };
+
+        let nodes: Vec<NodeReport> = model
+            .nodes
+            .iter()
+            .map(|node| {
+                let cost = summary.node_costs.get(&node.id);
+                NodeReport {
+                    id: node.id,
+                    name: node.onnx_name.clone(),
+                    op_type: node.name.clone(),
+                    inputs: node.inputs.clone(),
+                    outputs: node.outputs.iter().filter_map(|o| tensor(o)).collect(),
+                    flops: cost.map(|c| c.flops),
+                    params: cost.map(|c| c.parameters),
+                    shapes_known: !summary.unknown_nodes.contains(&node.id),
+                }
+            })
+            .collect();
+
+        let mut costs: HashMap<&str, (usize, usize)> = HashMap::new();
+        for node in nodes.iter() {
+            let entry = costs.entry(node.op_type.as_str()).or_default();
+            entry.0 += node.flops.unwrap_or(0);
+            entry.1 += node.params.unwrap_or(0);
+        }
+        let mut op_types: Vec<OpTypeReport> = summary
+            .op_frequencies
+            .iter()
+            .map(|(op_type, &count)| {
+                let (flops, params) = costs.get(&**op_type).copied().unwrap_or_default();
+                OpTypeReport {
+                    op_type: op_type.clone(),
+                    count,
+                    flops,
+                    params,
+                }
+            })
+            .collect();
+        op_types.sort_by(|a, b| b.count.cmp(&a.count).then(a.op_type.cmp(&b.op_type)));
+
+        let devices = devices
+            .iter()
+            .map(|device| DeviceReport {
+                name: device.name.clone(),
+                iterations_per_second: device
+                    .flops_per_sec(DType::F32)
+                    .ok()
+                    .filter(|_| summary.total_flops > 0)
+                    .map(|per_sec| per_sec as f64 / summary.total_flops as f64),
+            })
+            .collect();
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            steelix_version: env!("CARGO_PKG_VERSION").to_string(),
+            model: model_name.to_string(),
+            total_flops: summary.total_flops,
+            total_params: summary.total_params,
+            partial: !summary.unknown_nodes.is_empty(),
+            inputs: model
+                .inputs
+                .iter()
+                .flat_map(|&id| model.nodes[id].outputs.iter())
+                .filter_map(|name| annotated(name))
+                .collect(),
+            outputs: model
+                .outputs
+                .iter()
+                .flat_map(|&id| model.nodes[id].inputs.iter())
+                .filter_map(|name| annotated(name))
+                .collect(),
+            op_types,
+            nodes,
+            devices,
+            architecture: ArchitectureReport {
+                structure_hash: fingerprint.hash.clone(),
+                matches: fingerprint
+                    .matches
+                    .iter()
+                    .map(|m| ArchitectureMatchReport {
+                        name: m.name.to_string(),
+                        score: m.score,
+                    })
+                    .collect(),
+            },
+            repeated_blocks: repeats
+                .iter()
+                .map(|block| {
+                    let cost = block.cost(model, summary);
+                    RepeatedBlockReport {
+                        name: block_name(model, block),
+                        ops: block.ops(model).into_iter().map(str::to_string).collect(),
+                        count: block.count(),
+                        nodes: block.instances.clone(),
+                        flops: cost.flops,
+                        params: cost.parameters,
+                    }
+                })
+                .collect(),
+            hinted_nodes: summary.hinted_nodes.clone(),
+            shape_mismatches: summary.shape_mismatches.iter().map(Into::into).collect(),
+            diagnostics: summary.diagnostics.iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Report is always serializable.")
+    }
+
+    ///One row per node. Multiple outputs are joined with `;`, and dimensions with `x`.
+    pub fn to_csv(&self) -> String {
+        let mut out =
+            String::from("id,name,op_type,flops,params,shapes_known,outputs,dtypes,shapes\n");
+        let optional = |v: Option<usize>| v.map_or_else(String::new, |v| v.to_string());
+        for node in self.nodes.iter() {
+            let join = |f: &dyn Fn(&TensorReport) -> String| -> String {
+                node.outputs.iter().map(f).collect::<Vec<_>>().join(";")
+            };
+            let row = [
+                node.id.to_string(),
+                node.name.clone(),
+                node.op_type.clone(),
+                optional(node.flops),
+                optional(node.params),
+                node.shapes_known.to_string(),
+                join(&|t| t.name.clone()),
+                join(&|t| t.dtype.clone()),
+                join(&|t| t.shape_string()),
+            ];
+            let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            writeln!(out, "{}", row.join(",")).unwrap();
+        }
+        out
+    }
+
+    ///Tables for pasting into pull requests and wikis, per-node rows are left out
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("## {} Model Summary\n\n", markdown_cell(&self.model));
+
+        let mut metrics = vec![
+            vec!["FLOPS".to_string(), self.total_flops.to_string()],
+            vec!["Parameters".to_string(), self.total_params.to_string()],
+        ];
+        if let Some(best) = self.architecture.matches.first() {
+            metrics.push(vec![
+                "Architecture".to_string(),
+                format!("{} ({:.0}% match)", best.name, best.score * 100.),
+            ]);
+        }
+        metrics.push(vec![
+            "Structure".to_string(),
+            format!("`{}`", self.architecture.structure_hash),
+        ]);
+        markdown_section(&mut out, "Metrics", &["Metric", "Total"], &metrics);
+        if self.partial {
+            out.push_str("Some nodes had unknown shapes, totals are partial.\n\n");
+        }
+
+        let ops: Vec<Vec<String>> = self
+            .op_types
+            .iter()
+            .map(|op| {
+                vec![
+                    op.op_type.clone(),
+                    op.count.to_string(),
+                    op.flops.to_string(),
+                    op.params.to_string(),
+                ]
+            })
+            .collect();
+        markdown_section(
+            &mut out,
+            "Operations",
+            &["Op", "Count", "FLOPS", "Parameters"],
+            &ops,
+        );
+
+        let devices: Vec<Vec<String>> = self
+            .devices
+            .iter()
+            .map(|d| {
+                let its = d
+                    .iterations_per_second
+                    .map_or_else(|| "-".to_string(), |its| format!("{:.2} it/s", its));
+                vec![d.name.clone(), its]
+            })
+            .collect();
+        markdown_section(&mut out, "Hardware", &["Name", "Its"], &devices);
+
+        let repeats: Vec<Vec<String>> = self
+            .repeated_blocks
+            .iter()
+            .map(|b| {
+                vec![
+                    b.name.clone(),
+                    b.count.to_string(),
+                    b.ops.len().to_string(),
+                    b.flops.to_string(),
+                    b.params.to_string(),
+                ]
+            })
+            .collect();
+        markdown_section(
+            &mut out,
+            "Repeated Blocks",
+            &[
+                "Block",
+                "Repeats",
+                "Nodes",
+                "FLOPS/Block",
+                "Parameters/Block",
+            ],
+            &repeats,
+        );
+
+        let shape = |dims: &[usize]| {
+            let dims: Vec<String> = dims.iter().map(usize::to_string).collect();
+            dims.join("x")
+        };
+        let mismatches: Vec<Vec<String>> = self
+            .shape_mismatches
+            .iter()
+            .map(|m| {
+                vec![
+                    m.op_type.clone(),
+                    m.tensor.clone(),
+                    shape(&m.inferred),
+                    shape(&m.annotated),
+                ]
+            })
+            .collect();
+        markdown_section(
+            &mut out,
+            "Shape Mismatches",
+            &["Op", "Tensor", "Inferred", "Annotated"],
+            &mismatches,
+        );
+
+        let diagnostics: Vec<Vec<String>> = self
+            .diagnostics
+            .iter()
+            .map(|d| {
+                vec![
+                    d.severity.clone(),
+                    d.op_type.clone(),
+                    d.node_name.clone(),
+                    d.message.clone(),
+                ]
+            })
+            .collect();
+        markdown_section(
+            &mut out,
+            "Diagnostics",
+            &["Severity", "Op", "Node", "Message"],
+            &diagnostics,
+        );
+        out
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn markdown_cell(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', " ")
+}
+
+///Empty sections are left out
+fn markdown_section(out: &mut String, title: &str, headers: &[&str], rows: &[Vec<String>]) {
+    if rows.is_empty() {
+        return;
+    }
+    writeln!(out, "### {}\n", title).unwrap();
+    writeln!(out, "| {} |", headers.join(" | ")).unwrap();
+    writeln!(out, "|{}", "---|".repeat(headers.len())).unwrap();
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|c| markdown_cell(c)).collect();
+        writeln!(out, "| {} |", cells.join(" | ")).unwrap();
+    }
+    out.push('\n');
+}
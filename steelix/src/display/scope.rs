@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+
+use human_repr::HumanCount;
+
+use crate::{
+    analysis::RepeatedSubgraph,
+    ir::{Model, OpCost},
+};
+
+use super::{Edge, Node, RenderableGraph};
+
+type Nd = usize;
+
+///Prefix of the cluster key of a repeated block, which no name scope can start with
+const REPEAT_KEY: &str = "\0repeat";
+
+///A block drawn once for several identical repetitions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repeat {
+    pub count: usize,
+    pub nodes: Vec<Nd>,
+    pub scope: Vec<String>, //name scopes shared by every repetition, where the badge is drawn
+}
+
+///Name scopes of a node, e.g. `/encoder/layer.3/attention/MatMul` is in `encoder`,
+///`encoder/layer.3` and `encoder/layer.3/attention`. The last component names the node itself.
+pub fn name_scope(onnx_name: &str) -> Vec<String> {
+    let mut components: Vec<String> = onnx_name
+        .split('/')
+        .filter(|c| !c.is_empty())
+        .map(str::to_string)
+        .collect();
+    components.pop();
+    components
+}
+
+///Name scope shared by the first repetition of a block, or its ops when there is none
+pub fn block_name(model: &Model, block: &RepeatedSubgraph) -> String {
+    let scope = block.instances[0]
+        .iter()
+        .map(|&id| name_scope(&model.nodes[id].onnx_name))
+        .reduce(|a, b| {
+            let shared = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+            a[..shared].to_vec()
+        })
+        .unwrap_or_default();
+    if !scope.is_empty() {
+        return scope.join("/");
+    }
+    let ops = block.ops(model);
+    match ops.len() {
+        0..=4 => ops.join(" → "),
+        _ => format!("{} → … → {}", ops[..3].join(" → "), ops[ops.len() - 1]),
+    }
+}
+
+///A name scope, or a repeated block, and the nodes directly inside it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scope {
+    pub name: String, //last component of the path
+    pub path: String,
+    pub nodes: Vec<Nd>,
+    pub children: Vec<Scope>,
+    pub repeat: Option<usize>, //number of repetitions, for a repeated block
+    key: String,
+}
+
+impl Scope {
+    fn child(&mut self, key: &str, repeats: &[Repeat]) -> &mut Scope {
+        let i = match self.children.iter().position(|c| c.key == key) {
+            Some(i) => i,
+            None => {
+                let repeat = key
+                    .strip_prefix(REPEAT_KEY)
+                    .and_then(|i| i.parse::<usize>().ok())
+                    .map(|i| repeats[i].count);
+                let name = match repeat {
+                    Some(count) => format!("×{}", count),
+                    None => key.to_string(),
+                };
+                let path = if self.path.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}/{}", self.path, name)
+                };
+                self.children.push(Scope {
+                    name,
+                    path,
+                    repeat,
+                    key: key.to_string(),
+                    ..Default::default()
+                });
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[i]
+    }
+
+    ///A cluster around a lone node only adds noise, so the node moves up to the parent.
+    ///Repeated blocks keep their badge.
+    fn hoist_single_nodes(&mut self) {
+        for child in self.children.iter_mut() {
+            child.hoist_single_nodes();
+        }
+        let mut hoisted = vec![];
+        self.children.retain(|child| {
+            if child.repeat.is_none() && child.children.is_empty() && child.nodes.len() <= 1 {
+                hoisted.extend(child.nodes.iter().copied());
+                false
+            } else {
+                true
+            }
+        });
+        self.nodes.extend(hoisted);
+    }
+}
+
+impl RenderableGraph {
+    ///Keys of the clusters each node is drawn in, outermost first. Name scopes, when clustering,
+    ///are keyed by name. Repeated blocks are nested in the scopes shared by all of their nodes.
+    pub(crate) fn cluster_keys(&self) -> Vec<Vec<String>> {
+        let mut keys: Vec<Vec<String>> = self
+            .nodes
+            .iter()
+            .map(|node| match self.clustered {
+                true => node.scope.clone(),
+                false => vec![],
+            })
+            .collect();
+        for (i, repeat) in self.repeats.iter().enumerate() {
+            let depth = if self.clustered {
+                repeat.scope.len()
+            } else {
+                0
+            };
+            for &nd in repeat.nodes.iter() {
+                let depth = depth.min(keys[nd].len());
+                keys[nd].insert(depth, format!("{}{}", REPEAT_KEY, i));
+            }
+        }
+        keys
+    }
+
+    ///Tree of the clusters of the graph, whose root holds the nodes outside any cluster.
+    ///Without clustering only repeated blocks are clusters.
+    pub fn scopes(&self) -> Scope {
+        let mut root = Scope::default();
+        for (nd, keys) in self.cluster_keys().iter().enumerate() {
+            let mut scope = &mut root;
+            for key in keys.iter() {
+                scope = scope.child(key, &self.repeats);
+            }
+            scope.nodes.push(nd);
+        }
+        root.hoist_single_nodes();
+        root
+    }
+
+    ///Replaces every scope `depth` levels deep by a single node, labelled with the number of
+    ///nodes it holds and their total FLOPs and parameters. Edges inside a scope are dropped,
+    ///and parallel edges between two nodes merged.
+    pub fn collapse_scopes(&mut self, depth: usize) {
+        if depth == 0 {
+            return;
+        }
+        let mut nodes: Vec<Node> = vec![];
+        let mut members: HashMap<Nd, usize> = HashMap::new();
+        let mut collapsed: HashMap<Vec<String>, Nd> = HashMap::new();
+        let mut remap = vec![0; self.nodes.len()];
+
+        for node in self.nodes.drain(..) {
+            if node.scope.len() < depth {
+                remap[node.id] = nodes.len();
+                nodes.push(Node {
+                    id: nodes.len(),
+                    ..node
+                });
+                continue;
+            }
+            let prefix = node.scope[..depth].to_vec();
+            match collapsed.get(&prefix) {
+                Some(&nd) => {
+                    let summary = &mut nodes[nd];
+                    summary.highlighted |= node.highlighted;
+                    summary.cost = match (summary.cost, node.cost) {
+                        (Some(a), Some(b)) => Some(OpCost {
+                            flops: a.flops + b.flops,
+                            parameters: a.parameters + b.parameters,
+                        }),
+                        _ => None,
+                    };
+                    //Constants drawn as nodes are not counted among the nodes of the scope
+                    if node.op_type != "Constant" {
+                        *members.get_mut(&nd).unwrap() += 1;
+                    }
+                    remap[node.id] = nd;
+                }
+                None => {
+                    let nd = nodes.len();
+                    let mut summary = Node {
+                        id: nd,
+                        label: String::new(),
+                        attributes: None,
+                        highlighted: node.highlighted,
+                        op_type: "Scope".to_string(),
+                        name: prefix.join("/"),
+                        cost: node.cost,
+                        scope: prefix[..depth - 1].to_vec(),
+                        constants: vec![],
+                    };
+                    summary.apply_style(&self.theme.style(None, "Scope"));
+                    nodes.push(summary);
+                    members.insert(nd, (node.op_type != "Constant") as usize);
+                    collapsed.insert(prefix, nd);
+                    remap[node.id] = nd;
+                }
+            }
+        }
+
+        for (&nd, &count) in members.iter() {
+            let summary = &mut nodes[nd];
+            let mut label = format!(
+                "{}\n{} nodes",
+                summary.name.rsplit('/').next().unwrap_or_default(),
+                count
+            );
+            if let Some(cost) = summary.cost {
+                label.push_str(&format!(
+                    "\n{} FLOPs\n{} params",
+                    cost.flops.human_count_bare(),
+                    cost.parameters.human_count_bare()
+                ));
+            }
+            summary.label = label;
+        }
+
+        self.reconnect(nodes, &remap, |_| true);
+    }
+
+    ///Draws every repeated block once, in place of its first repetition, with a badge counting
+    ///the repetitions. Edges into later repetitions are redirected to the first, and edges
+    ///chaining one repetition to the next are dropped. Blocks sharing a drawn node with
+    ///another repetition, as after collapsing scopes across their boundaries, are left as is.
+    pub fn collapse_repeats(&mut self, blocks: &[RepeatedSubgraph]) {
+        let mut instance_of: HashMap<Nd, (usize, usize)> = HashMap::new(); //block and repetition
+        let mut merged: HashMap<Nd, Nd> = HashMap::new();
+        for (b, block) in blocks.iter().enumerate() {
+            let drawn = |instance: &[usize]| -> Option<Vec<Nd>> {
+                instance
+                    .iter()
+                    .map(|id| self.node_ids.get(id).copied())
+                    .collect()
+            };
+            let instances: Option<Vec<Vec<Nd>>> =
+                block.instances.iter().map(|i| drawn(i)).collect();
+            let instances = match instances {
+                Some(instances) => instances,
+                None => continue,
+            };
+            let overlaps = instances.iter().enumerate().any(|(k, instance)| {
+                instance.iter().any(|nd| {
+                    instance_of.contains_key(nd)
+                        || instances
+                            .iter()
+                            .enumerate()
+                            .any(|(j, other)| j != k && other.contains(nd))
+                })
+            });
+            if overlaps {
+                continue;
+            }
+            for (k, instance) in instances.iter().enumerate() {
+                for (i, &nd) in instance.iter().enumerate() {
+                    instance_of.insert(nd, (b, k));
+                    if k > 0 {
+                        merged.insert(nd, instances[0][i]);
+                        self.nodes[instances[0][i]].highlighted |= self.nodes[nd].highlighted;
+                    }
+                }
+            }
+            let scope = instances
+                .iter()
+                .flatten()
+                .map(|&nd| &self.nodes[nd].scope[..])
+                .reduce(|a, b| {
+                    let shared = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+                    &a[..shared]
+                })
+                .unwrap_or_default()
+                .to_vec();
+            let mut nodes = instances[0].clone();
+            nodes.extend(self.constants_feeding(|nd| instances[0].contains(&nd)));
+            nodes.sort_unstable();
+            nodes.dedup();
+            self.repeats.push(Repeat {
+                count: block.count(),
+                nodes,
+                scope,
+            });
+        }
+
+        //Constants drawn for later repetitions are dropped along with them
+        let dropped = self.constants_feeding(|nd| merged.contains_key(&nd));
+        self.node_ids.retain(|_, nd| !dropped.contains(nd));
+        let mut nodes: Vec<Node> = vec![];
+        let mut remap = vec![0; self.nodes.len()];
+        for node in self.nodes.drain(..) {
+            if !merged.contains_key(&node.id) && !dropped.contains(&node.id) {
+                remap[node.id] = nodes.len();
+                nodes.push(Node {
+                    id: nodes.len(),
+                    ..node
+                });
+            }
+        }
+        for (&nd, &first) in merged.iter() {
+            remap[nd] = remap[first];
+        }
+        let chained = |edge: &Edge| match (instance_of.get(&edge.from), instance_of.get(&edge.to)) {
+            (Some((a, i)), Some((b, j))) => a == b && i != j,
+            _ => false,
+        };
+        self.reconnect(nodes, &remap, |edge| {
+            !chained(edge) && !dropped.contains(&edge.from)
+        });
+    }
+
+    ///Constants drawn as nodes whose every consumer is in `consumers`
+    fn constants_feeding(&self, consumers: impl Fn(Nd) -> bool) -> Vec<Nd> {
+        self.nodes
+            .iter()
+            .filter(|node| node.op_type == "Constant")
+            .map(|node| node.id)
+            .filter(|&nd| {
+                let mut out = self.edges.iter().filter(|e| e.from == nd).peekable();
+                out.peek().is_some() && out.all(|e| consumers(e.to))
+            })
+            .collect()
+    }
+
+    ///Swaps in renumbered nodes, moving edges, repeats and model ids along with them. Edges
+    ///which become loops are dropped, and parallel edges merged.
+    ///
+    ///A merged edge lists every tensor it carries and drops the shape label, which could only
+    ///describe one of them.
+    fn reconnect(&mut self, nodes: Vec<Node>, remap: &[Nd], keep: impl Fn(&Edge) -> bool) {
+        let mut edges: Vec<Edge> = vec![];
+        let mut carried: Vec<Vec<String>> = vec![]; //tensors of each edge
+        let mut merged: HashMap<(Nd, Nd), usize> = HashMap::new();
+        for edge in self.edges.drain(..).filter(|e| keep(e)) {
+            let (from, to) = (remap[edge.from], remap[edge.to]);
+            if from == to {
+                continue;
+            }
+            match merged.get(&(from, to)) {
+                Some(&i) => {
+                    let kept = &mut edges[i];
+                    kept.highlighted |= edge.highlighted;
+                    //A tensor read by several nodes of a collapsed scope is only sent once
+                    if !edge.tensor.is_empty() && carried[i].contains(&edge.tensor) {
+                        continue;
+                    }
+                    carried[i].push(edge.tensor);
+                    kept.bytes = match (kept.bytes, edge.bytes) {
+                        (Some(a), Some(b)) => Some(a + b),
+                        (a, b) => a.or(b),
+                    };
+                    kept.label = String::new();
+                    kept.tensor = carried[i]
+                        .iter()
+                        .filter(|t| !t.is_empty())
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                }
+                None => {
+                    merged.insert((from, to), edges.len());
+                    carried.push(vec![edge.tensor.clone()]);
+                    edges.push(Edge { from, to, ..edge });
+                }
+            }
+        }
+
+        for nd in self.node_ids.values_mut() {
+            *nd = remap[*nd];
+        }
+        for repeat in self.repeats.iter_mut() {
+            for nd in repeat.nodes.iter_mut() {
+                *nd = remap[*nd];
+            }
+            repeat.nodes.sort_unstable();
+            repeat.nodes.dedup();
+        }
+        self.current_id = nodes.len();
+        self.nodes = nodes;
+        self.edges = edges;
+    }
+}
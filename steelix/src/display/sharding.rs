@@ -0,0 +1,109 @@
+use human_repr::HumanCount;
+
+use crate::{
+    analysis::{CollectiveKind, ShardKind, TensorParallelPlan},
+    ir::Model,
+};
+use tabled::{object::Rows, Alignment, Modify, Panel, Style, Table, Tabled};
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct ShardingEntry {
+    metric: String,
+    total: String,
+    per_device: String,
+}
+
+pub fn sharding_table(plan: &TensorParallelPlan) -> Table {
+    let traffic = plan.traffic_per_device() as usize;
+    let entries = vec![
+        ShardingEntry {
+            metric: "FLOPS".to_string(),
+            total: plan.total_flops.human_count_bare().to_string(),
+            per_device: plan.flops_per_device.human_count_bare().to_string(),
+        },
+        ShardingEntry {
+            metric: "Parameters".to_string(),
+            total: plan.total_param_bytes.human_count_bytes().to_string(),
+            per_device: plan.param_bytes_per_device.human_count_bytes().to_string(),
+        },
+        ShardingEntry {
+            metric: "Traffic".to_string(),
+            total: "-".to_string(),
+            per_device: traffic.human_count_bytes().to_string(),
+        },
+    ];
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(format!(
+            "{} way tensor parallel",
+            plan.degree
+        )))
+        .to_owned()
+}
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct ShardedNodeEntry {
+    op: String,
+    node: String,
+    strategy: String,
+}
+
+///Nodes which are split across devices, in traversal order
+pub fn sharded_nodes_table(model: &Model, plan: &TensorParallelPlan) -> Table {
+    let order = model.traversal_order.clone().unwrap_or_default();
+    let entries: Vec<ShardedNodeEntry> = order
+        .iter()
+        .filter_map(|id| match plan.shards.get(id)? {
+            ShardKind::Replicated => None,
+            kind => Some(ShardedNodeEntry {
+                op: model.nodes[*id].name.clone(),
+                node: model.nodes[*id].onnx_name.clone(),
+                strategy: format!("{:?}", kind),
+            }),
+        })
+        .collect();
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .to_owned()
+}
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct CollectiveEntry {
+    collective: String,
+    tensor: String,
+    size: String,
+    per_device: String,
+}
+
+pub fn collectives_table(plan: &TensorParallelPlan) -> Table {
+    let entries: Vec<CollectiveEntry> = plan
+        .collectives
+        .iter()
+        .map(|c| CollectiveEntry {
+            collective: match c.kind {
+                CollectiveKind::AllReduce => "all-reduce".to_string(),
+                CollectiveKind::AllGather => "all-gather".to_string(),
+            },
+            tensor: c.tensor.clone(),
+            size: c.bytes.human_count_bytes().to_string(),
+            per_device: (c.bytes_per_device(plan.degree) as usize)
+                .human_count_bytes()
+                .to_string(),
+        })
+        .collect();
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .to_owned()
+}
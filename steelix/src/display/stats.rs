@@ -0,0 +1,81 @@
+use crate::{analysis::GraphStats, ir::Model};
+use tabled::{object::Rows, Alignment, Modify, Panel, Style, Table, Tabled};
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct StatsEntry {
+    metric: String,
+    value: String,
+}
+
+pub fn stats_table(model: &Model, stats: &GraphStats) -> Table {
+    let fan = |fan: Option<(usize, usize)>| match fan {
+        Some((id, n)) => format!("{} ({})", n, model.nodes[id].onnx_name),
+        None => "0".to_string(),
+    };
+    let entry = |metric: &str, value: String| StatsEntry {
+        metric: metric.to_string(),
+        value,
+    };
+    let mut entries = vec![
+        entry("Nodes", stats.nodes.to_string()),
+        entry("Inputs", stats.inputs.to_string()),
+        entry("Outputs", stats.outputs.to_string()),
+        entry("Constants", stats.constants.to_string()),
+        entry("Edges", stats.edges.to_string()),
+        entry("Depth", stats.depth.to_string()),
+        entry("Max fan-in", fan(stats.max_fan_in)),
+        entry("Max fan-out", fan(stats.max_fan_out)),
+        entry("Branch points", stats.branch_points.len().to_string()),
+        entry("Merge points", stats.merge_points.len().to_string()),
+        entry("Residual connections", stats.residuals.len().to_string()),
+    ];
+    if let Some(block) = &stats.repeated_block {
+        entries.push(entry(
+            "Repeated block",
+            format!("{} ops x{}", block.len(), block.count()),
+        ));
+    }
+
+    let footer = match &stats.repeated_block {
+        Some(block) => format!("Repeated block: {}", block.ops(model).join(" -> ")),
+        None => "No repeated blocks found".to_string(),
+    };
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(footer))
+        .to_owned()
+}
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct ResidualEntry {
+    from: String,
+    to: String,
+    span: usize,
+}
+
+pub fn residuals_table(model: &Model, stats: &GraphStats) -> Table {
+    let entries: Vec<ResidualEntry> = stats
+        .residuals
+        .iter()
+        .map(|r| ResidualEntry {
+            from: model.nodes[r.from].onnx_name.clone(),
+            to: model.nodes[r.to].onnx_name.clone(),
+            span: r.span,
+        })
+        .collect();
+    let longest = stats.residuals.iter().map(|r| r.span).max().unwrap_or(0);
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(format!(
+            "Longest skip spans {} nodes",
+            longest
+        )))
+        .to_owned()
+}
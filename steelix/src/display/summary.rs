@@ -1,10 +1,13 @@
 use human_repr::HumanCount;
 use std::collections::HashMap;
 
-use crate::ir::{DType, ModelSummary};
+use crate::{
+    analysis::RepeatedSubgraph,
+    ir::{DType, Diagnostic, Model, ModelSummary, Severity, ShapeMismatch},
+};
 use tabled::{object::Rows, Alignment, Modify, Panel, Style, Table, Tabled};
 
-use super::load_devices;
+use super::{block_name, load_devices};
 
 #[derive(Tabled)]
 #[tabled(rename_all = "PascalCase")]
@@ -51,11 +54,18 @@ pub fn metrics_table(model_summary: ModelSummary) -> Table {
             total: model_summary.total_params.human_count_bare().to_string(),
         },
     ];
-    Table::new(metrics)
+    let mut table = Table::new(metrics)
         .with(Style::modern())
         .with(Modify::new(Rows::first()).with(Alignment::center()))
         .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
-        .to_owned()
+        .to_owned();
+    if !model_summary.unknown_nodes.is_empty() {
+        table.with(Panel::footer(format!(
+            "{} nodes had unknown shapes, totals are partial",
+            model_summary.unknown_nodes.len()
+        )));
+    }
+    table
 }
 
 #[derive(Tabled)]
@@ -85,3 +95,114 @@ pub fn hardware_table(total_flops: usize) -> Table {
         .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
         .to_owned()
 }
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct ShapeMismatchEntry {
+    op: String,
+    tensor: String,
+    inferred: String,
+    annotated: String,
+}
+
+pub fn shape_mismatch_table(mismatches: &[ShapeMismatch]) -> Table {
+    let entries: Vec<ShapeMismatchEntry> = mismatches
+        .iter()
+        .map(|m| ShapeMismatchEntry {
+            op: m.op.clone(),
+            tensor: m.tensor.clone(),
+            inferred: m.inferred.to_string().trim().to_string(),
+            annotated: m.annotated.to_string().trim().to_string(),
+        })
+        .collect();
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(format!(
+            "{} tensors disagree with value_info",
+            mismatches.len()
+        )))
+        .to_owned()
+}
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct DiagnosticEntry {
+    severity: String,
+    op: String,
+    node: String,
+    message: String,
+}
+
+pub fn diagnostics_table(diagnostics: &[Diagnostic]) -> Table {
+    let entries: Vec<DiagnosticEntry> = diagnostics
+        .iter()
+        .map(|d| DiagnosticEntry {
+            severity: format!("{:?}", d.severity),
+            op: d.op_type.clone(),
+            node: d.node_name.clone(),
+            message: d.message.clone(),
+        })
+        .collect();
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(format!(
+            "{} errors, {} warnings",
+            errors,
+            diagnostics.len() - errors
+        )))
+        .to_owned()
+}
+
+#[derive(Tabled)]
+#[tabled(rename_all = "PascalCase")]
+struct RepeatEntry {
+    block: String,
+    repeats: usize,
+    nodes: usize,
+    #[tabled(rename = "FLOPS/Block")]
+    flops: String,
+    #[tabled(rename = "Params/Block")]
+    params: String,
+    #[tabled(rename = "Total FLOPS")]
+    total_flops: String,
+}
+
+///Cost of each repeated block, counted once, beside how often it repeats
+pub fn repeats_table(model: &Model, summary: &ModelSummary, blocks: &[RepeatedSubgraph]) -> Table {
+    let entries: Vec<RepeatEntry> = blocks
+        .iter()
+        .map(|block| {
+            let cost = block.cost(model, summary);
+            RepeatEntry {
+                block: block_name(model, block),
+                repeats: block.count(),
+                nodes: block.len(),
+                flops: cost.flops.human_count_bare().to_string(),
+                params: cost.parameters.human_count_bare().to_string(),
+                total_flops: (cost.flops * block.count()).human_count_bare().to_string(),
+            }
+        })
+        .collect();
+    let covered: usize = blocks.iter().map(|b| b.len() * b.count()).sum();
+
+    Table::new(entries)
+        .with(Style::modern())
+        .with(Modify::new(Rows::first()).with(Alignment::center()))
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Panel::footer(format!(
+            "{} nodes in {} repeated blocks",
+            covered,
+            blocks.len()
+        )))
+        .to_owned()
+}
@@ -0,0 +1,415 @@
+use std::fmt::Write;
+
+use super::{
+    heat_stops, layout, Legend, NodeBox, Point, RenderableGraph, Scope, CLUSTER_LABEL_HEIGHT,
+    CLUSTER_PAD, FONT_SIZE, LINE_HEIGHT,
+};
+
+const FONT_FAMILY: &str = "Helvetica, Arial, sans-serif";
+const EDGE_FONT_SIZE: f64 = 12.;
+const HIGHLIGHT: &str = "red";
+const LEGEND_WIDTH: f64 = 180.;
+const LEGEND_HEIGHT: f64 = 72.;
+
+pub(crate) fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+///Smooth path through the points, leaving and entering each point vertically
+fn edge_path(points: &[Point]) -> String {
+    let mut d = format!("M{:.1},{:.1}", points[0].x, points[0].y);
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let mid = (a.y + b.y) / 2.;
+        write!(
+            d,
+            " C{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}",
+            a.x, mid, b.x, mid, b.x, b.y
+        )
+        .unwrap();
+    }
+    d
+}
+
+///Writes the lines of the label centered on the point, with any extra attributes on the text
+fn write_label(out: &mut String, label: &str, x: f64, y: f64, extra: &str) {
+    let lines: Vec<&str> = label.lines().collect();
+    let first = y - (lines.len() as f64 - 1.) * LINE_HEIGHT / 2.;
+    write!(
+        out,
+        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" dominant-baseline="central"{}>"#,
+        x, first, extra
+    )
+    .unwrap();
+    for (i, line) in lines.iter().enumerate() {
+        let dy = if i == 0 { 0. } else { LINE_HEIGHT };
+        write!(
+            out,
+            r#"<tspan x="{:.1}" dy="{}">{}</tspan>"#,
+            x,
+            dy,
+            escape_xml(line)
+        )
+        .unwrap();
+    }
+    out.push_str("</text>");
+}
+
+///Corners of the Graphviz polygon shapes drawn by the built-in renderer
+fn polygon(shape: &str, b: &NodeBox) -> Option<Vec<Point>> {
+    let (l, t, r, bottom) = (
+        b.x - b.width / 2.,
+        b.y - b.height / 2.,
+        b.x + b.width / 2.,
+        b.y + b.height / 2.,
+    );
+    let p = |x: f64, y: f64| Point { x, y };
+    let slant = b.width / 5.;
+    Some(match shape {
+        "diamond" => vec![p(b.x, t), p(r, b.y), p(b.x, bottom), p(l, b.y)],
+        "hexagon" => vec![
+            p(l + slant, t),
+            p(r - slant, t),
+            p(r, b.y),
+            p(r - slant, bottom),
+            p(l + slant, bottom),
+            p(l, b.y),
+        ],
+        "octagon" => {
+            let c = b.width.min(b.height) * 0.3;
+            vec![
+                p(l + c, t),
+                p(r - c, t),
+                p(r, t + c),
+                p(r, bottom - c),
+                p(r - c, bottom),
+                p(l + c, bottom),
+                p(l, bottom - c),
+                p(l, t + c),
+            ]
+        }
+        "parallelogram" => vec![p(l + slant, t), p(r, t), p(r - slant, bottom), p(l, bottom)],
+        "trapezium" => vec![p(l + slant, t), p(r - slant, t), p(r, bottom), p(l, bottom)],
+        _ => return None,
+    })
+}
+
+///SVG attributes drawing a Graphviz border style
+fn border_style(style: &str) -> &'static str {
+    if style.contains("dashed") {
+        r#" stroke-dasharray="5 3""#
+    } else if style.contains("dotted") {
+        r#" stroke-dasharray="1 3""#
+    } else {
+        ""
+    }
+}
+
+///Rectangle around the nodes of a scope and of its nested scopes
+struct ClusterBox<'a> {
+    scope: &'a Scope,
+    left: f64,
+    top: f64,
+    right: f64,
+    bottom: f64,
+}
+
+///Boxes of the scopes below `scope`, outermost first so inner ones are drawn on top.
+///Returns the extent of `scope` itself, unpadded.
+fn cluster_boxes<'a>(
+    scope: &'a Scope,
+    nodes: &[NodeBox],
+    out: &mut Vec<ClusterBox<'a>>,
+) -> Option<(f64, f64, f64, f64)> {
+    let mut extent: Option<(f64, f64, f64, f64)> = None;
+    let mut extend = |(l, t, r, b): (f64, f64, f64, f64)| {
+        extent = Some(match extent {
+            Some((l0, t0, r0, b0)) => (l0.min(l), t0.min(t), r0.max(r), b0.max(b)),
+            None => (l, t, r, b),
+        })
+    };
+    for &nd in scope.nodes.iter() {
+        let b = nodes[nd];
+        extend((
+            b.x - b.width / 2.,
+            b.y - b.height / 2.,
+            b.x + b.width / 2.,
+            b.y + b.height / 2.,
+        ));
+    }
+    for child in scope.children.iter() {
+        let i = out.len();
+        out.push(ClusterBox {
+            scope: child,
+            left: 0.,
+            top: 0.,
+            right: 0.,
+            bottom: 0.,
+        });
+        match cluster_boxes(child, nodes, out) {
+            Some((l, t, r, b)) => {
+                let padded = (
+                    l - CLUSTER_PAD,
+                    t - CLUSTER_PAD - CLUSTER_LABEL_HEIGHT,
+                    r + CLUSTER_PAD,
+                    b + CLUSTER_PAD,
+                );
+                (out[i].left, out[i].top, out[i].right, out[i].bottom) = padded;
+                extend(padded);
+            }
+            None => {
+                out.remove(i);
+            }
+        }
+    }
+    extent
+}
+
+///Renders the graph as SVG with the built-in layered layout, without Graphviz.
+///
+///Nodes and edges are groups of class `node` and `edge` titled with their DOT ids, like the
+///output of `dot -Tsvg`, so both can be wrapped by `render_html`.
+pub fn render_svg(graph: &RenderableGraph) -> String {
+    let theme = &graph.theme;
+    let foreground = escape_xml(theme.edge());
+    let layout = layout(graph);
+    let scopes = graph.scopes();
+    let mut clusters = vec![];
+    cluster_boxes(&scopes, &layout.nodes, &mut clusters);
+    //Clusters may reach past the margins of the layout, which is shifted to make room
+    let (mut left, mut top, mut right, mut bottom) = (0., 0., layout.width, layout.height);
+    for c in clusters.iter() {
+        left = f64::min(left, c.left - CLUSTER_PAD);
+        top = f64::min(top, c.top - CLUSTER_PAD);
+        right = f64::max(right, c.right + CLUSTER_PAD);
+        bottom = f64::max(bottom, c.bottom + CLUSTER_PAD);
+    }
+    let (dx, dy) = (0. - left, 0. - top);
+    //The legend sits to the right of the graph
+    let (width, height) = match graph.legend {
+        Some(_) => (right + dx + LEGEND_WIDTH, (bottom + dy).max(LEGEND_HEIGHT)),
+        None => (right + dx, bottom + dy),
+    };
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{h:.0}" viewBox="0 0 {w:.0} {h:.0}" font-family="{}" font-size="{}" fill="{}">"#,
+        escape_xml(theme.node.font.as_deref().unwrap_or(FONT_FAMILY)),
+        FONT_SIZE,
+        foreground,
+        w = width,
+        h = height,
+    )
+    .unwrap();
+    out.push_str("<defs>");
+    for (id, color) in [
+        ("arrow", foreground.as_str()),
+        ("arrow-highlight", HIGHLIGHT),
+    ] {
+        write!(
+            out,
+            r#"<marker id="{}" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="8" markerHeight="8" markerUnits="userSpaceOnUse" orient="auto"><path d="M0,0 L10,5 L0,10 z" fill="{}"/></marker>"#,
+            id, color
+        )
+        .unwrap();
+    }
+    if graph.legend.is_some() {
+        let stops = heat_stops(8);
+        out.push_str(r#"<linearGradient id="heat">"#);
+        for (i, color) in stops.iter().enumerate() {
+            write!(
+                out,
+                r#"<stop offset="{:.3}" stop-color="{}"/>"#,
+                i as f64 / (stops.len() - 1) as f64,
+                color
+            )
+            .unwrap();
+        }
+        out.push_str("</linearGradient>");
+    }
+    out.push_str("</defs>\n");
+    writeln!(
+        out,
+        r#"<rect width="100%" height="100%" fill="{}"/><g class="graph" transform="translate({:.1},{:.1})">"#,
+        escape_xml(theme.background()),
+        dx,
+        dy
+    )
+    .unwrap();
+
+    for c in clusters.iter() {
+        //Repeated blocks stand out from name scopes, with their badge in bold
+        let (class, cluster) = match c.scope.repeat {
+            Some(_) => ("repeat", &theme.repeat),
+            None => ("cluster", &theme.cluster),
+        };
+        let border = cluster.border_style.as_deref().unwrap_or("");
+        let bold = border.contains("bold");
+        let style = format!(
+            r#"fill="{}" stroke="{}" stroke-width="{}"{}"#,
+            escape_xml(cluster.fill.as_deref().unwrap_or("none")),
+            escape_xml(cluster.border.as_deref().unwrap_or("black")),
+            if bold { 2 } else { 1 },
+            border_style(border)
+        );
+        let mut text = String::new();
+        if bold {
+            text.push_str(r#" font-weight="bold""#);
+        }
+        if let Some(color) = &cluster.font_color {
+            write!(text, r#" fill="{}""#, escape_xml(color)).unwrap();
+        }
+        writeln!(
+            out,
+            r#"<g class="{}"><title>{}</title><rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="6" {}/><text x="{:.1}" y="{:.1}" font-size="{}"{}>{}</text></g>"#,
+            class,
+            escape_xml(&c.scope.path),
+            c.left,
+            c.top,
+            c.right - c.left,
+            c.bottom - c.top,
+            style,
+            c.left + 6.,
+            c.top + CLUSTER_LABEL_HEIGHT - 2.,
+            EDGE_FONT_SIZE,
+            text,
+            escape_xml(&c.scope.name)
+        )
+        .unwrap();
+    }
+
+    for (edge, path) in graph.edges.iter().zip(layout.edges.iter()) {
+        let (color, width, marker) = if edge.highlighted {
+            (HIGHLIGHT, edge.width.max(3.), "arrow-highlight")
+        } else {
+            (foreground.as_str(), edge.width, "arrow")
+        };
+        write!(
+            out,
+            r#"<g class="edge"><title>N{}&#45;&gt;N{}</title><path d="{}" fill="none" stroke="{}" stroke-width="{:.2}" marker-end="url(#{})"/>"#,
+            edge.from,
+            edge.to,
+            edge_path(&path.points),
+            color,
+            width,
+            marker
+        )
+        .unwrap();
+        let label = edge.label.trim();
+        if !label.is_empty() {
+            let mid = path.midpoint();
+            write!(
+                out,
+                r#"<text x="{:.1}" y="{:.1}" font-size="{}" dominant-baseline="central">{}</text>"#,
+                mid.x + 6.,
+                mid.y,
+                EDGE_FONT_SIZE,
+                escape_xml(label)
+            )
+            .unwrap();
+        }
+        out.push_str("</g>\n");
+    }
+
+    for (node, b) in graph.nodes.iter().zip(layout.nodes.iter()) {
+        let attribute = |name: &str, default: &'static str| node.attribute(name).unwrap_or(default);
+        let node_style = attribute("style", "");
+        let (stroke, width) = if node.highlighted {
+            (HIGHLIGHT.to_string(), 3.)
+        } else if node_style.contains("bold") {
+            (escape_xml(attribute("color", "black")), 2.)
+        } else {
+            (escape_xml(attribute("color", "black")), 1.)
+        };
+        let style = format!(
+            r#"fill="{}" stroke="{}" stroke-width="{}"{}"#,
+            escape_xml(attribute("fillcolor", "white")),
+            stroke,
+            width,
+            border_style(node_style)
+        );
+        let mut text = format!(r#" fill="{}""#, escape_xml(attribute("fontcolor", "black")));
+        if let Some(font) = node.attribute("fontname") {
+            write!(text, r#" font-family="{}""#, escape_xml(font)).unwrap();
+        }
+        write!(
+            out,
+            r#"<g class="node" id="node{}"><title>N{}</title>"#,
+            node.id, node.id
+        )
+        .unwrap();
+        let shape = attribute("shape", "ellipse");
+        match (shape, polygon(shape, b)) {
+            ("box" | "record" | "rect", _) => write!(
+                out,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" {}/>"#,
+                b.x - b.width / 2.,
+                b.y - b.height / 2.,
+                b.width,
+                b.height,
+                style
+            ),
+            (_, Some(corners)) => {
+                let points: Vec<String> = corners
+                    .iter()
+                    .map(|p| format!("{:.1},{:.1}", p.x, p.y))
+                    .collect();
+                write!(out, r#"<polygon points="{}" {}/>"#, points.join(" "), style)
+            }
+            _ => write!(
+                out,
+                r#"<ellipse cx="{:.1}" cy="{:.1}" rx="{:.1}" ry="{:.1}" {}/>"#,
+                b.x,
+                b.y,
+                b.width / 2.,
+                b.height / 2.,
+                style
+            ),
+        }
+        .unwrap();
+        write_label(&mut out, &node.text(), b.x, b.y, &text);
+        if !node.constants.is_empty() {
+            //Constants are listed below a rule, as in a Graphviz record
+            let lines = (node.label.lines().count() + node.constants.len()) as f64;
+            let y = b.y + (node.label.lines().count() as f64 - lines / 2.) * LINE_HEIGHT;
+            write!(
+                out,
+                r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="{}"/>"#,
+                b.x - b.width / 2.,
+                y,
+                b.x + b.width / 2.,
+                y,
+                stroke,
+                width
+            )
+            .unwrap();
+        }
+        out.push_str("</g>\n");
+    }
+    out.push_str("</g>\n");
+    if let Some(legend) = &graph.legend {
+        write_legend(&mut out, legend, right + dx + 12.);
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn write_legend(out: &mut String, legend: &Legend, x: f64) {
+    let bar = LEGEND_WIDTH - 30.;
+    write!(
+        out,
+        r#"<g class="legend" font-size="{size}"><text x="{x:.1}" y="20">{}</text><rect x="{x:.1}" y="28" width="{bar:.0}" height="12" fill="url(#heat)" stroke="black" stroke-width="0.5"/><text x="{x:.1}" y="56">{}</text><text x="{end:.1}" y="56" text-anchor="end">{}</text></g>"#,
+        escape_xml(&legend.title),
+        escape_xml(&legend.min),
+        escape_xml(&legend.max),
+        size = EDGE_FONT_SIZE,
+        x = x,
+        bar = bar,
+        end = x + bar,
+    )
+    .unwrap();
+    out.push('\n');
+}
@@ -0,0 +1,192 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ir::OpGroup;
+
+use super::Node;
+
+const PRESETS: &[(&str, &str)] = &[
+    ("light", include_str!("themes/light.toml")),
+    ("dark", include_str!("themes/dark.toml")),
+    ("print", include_str!("themes/print.toml")),
+];
+
+#[derive(thiserror::Error, Debug)]
+pub enum ThemeError {
+    #[error("Unknown theme {0}, expected light, dark, print or a .toml or .json file.")]
+    Unknown(String),
+    #[error("Failed to read theme {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Invalid TOML theme: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("Invalid JSON theme: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Theme styles unknown op group {0}.")]
+    UnknownGroup(String),
+}
+
+///Look of a node or cluster. Fields left out fall back to the style beneath.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NodeStyle {
+    pub fill: Option<String>,
+    pub shape: Option<String>, //Graphviz shape name, e.g. "box"
+    pub font: Option<String>,  //font family
+    pub font_color: Option<String>,
+    pub border: Option<String>,       //color of the outline
+    pub border_style: Option<String>, //"dashed", "dotted" or "bold"
+}
+
+impl NodeStyle {
+    ///Fields of `other` override those of `self`
+    fn merge(&mut self, other: &NodeStyle) {
+        let fields = [
+            (&mut self.fill, &other.fill),
+            (&mut self.shape, &other.shape),
+            (&mut self.font, &other.font),
+            (&mut self.font_color, &other.font_color),
+            (&mut self.border, &other.border),
+            (&mut self.border_style, &other.border_style),
+        ];
+        for (field, value) in fields {
+            if value.is_some() {
+                field.clone_from(value);
+            }
+        }
+    }
+}
+
+///Colors, shapes and fonts of a plot.
+///
+///Nodes are styled by their op type under `ops`, then by their op group under `groups`, then
+///by `node`. The nodes steelix adds itself are styled as the op types `Input`, `Output`,
+///`Constant`, `Scope` and `Stub`. A theme may extend a built-in one, overriding only the
+///fields it sets.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub extends: Option<String>, //name of a built-in theme
+    pub background: Option<String>,
+    pub edge: Option<String>, //color of edges and their labels
+    pub node: NodeStyle,
+    pub cluster: NodeStyle,                 //boxes around name scopes
+    pub repeat: NodeStyle,                  //boxes around repeated blocks
+    pub groups: HashMap<String, NodeStyle>, //by lowercase op group, e.g. "activation"
+    pub ops: HashMap<String, NodeStyle>,
+}
+
+impl Theme {
+    pub fn presets() -> Vec<&'static str> {
+        PRESETS.iter().map(|(name, _)| *name).collect()
+    }
+
+    ///The default theme
+    pub fn light() -> Self {
+        Self::preset("light").expect("Built-in themes are valid.")
+    }
+
+    ///A built-in theme by name
+    pub fn preset(name: &str) -> Result<Self, ThemeError> {
+        let (_, source) = PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .ok_or_else(|| ThemeError::Unknown(name.to_string()))?;
+        Self::from_toml(source)
+    }
+
+    ///A built-in theme by name, or else a theme file, TOML unless it ends in `.json`
+    pub fn load(name_or_path: &str) -> Result<Self, ThemeError> {
+        if PRESETS.iter().any(|(preset, _)| *preset == name_or_path) {
+            return Self::preset(name_or_path);
+        }
+        let path = Path::new(name_or_path);
+        if !path.is_file() {
+            return Err(ThemeError::Unknown(name_or_path.to_string()));
+        }
+        let source = std::fs::read_to_string(path).map_err(|source| ThemeError::Read {
+            path: name_or_path.to_string(),
+            source,
+        })?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json(&source),
+            _ => Self::from_toml(&source),
+        }
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, ThemeError> {
+        toml::from_str::<Theme>(source)?.resolve()
+    }
+
+    pub fn from_json(source: &str) -> Result<Self, ThemeError> {
+        serde_json::from_str::<Theme>(source)?.resolve()
+    }
+
+    ///Checks the group names, and applies the theme over the one it extends
+    fn resolve(self) -> Result<Self, ThemeError> {
+        let names: Vec<String> = OpGroup::ALL.iter().map(OpGroup::name).collect();
+        if let Some(unknown) = self.groups.keys().find(|g| !names.contains(g)) {
+            return Err(ThemeError::UnknownGroup(unknown.clone()));
+        }
+        let mut theme = match &self.extends {
+            Some(base) => Self::preset(base)?,
+            None => return Ok(self),
+        };
+        theme.background = self.background.or(theme.background);
+        theme.edge = self.edge.or(theme.edge);
+        theme.node.merge(&self.node);
+        theme.cluster.merge(&self.cluster);
+        theme.repeat.merge(&self.repeat);
+        for (styles, overrides) in [
+            (&mut theme.groups, &self.groups),
+            (&mut theme.ops, &self.ops),
+        ] {
+            for (key, style) in overrides.iter() {
+                styles.entry(key.clone()).or_default().merge(style);
+            }
+        }
+        Ok(theme)
+    }
+
+    pub fn background(&self) -> &str {
+        self.background.as_deref().unwrap_or("white")
+    }
+
+    pub fn edge(&self) -> &str {
+        self.edge.as_deref().unwrap_or("black")
+    }
+
+    ///Style of a node of the op type, in the op group if it has one
+    pub fn style(&self, group: Option<OpGroup>, op_type: &str) -> NodeStyle {
+        let mut style = self.node.clone();
+        if let Some(group) = group.and_then(|g| self.groups.get(&g.name())) {
+            style.merge(group);
+        }
+        if let Some(op) = self.ops.get(op_type) {
+            style.merge(op);
+        }
+        style
+    }
+}
+
+impl Node {
+    ///Sets the attributes of each field given by the style
+    pub fn apply_style(&mut self, style: &NodeStyle) {
+        let attributes = [
+            ("fillcolor", &style.fill),
+            ("shape", &style.shape),
+            ("fontname", &style.font),
+            ("fontcolor", &style.font_color),
+            ("color", &style.border),
+            ("style", &style.border_style),
+        ];
+        for (name, value) in attributes {
+            if let Some(value) = value {
+                self.add_attribute((name, value.clone()));
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt::Display;
+
+use crate::ir::Shape;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+///The phase of analysis which produced a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    Parse,
+    Realize,
+}
+
+///A problem found while analyzing a model, with enough context to find the offending node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub stage: Stage,
+    pub node_id: Option<usize>,
+    pub node_name: String, //name of the node in the ONNX graph
+    pub op_type: String,
+    pub opset: Option<i64>,
+    pub provider_shapes: Vec<Shape>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, stage: Stage, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            stage,
+            node_id: None,
+            node_name: String::new(),
+            op_type: String::new(),
+            opset: None,
+            provider_shapes: vec![],
+            message: message.into(),
+        }
+    }
+
+    pub fn error(stage: Stage, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, stage, message)
+    }
+
+    pub fn warning(stage: Stage, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, stage, message)
+    }
+
+    pub fn with_node(mut self, node_id: Option<usize>, node_name: &str, op_type: &str) -> Self {
+        self.node_id = node_id;
+        self.node_name = node_name.to_string();
+        self.op_type = op_type.to_string();
+        self
+    }
+
+    pub fn with_opset(mut self, opset: Option<i64>) -> Self {
+        self.opset = opset;
+        self
+    }
+
+    pub fn with_provider_shapes(mut self, provider_shapes: Vec<Shape>) -> Self {
+        self.provider_shapes = provider_shapes;
+        self
+    }
+
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        let stage = match self.stage {
+            Stage::Parse => "parse",
+            Stage::Realize => "realize",
+        };
+        write!(f, "{}[{}]", severity, stage)?;
+        if !self.op_type.is_empty() {
+            write!(f, " {}", self.op_type)?;
+        }
+        if !self.node_name.is_empty() {
+            write!(f, " '{}'", self.node_name)?;
+        }
+        if let Some(opset) = self.opset {
+            write!(f, " (opset {})", opset)?;
+        }
+        if !self.provider_shapes.is_empty() {
+            let shapes = self
+                .provider_shapes
+                .iter()
+                .map(|s| s.to_string().trim().to_string())
+                .collect::<Vec<_>>();
+            write!(f, " with inputs [{}]", shapes.join(", "))?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
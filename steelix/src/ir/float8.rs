@@ -0,0 +1,97 @@
+use std::fmt;
+
+//Float8 formats as defined by ONNX: https://onnx.ai/onnx/technical/float8.html
+//Steelix never computes in these types, they only need to be stored, compared and printed.
+macro_rules! float8 {
+    ($F8:ident, $exp_bits:literal, $bias:literal, $has_inf:literal, $uz:literal) => {
+        #[repr(transparent)]
+        #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+        pub struct $F8(pub u8);
+
+        impl $F8 {
+            #[inline]
+            pub fn from_bits(bits: u8) -> Self {
+                Self(bits)
+            }
+
+            #[inline]
+            pub fn to_bits(self) -> u8 {
+                self.0
+            }
+
+            pub fn to_f32(self) -> f32 {
+                f8_to_f32(self.0, $exp_bits, $bias, $has_inf, $uz)
+            }
+        }
+
+        impl fmt::Display for $F8 {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.to_f32())
+            }
+        }
+    };
+}
+
+float8!(F8E4M3FN, 4, 7, false, false);
+float8!(F8E4M3FNUZ, 4, 8, false, true);
+float8!(F8E5M2, 5, 15, true, false);
+float8!(F8E5M2FNUZ, 5, 16, false, true);
+
+///Decodes an 8 bit float with the provided exponent width and bias.
+///`uz` formats have no negative zero, and use 0x80 as their only NaN.
+fn f8_to_f32(bits: u8, exp_bits: u32, bias: i32, has_inf: bool, uz: bool) -> f32 {
+    let man_bits = 7 - exp_bits;
+    let sign = if bits & 0x80 != 0 { -1.0 } else { 1.0 };
+    let exp = ((bits & 0x7F) >> man_bits) as i32;
+    let man = (bits & ((1 << man_bits) - 1)) as i32;
+    let max_exp = (1 << exp_bits) - 1;
+
+    if uz && bits == 0x80 {
+        return f32::NAN;
+    }
+    if !uz && exp == max_exp {
+        if has_inf {
+            return if man == 0 {
+                sign * f32::INFINITY
+            } else {
+                f32::NAN
+            };
+        } else if man == (1 << man_bits) - 1 {
+            return f32::NAN;
+        }
+    }
+
+    let scale = (1 << man_bits) as f32;
+    if exp == 0 {
+        sign * (man as f32 / scale) * 2f32.powi(1 - bias)
+    } else {
+        sign * (1.0 + man as f32 / scale) * 2f32.powi(exp - bias)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn e4m3fn_bounds() {
+        assert_eq!(F8E4M3FN(0x7E).to_f32(), 448.0);
+        assert!(F8E4M3FN(0x7F).to_f32().is_nan());
+        assert_eq!(F8E4M3FN(0x01).to_f32(), 2f32.powi(-9));
+    }
+
+    #[test]
+    fn e5m2_bounds() {
+        assert_eq!(F8E5M2(0x7B).to_f32(), 57344.0);
+        assert_eq!(F8E5M2(0x7C).to_f32(), f32::INFINITY);
+        assert!(F8E5M2(0x7D).to_f32().is_nan());
+    }
+
+    #[test]
+    fn fnuz_nan() {
+        assert!(F8E4M3FNUZ(0x80).to_f32().is_nan());
+        assert!(F8E5M2FNUZ(0x80).to_f32().is_nan());
+        assert_eq!(F8E4M3FNUZ(0x7F).to_f32(), 240.0);
+        assert_eq!(F8E5M2FNUZ(0x7F).to_f32(), 57344.0);
+    }
+}
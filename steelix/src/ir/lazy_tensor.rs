@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use steelix_onnx::onnx_pb::{self, tensor_proto::DataType as ProtoDType};
+
+use crate::ir::{DType, IntoArcTensor, Shape, Tensor};
+
+///Initializers with more elements than this are never read in `WeightLoading::ShapesOnly`.
+///Tensors consumed by shape operations (Reshape, Gather indices, etc.) are far smaller.
+pub const SHAPE_TENSOR_LIMIT: usize = 1024;
+
+///How initializer data is loaded when parsing a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WeightLoading {
+    ///Every initializer is decoded up front
+    #[default]
+    Eager,
+    ///Initializers borrow from the memory mapped model. Each is decoded when realized and
+    ///dropped once its consumers have run.
+    Lazy,
+    ///Only initializers small enough to be shape inputs are ever decoded
+    ShapesOnly,
+}
+
+impl std::str::FromStr for WeightLoading {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "eager" => Ok(WeightLoading::Eager),
+            "lazy" => Ok(WeightLoading::Lazy),
+            "shapes-only" => Ok(WeightLoading::ShapesOnly),
+            _ => anyhow::bail!("Unknown weight loading mode: {}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum LazySource {
+    ///`raw_data` of the proto borrows from a memory map, so holding it is cheap
+    Proto(Box<onnx_pb::TensorProto>),
+    Skipped,
+}
+
+///An initializer whose data has not yet been decoded.
+#[derive(Debug, Clone)]
+pub struct LazyTensor {
+    pub name: String,
+    pub dt: DType,
+    pub shape: Shape,
+    source: LazySource,
+}
+
+impl LazyTensor {
+    pub fn new(tproto: onnx_pb::TensorProto, loading: WeightLoading) -> anyhow::Result<Self> {
+        let dt: DType = ProtoDType::from_i32(tproto.data_type)
+            .ok_or_else(|| anyhow::anyhow!("Unknown tensor data type: {}", tproto.data_type))?
+            .try_into()?;
+        let shape = Shape(tproto.dims.iter().map(|&i| i as usize).collect());
+        let name = tproto.name.clone();
+        let source = if loading == WeightLoading::ShapesOnly
+            && shape.iter().product::<usize>() > SHAPE_TENSOR_LIMIT
+        {
+            LazySource::Skipped
+        } else {
+            LazySource::Proto(Box::new(tproto))
+        };
+        Ok(Self {
+            name,
+            dt,
+            shape,
+            source,
+        })
+    }
+
+    pub fn numel(&self) -> usize {
+        self.shape.iter().product::<usize>()
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        matches!(self.source, LazySource::Skipped)
+    }
+
+    ///Decodes the tensor data.
+    ///Skipped tensors are materialized with their shape alone, holding no buffer.
+    pub fn materialize(&self) -> anyhow::Result<Arc<Tensor>> {
+        match &self.source {
+            LazySource::Proto(tproto) => {
+                Ok(Tensor::try_from((**tproto).clone())?.into_arc_tensor())
+            }
+            LazySource::Skipped => {
+                Ok(Tensor::shape_only(self.dt, self.shape.clone()).into_arc_tensor())
+            }
+        }
+    }
+}
@@ -1,10 +1,15 @@
 //Operator set is defined here: https://github.com/onnx/onnx/blob/main/onnx/defs/operator_sets.h
+mod diagnostic;
+mod float8;
+mod lazy_tensor;
 mod model;
+mod model_builder;
 mod op_group;
 mod op_node;
 mod op_register;
 mod shape;
 mod tensor;
+pub(crate) mod text;
 mod value_info;
 
 pub mod ops;
@@ -13,15 +18,20 @@ use anyhow::bail;
 use smallvec::SmallVec;
 use std::{borrow::Cow, sync::Arc};
 
+pub use diagnostic::*;
+pub use float8::*;
+pub use lazy_tensor::*;
 pub use model::*;
+pub use model_builder::*;
 pub use op_group::*;
 pub use op_node::*;
 pub use op_register::*;
 pub use shape::*;
 pub use tensor::*;
+pub use text::{format_attribute, MAX_PRINTED_ELEMENTS};
 pub use value_info::*;
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct OpCost {
     pub flops: usize,
     pub parameters: usize,
@@ -79,6 +89,12 @@ pub trait Op {
     ///Computes the cost of the operation and propagates the tensors forward
     ///with the appropriate shape updates
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp>;
+
+    ///Whether `realize` accepts providers of partially known shape and carries their
+    ///facts onto its outputs. Other ops are only trusted with fully known shapes.
+    fn propagates_facts(&self) -> bool {
+        false
+    }
 }
 
 pub type BoxOp = Box<dyn Op>;
@@ -102,6 +118,19 @@ pub fn validate_providers(
     }
 }
 
+pub fn validate_rank(tensor: &Tensor, rank: usize, name: &str) -> anyhow::Result<()> {
+    if tensor.rank() != rank {
+        bail!(
+            "Expected a tensor of rank {}, got: {} in operation: {}",
+            rank,
+            tensor.shape.to_string().trim(),
+            name
+        )
+    } else {
+        Ok(())
+    }
+}
+
 #[macro_export]
 macro_rules! elementwise {
     ($Op:ident, $group:ident, $flop:literal) => {
@@ -124,6 +153,10 @@ macro_rules! elementwise {
                     outputs: pvec![providers[0].clone()],
                 })
             }
+
+            fn propagates_facts(&self) -> bool {
+                true
+            }
         }
     };
 }
@@ -132,7 +165,7 @@ macro_rules! elementwise {
 macro_rules! shape {
     (@one $x:expr) => (1usize);
     ($elem:expr; $n:expr) => ({
-        $crate::Shape::from_elem($elem, $n)
+        $crate::Shape(smallvec::SmallVec::from_elem($elem, $n))
     });
     ($($x:expr),*$(,)*) => ({
         let count = 0usize $(+ shape!(@one $x))*;
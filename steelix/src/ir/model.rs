@@ -1,4 +1,7 @@
-use crate::ir::{BoxOp, IntoArcTensor, Op, OpGroup, OpNode, PVec, RealizedOp, Shape, Tensor};
+use crate::ir::{
+    BoxOp, Diagnostic, DimFact, IntoArcTensor, IntoTensor, Op, OpCost, OpGroup, OpNode, PVec,
+    RealizedOp, Shape, ShapeFact, Stage, Tensor, ValueInfo,
+};
 use crate::pvec;
 
 impl<T: Op + ?Sized> Op for Box<T> {
@@ -16,6 +19,11 @@ impl<T: Op + ?Sized> Op for Box<T> {
     fn realize(&self, provider: PVec) -> anyhow::Result<RealizedOp> {
         (**self).realize(provider)
     }
+
+    #[inline]
+    fn propagates_facts(&self) -> bool {
+        (**self).propagates_facts()
+    }
 }
 
 use core::fmt::Debug;
@@ -29,12 +37,17 @@ impl Debug for dyn Op {
     }
 }
 
+///Size of a symbolic dimension when probing which output dimensions follow it
+const PROBE_SIZE: usize = 2;
+
 #[derive(thiserror::Error, Debug)]
 pub enum ModelError {
     #[error("{0}")]
     ValidationError(String),
     #[error("{0}")]
     UnsupportedType(String),
+    #[error("{0}")]
+    Diagnostic(Box<Diagnostic>),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -49,6 +62,9 @@ pub struct Model {
     pub inputs: Vec<usize>,  //IDs of input nodes
     pub outputs: Vec<usize>, //IDs of output nodes
     pub traversal_order: Option<Vec<usize>>,
+    pub value_info: HashMap<String, ValueInfo>, //shape annotations from the ONNX file
+    pub opset_imports: HashMap<String, i64>,    //opset version of each domain
+    pub diagnostics: Vec<Diagnostic>,           //problems found while parsing
 }
 
 #[derive(Debug, Default)]
@@ -62,6 +78,22 @@ pub struct ModelSummary {
     pub total_params: usize,
     pub op_frequencies: HashMap<String, usize>,
     pub output_shapes: HashMap<usize, Shape>,
+    pub hinted_nodes: Vec<usize>, //Unimplemented nodes whose outputs came from value_info
+    pub shape_mismatches: Vec<ShapeMismatch>,
+    pub inferred_tensors: HashMap<String, ValueInfo>, //keyed by tensor name
+    pub unknown_nodes: Vec<usize>, //nodes whose outputs, and therefore cost, are not fully known
+    pub diagnostics: Vec<Diagnostic>,
+    pub node_costs: HashMap<usize, OpCost>, //cost of each realized node
+}
+
+///An inferred shape which disagrees with the value_info annotation of the same tensor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeMismatch {
+    pub node_id: usize,
+    pub op: String,
+    pub tensor: String,
+    pub inferred: Shape,
+    pub annotated: Shape,
 }
 
 impl Model {
@@ -78,13 +110,140 @@ impl Model {
         self.nodes.push(OpNode {
             id,
             name,
+            onnx_name: String::new(),
+            domain: String::new(),
             op,
             providers: vec![],
             consumers: vec![],
+            inputs: vec![],
+            outputs: vec![],
+            attributes: vec![],
         });
         id
     }
 
+    ///Opset version the node was exported with
+    pub fn node_opset(&self, node_id: usize) -> Option<i64> {
+        let domain = match &*self.nodes[node_id].domain {
+            "ai.onnx" => "",
+            d => d,
+        };
+        self.opset_imports.get(domain).copied()
+    }
+
+    ///A diagnostic carrying the context of the provided node
+    pub fn node_diagnostic(&self, node_id: usize, diagnostic: Diagnostic) -> Diagnostic {
+        let node = &self.nodes[node_id];
+        diagnostic
+            .with_node(Some(node_id), &node.onnx_name, &node.name)
+            .with_opset(self.node_opset(node_id))
+    }
+
+    ///Placeholder outputs built from value_info, if every output of the node is annotated
+    fn hinted_outputs(value_info: &HashMap<String, ValueInfo>, outputs: &[String]) -> Option<PVec> {
+        if outputs.is_empty() {
+            return None;
+        }
+        outputs
+            .iter()
+            .map(|name| {
+                let hint = value_info.get(name)?;
+                Some(hint.to_tensor().into_arc_tensor())
+            })
+            .collect()
+    }
+
+    ///Ops that don't propagate facts compute symbolic dimensions as 1. The node is realized again
+    ///with each symbol at `PROBE_SIZE`, and the output dimensions which follow it, being 1 and then
+    ///`PROBE_SIZE`, take its name. Outputs are kept as they are when a probe fails.
+    fn probe_symbols(&self, node_id: usize, providers: &PVec, outputs: PVec) -> PVec {
+        let mut symbols: Vec<String> = vec![];
+        for provider in providers.iter() {
+            for axis in 0..provider.rank() {
+                if let DimFact::Symbolic(name) = provider.fact.dim(axis) {
+                    if !symbols.contains(&name) {
+                        symbols.push(name);
+                    }
+                }
+            }
+        }
+        if symbols.is_empty() {
+            return outputs;
+        }
+
+        let mut facts: Vec<Vec<DimFact>> = outputs
+            .iter()
+            .map(|output| {
+                (0..output.rank())
+                    .map(|axis| output.fact.dim(axis))
+                    .collect()
+            })
+            .collect();
+        for symbol in symbols {
+            let symbolic = DimFact::Symbolic(symbol);
+            //Probed providers hold no data, their values would not match the shape
+            let probe: PVec = providers
+                .iter()
+                .map(|provider| {
+                    if !(0..provider.rank()).any(|axis| provider.fact.dim(axis) == symbolic) {
+                        return provider.clone();
+                    }
+                    let shape = (0..provider.rank())
+                        .map(|axis| match provider.fact.dim(axis) == symbolic {
+                            true => PROBE_SIZE,
+                            false => provider.shape[axis],
+                        })
+                        .collect();
+                    Tensor::shape_only(provider.dt, Shape(shape))
+                        .with_fact(provider.fact.clone())
+                        .into_arc_tensor()
+                })
+                .collect();
+            let probed = match self.nodes[node_id].realize(probe) {
+                Ok(probed) if probed.outputs.len() == outputs.len() => probed.outputs,
+                _ => continue,
+            };
+            for ((fact, output), probed) in facts.iter_mut().zip(outputs.iter()).zip(probed.iter())
+            {
+                if probed.rank() != output.rank() {
+                    continue;
+                }
+                for (axis, dim) in fact.iter_mut().enumerate() {
+                    if *dim == DimFact::Known
+                        && output.shape[axis] == 1
+                        && probed.shape[axis] == PROBE_SIZE
+                    {
+                        *dim = symbolic.clone();
+                    }
+                }
+            }
+        }
+
+        outputs
+            .into_iter()
+            .zip(facts)
+            .map(|(output, fact)| match ShapeFact::from_dims(fact) {
+                ShapeFact::Known => output,
+                fact => output.into_tensor().with_fact(fact).into_arc_tensor(),
+            })
+            .collect()
+    }
+
+    ///Tensors read by a node, with the id of the node producing each.
+    ///Nodes without named inputs read every output of their providers.
+    pub fn input_tensors(&self, node_id: usize) -> Vec<(usize, String)> {
+        let node = &self.nodes[node_id];
+        let mut tensors = vec![];
+        for &provider in node.providers.iter() {
+            for tensor in self.nodes[provider].outputs.iter() {
+                if node.inputs.is_empty() || node.inputs.contains(tensor) {
+                    tensors.push((provider, tensor.clone()));
+                }
+            }
+        }
+        tensors
+    }
+
     pub fn add_edge(&mut self, producer_id: usize, consumer_id: usize) {
         let producer = &mut self.nodes[producer_id];
         producer.consumers.push(consumer_id);
@@ -128,9 +287,24 @@ impl Model {
         self
     }
 
+    ///Propagates shapes through the graph, stopping at the first error
     pub fn run(&mut self) -> Result<ModelSummary, ModelError> {
-        let mut order = self.traversal_order.clone().unwrap();
-        order.pop(); //remove the final node
+        self.run_impl(false)
+    }
+
+    ///Propagates shapes through the whole graph, collecting every error as a diagnostic.
+    ///Outputs of failed nodes are treated as unknown.
+    pub fn run_keep_going(&mut self) -> Result<ModelSummary, ModelError> {
+        self.run_impl(true)
+    }
+
+    fn run_impl(&mut self, keep_going: bool) -> Result<ModelSummary, ModelError> {
+        if !keep_going {
+            if let Some(error) = self.diagnostics.iter().find(|d| d.is_error()) {
+                return Err(ModelError::Diagnostic(Box::new(error.clone())));
+            }
+        }
+        let order = self.traversal_order.clone().unwrap();
         let mut traversal_state = TraversalState {
             intermediates: HashMap::new(),
         };
@@ -138,10 +312,28 @@ impl Model {
         let mut total_flops = 0;
         let mut total_params = 0;
         let mut output_shapes = HashMap::new();
+        let mut hinted_nodes = vec![];
+        let mut shape_mismatches = vec![];
+        let mut inferred_tensors = HashMap::new();
+        let mut unknown_nodes = vec![];
+        let mut node_costs = HashMap::new();
+        let mut diagnostics = self.diagnostics.clone();
+
+        //Outputs are dropped once every consumer has run, so lazily loaded weights are only
+        //held while they are needed
+        let mut pending_reads: HashMap<usize, usize> = HashMap::new();
+        for &node_id in order.iter().filter(|id| !self.outputs.contains(id)) {
+            for &provider in self.nodes[node_id].providers.iter() {
+                *pending_reads.entry(provider).or_insert(0) += 1;
+            }
+        }
 
         let mut op_counts = HashMap::new();
         for node_id in order {
-            let node = &mut self.nodes[node_id];
+            if self.outputs.contains(&node_id) {
+                continue; //output nodes only mark graph outputs
+            }
+            let node = &self.nodes[node_id];
 
             if node.op.op_group() != OpGroup::Constant {
                 *op_counts.entry(node.name.to_owned()).or_insert(0) += 1;
@@ -159,20 +351,126 @@ impl Model {
                     )
                 })
                 .collect();
-            let result = node.realize(providers)?;
+
+            //Unless they propagate facts, ops are never asked to realize tensors of unknown rank
+            let unknown_rank = providers.iter().any(|p| p.fact.is_unknown_rank());
+            let unknown_dims = providers.iter().any(|p| !p.is_known());
+            let propagates = node.op.propagates_facts();
+            let dt = providers.first().map(|p| p.dt).unwrap_or_default();
+            let realized = if unknown_rank && !propagates {
+                Ok(RealizedOp::default())
+            } else {
+                node.realize(providers.clone())
+            };
+            let mut result = match realized {
+                Ok(result) => result,
+                Err(e) => {
+                    let diagnostic = self
+                        .node_diagnostic(
+                            node_id,
+                            Diagnostic::error(Stage::Realize, format!("{:#}", e)),
+                        )
+                        .with_provider_shapes(providers.iter().map(|p| p.shape.clone()).collect());
+                    if !keep_going {
+                        return Err(ModelError::Diagnostic(Box::new(diagnostic)));
+                    }
+                    diagnostics.push(diagnostic);
+                    RealizedOp::default()
+                }
+            };
+
+            if result.outputs.is_empty() {
+                if let Some(hinted) = Self::hinted_outputs(&self.value_info, &node.outputs) {
+                    result.outputs = hinted;
+                    hinted_nodes.push(node_id);
+                } else {
+                    let n_outputs = node.outputs.len().max(1);
+                    result.outputs = (0..n_outputs)
+                        .map(|_| Tensor::unknown(dt).into_arc_tensor())
+                        .collect();
+                    unknown_nodes.push(node_id);
+                }
+            } else if unknown_dims && propagates {
+                //The outputs carry what is known of their shapes, the cost may be a placeholder
+                if unknown_rank {
+                    result.cost = OpCost::zero_cost();
+                }
+                if result.cost != OpCost::zero_cost()
+                    || result.outputs.iter().any(|o| !o.is_known())
+                {
+                    unknown_nodes.push(node_id);
+                }
+            } else if unknown_dims {
+                //Computed from placeholders, so neither the shapes nor the cost can be trusted
+                result.outputs = result
+                    .outputs
+                    .into_iter()
+                    .map(|o| o.into_tensor().with_unknown_dims().into_arc_tensor())
+                    .collect();
+                unknown_nodes.push(node_id);
+            } else {
+                if !propagates {
+                    result.outputs = self.probe_symbols(node_id, &providers, result.outputs);
+                }
+                for (tensor, output) in node.outputs.iter().zip(result.outputs.iter()) {
+                    match self.value_info.get(tensor) {
+                        Some(hint) if !hint.matches(&output.shape) => {
+                            diagnostics.push(self.node_diagnostic(
+                                node_id,
+                                Diagnostic::warning(
+                                    Stage::Realize,
+                                    format!(
+                                        "inferred shape of {} is {} but value_info has {}",
+                                        tensor,
+                                        output.shape.to_string().trim(),
+                                        hint.dimensions.to_string().trim()
+                                    ),
+                                ),
+                            ));
+                            shape_mismatches.push(ShapeMismatch {
+                                node_id,
+                                op: node.name.clone(),
+                                tensor: tensor.clone(),
+                                inferred: output.shape.clone(),
+                                annotated: hint.dimensions.clone(),
+                            })
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            for (tensor, output) in node.outputs.iter().zip(result.outputs.iter()) {
+                if !output.fact.is_unknown_rank() {
+                    inferred_tensors.insert(tensor.clone(), ValueInfo::from_tensor(tensor, output));
+                }
+            }
             total_flops += result.cost.flops;
             total_params += result.cost.parameters;
+            node_costs.insert(node_id, result.cost);
             output_shapes.insert(node_id, result.outputs[0].shape.clone());
 
             traversal_state
                 .intermediates
                 .insert(node_id, result.outputs);
+            for provider in node.providers.iter() {
+                let reads = pending_reads.get_mut(provider).unwrap();
+                *reads -= 1;
+                if *reads == 0 {
+                    traversal_state.intermediates.remove(provider);
+                }
+            }
         }
         Ok(ModelSummary {
             total_flops,
             total_params,
             op_frequencies: op_counts,
             output_shapes,
+            hinted_nodes,
+            shape_mismatches,
+            inferred_tensors,
+            unknown_nodes,
+            diagnostics,
+            node_costs,
         })
     }
 }
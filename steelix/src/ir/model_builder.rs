@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+
+use steelix_onnx::onnx_pb::{self, attribute_proto::AttributeType};
+
+use crate::ir::{
+    ops, text::node_attributes, BoxOp, DType, Model, OpRegister, Shape, Tensor, ValueInfo,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum BuilderError {
+    #[error("Tensor {0} is produced more than once.")]
+    DuplicateTensor(String),
+    #[error("Node {node} reads {tensor}, which no input, constant or node produces.")]
+    UnknownTensor { node: String, tensor: String },
+    #[error("Graph output {0} is not produced by any input, constant or node.")]
+    UnknownOutput(String),
+    #[error("Operator {0} is not supported.")]
+    UnsupportedOp(String),
+    #[error("Failed to build node {node}: {source}")]
+    InvalidNode {
+        node: String,
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+///Value of a node attribute, converted from the matching Rust type
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Int(i64),
+    Float(f32),
+    String(String),
+    Ints(Vec<i64>),
+    Floats(Vec<f32>),
+}
+
+impl From<i64> for AttributeValue {
+    fn from(value: i64) -> Self {
+        AttributeValue::Int(value)
+    }
+}
+
+impl From<f32> for AttributeValue {
+    fn from(value: f32) -> Self {
+        AttributeValue::Float(value)
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        AttributeValue::String(value.to_string())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        AttributeValue::String(value)
+    }
+}
+
+impl From<Vec<i64>> for AttributeValue {
+    fn from(value: Vec<i64>) -> Self {
+        AttributeValue::Ints(value)
+    }
+}
+
+impl From<&[i64]> for AttributeValue {
+    fn from(value: &[i64]) -> Self {
+        AttributeValue::Ints(value.to_vec())
+    }
+}
+
+impl From<Vec<f32>> for AttributeValue {
+    fn from(value: Vec<f32>) -> Self {
+        AttributeValue::Floats(value)
+    }
+}
+
+impl AttributeValue {
+    fn into_proto(self, name: &str) -> onnx_pb::AttributeProto {
+        let mut proto = onnx_pb::AttributeProto {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let attribute_type = match self {
+            AttributeValue::Int(i) => {
+                proto.i = i;
+                AttributeType::Int
+            }
+            AttributeValue::Float(f) => {
+                proto.f = f;
+                AttributeType::Float
+            }
+            AttributeValue::String(s) => {
+                proto.s = s.into_bytes();
+                AttributeType::String
+            }
+            AttributeValue::Ints(ints) => {
+                proto.ints = ints;
+                AttributeType::Ints
+            }
+            AttributeValue::Floats(floats) => {
+                proto.floats = floats;
+                AttributeType::Floats
+            }
+        };
+        proto.r#type = attribute_type as i32;
+        proto
+    }
+}
+
+///A node to be added by `ModelBuilder::op`
+#[derive(Debug, Clone)]
+pub struct NodeSpec {
+    proto: onnx_pb::NodeProto,
+}
+
+impl NodeSpec {
+    pub fn new(op_type: &str, inputs: &[&str], outputs: &[&str]) -> Self {
+        Self {
+            proto: onnx_pb::NodeProto {
+                op_type: op_type.to_string(),
+                input: inputs.iter().map(|s| s.to_string()).collect(),
+                output: outputs.iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn named(mut self, name: &str) -> Self {
+        self.proto.name = name.to_string();
+        self
+    }
+
+    pub fn domain(mut self, domain: &str) -> Self {
+        self.proto.domain = domain.to_string();
+        self
+    }
+
+    pub fn attr(mut self, name: &str, value: impl Into<AttributeValue>) -> Self {
+        self.proto.attribute.push(value.into().into_proto(name));
+        self
+    }
+
+    fn display_name(&self) -> String {
+        if self.proto.name.is_empty() {
+            self.proto.op_type.clone()
+        } else {
+            self.proto.name.clone()
+        }
+    }
+}
+
+///Builds a `Model` in Rust, without an ONNX file.
+///
+///Tensors are wired by name as in ONNX: each node reads the tensors named by its inputs, which
+///must be produced by a graph input, a constant or another node. Ops are built with the same
+///registry as the parser, so attributes take their ONNX names and defaults.
+///
+///```ignore
+///let model = ModelBuilder::new()
+///    .input("x", DType::F32, shape![1, 8])
+///    .constant("w", Tensor::zeros::<f32>(shape![8, 4]))
+///    .op(NodeSpec::new("MatMul", &["x", "w"], &["h"]).named("fc"))
+///    .node("Relu", &["h"], &["y"])
+///    .output("y")
+///    .build()?
+///    .build_traversal_order();
+///```
+#[derive(Default)]
+pub struct ModelBuilder {
+    inputs: Vec<ValueInfo>,
+    constants: Vec<(String, Tensor)>,
+    nodes: Vec<NodeSpec>,
+    outputs: Vec<String>,
+    opset_imports: HashMap<String, i64>,
+    register: OpRegister,
+}
+
+impl ModelBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn input(self, name: &str, dt: DType, dimensions: Shape) -> Self {
+        self.input_info(ValueInfo {
+            name: name.to_string(),
+            dt,
+            dim_params: vec![None; dimensions.len()],
+            dimensions,
+        })
+    }
+
+    ///Adds an input from its full annotation, such as one with symbolic dimensions
+    pub fn input_info(mut self, value_info: ValueInfo) -> Self {
+        self.inputs.push(value_info);
+        self
+    }
+
+    pub fn constant(mut self, name: &str, tensor: Tensor) -> Self {
+        self.constants.push((name.to_string(), tensor));
+        self
+    }
+
+    ///Adds a node without attributes
+    pub fn node(self, op_type: &str, inputs: &[&str], outputs: &[&str]) -> Self {
+        self.op(NodeSpec::new(op_type, inputs, outputs))
+    }
+
+    pub fn op(mut self, node: NodeSpec) -> Self {
+        self.nodes.push(node);
+        self
+    }
+
+    pub fn output(mut self, name: &str) -> Self {
+        self.outputs.push(name.to_string());
+        self
+    }
+
+    ///Opset version of a domain, the default domain is the empty string
+    pub fn opset(mut self, domain: &str, version: i64) -> Self {
+        self.opset_imports.insert(domain.to_string(), version);
+        self
+    }
+
+    pub fn build(self) -> Result<Model, BuilderError> {
+        let mut model = Model::new();
+        model.opset_imports = self.opset_imports;
+        let mut producers: HashMap<String, usize> = HashMap::new();
+        let mut produce = |tensor: &str, id: usize| -> Result<(), BuilderError> {
+            match producers.insert(tensor.to_string(), id) {
+                Some(_) => Err(BuilderError::DuplicateTensor(tensor.to_string())),
+                None => Ok(()),
+            }
+        };
+
+        for value_info in self.inputs {
+            let name = value_info.name.clone();
+            let id = model.add_node(name.clone(), ops::data::build_initial(value_info).unwrap());
+            model.nodes[id].outputs = vec![name.clone()];
+            model.inputs.push(id);
+            produce(&name, id)?;
+        }
+        for (name, tensor) in self.constants {
+            let id = model.add_node(name.clone(), ops::data::build_constant(tensor).unwrap());
+            model.nodes[id].outputs = vec![name.clone()];
+            produce(&name, id)?;
+        }
+
+        let graph_offset = model.nodes.len();
+        for node in self.nodes.iter() {
+            let builder = self
+                .register
+                .get(&node.proto.op_type)
+                .ok_or_else(|| BuilderError::UnsupportedOp(node.proto.op_type.clone()))?;
+            let op: BoxOp = builder(&node.proto).map_err(|source| BuilderError::InvalidNode {
+                node: node.display_name(),
+                source,
+            })?;
+            let id = model.add_node(node.proto.op_type.clone(), op);
+            let model_node = &mut model.nodes[id];
+            model_node.onnx_name = node.proto.name.clone();
+            model_node.domain = node.proto.domain.clone();
+            model_node.inputs = node.proto.input.clone();
+            model_node.outputs = node.proto.output.clone();
+            model_node.attributes = node_attributes(&node.proto);
+            for output in node.proto.output.iter() {
+                produce(output, id)?;
+            }
+        }
+
+        //Edges are added in input order once every producer is known, so nodes may be added
+        //in any order
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let mut provider_ids = vec![];
+            for input in node.proto.input.iter().filter(|i| !i.is_empty()) {
+                let id = *producers
+                    .get(input)
+                    .ok_or_else(|| BuilderError::UnknownTensor {
+                        node: node.display_name(),
+                        tensor: input.clone(),
+                    })?;
+                if !provider_ids.contains(&id) {
+                    provider_ids.push(id);
+                }
+            }
+            for id in provider_ids {
+                model.add_edge(id, graph_offset + idx);
+            }
+        }
+
+        for name in self.outputs {
+            let producer = *producers
+                .get(&name)
+                .ok_or_else(|| BuilderError::UnknownOutput(name.clone()))?;
+            let id = model.add_node(name.clone(), Box::new(ops::data::Unimplemented) as BoxOp);
+            model.nodes[id].inputs = vec![name];
+            model.outputs.push(id);
+            model.add_edge(producer, id);
+        }
+        Ok(model)
+    }
+}
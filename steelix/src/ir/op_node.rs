@@ -3,8 +3,13 @@ use crate::ir::{Op, PVec, RealizedOp};
 pub struct OpNode<O: Op> {
     pub id: usize,
     pub name: String,
+    pub onnx_name: String, //name of the node in the ONNX graph, may be empty
+    pub domain: String,    //operator set domain, empty for the default domain
     pub providers: Vec<usize>,
     pub consumers: Vec<usize>,
+    pub inputs: Vec<String>,  //names of the tensors consumed by this node
+    pub outputs: Vec<String>, //names of the tensors produced by this node
+    pub attributes: Vec<(String, String)>, //names and printed values, see `format_attribute`
     pub op: O,
 }
 
@@ -23,11 +23,20 @@ impl Op for Softmax {
     //   2*n          -- compute softmax from exp of shifted logits
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
         validate_providers(&providers, 1, 1, &self.name())?;
-        let output_shape = if self.axis == -1 {
-            providers[0].shape[providers[0].shape.len() - 1]
+        let rank = providers[0].rank() as i64;
+        let axis = if self.axis < 0 {
+            self.axis + rank
         } else {
-            providers[0].shape[self.axis as usize]
+            self.axis
         };
+        if !(0..rank).contains(&axis) {
+            anyhow::bail!(
+                "Softmax axis {} is out of range for rank {}",
+                self.axis,
+                rank
+            );
+        }
+        let output_shape = providers[0].shape[axis as usize];
         let out = Tensor::new(providers[0].dt, shape![output_shape]);
         Ok(RealizedOp {
             cost: OpCost {
@@ -1,6 +1,9 @@
 use std::borrow::Cow;
 
-use crate::{ir::ops::shape::multi_broadcast, prelude::*};
+use crate::{
+    ir::ops::shape::{broadcast_fact, multi_broadcast},
+    prelude::*,
+};
 use steelix_onnx::onnx_pb;
 
 #[derive(Debug, Clone)]
@@ -23,9 +26,10 @@ impl Op for Add {
                 .map(|p| p.shape.clone())
                 .collect::<Vec<_>>(),
         )
-        .expect("Failed to broadcast shapes in Add");
+        .ok_or_else(|| anyhow::anyhow!("Failed to broadcast shapes in Add"))?;
 
-        let res = Tensor::new(providers[0].dt, broadcasted_shape);
+        let res =
+            Tensor::new(providers[0].dt, broadcasted_shape).with_fact(broadcast_fact(&providers));
         Ok(RealizedOp {
             cost: OpCost {
                 flops: providers[0].numel(),
@@ -34,6 +38,10 @@ impl Op for Add {
             outputs: pvec![res.into_arc_tensor()],
         })
     }
+
+    fn propagates_facts(&self) -> bool {
+        true
+    }
 }
 
 pub fn build_add(_proto: &onnx_pb::NodeProto) -> Result<BoxOp, anyhow::Error> {
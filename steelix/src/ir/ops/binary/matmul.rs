@@ -2,7 +2,7 @@ use std::borrow::Cow;
 
 use crate::ir::ops::shape::multi_broadcast;
 use crate::prelude::*;
-use anyhow::format_err;
+use anyhow::{bail, format_err};
 use steelix_onnx::onnx_pb;
 
 #[derive(Debug, Clone)]
@@ -24,19 +24,22 @@ impl Op for Matmul {
     //𝑛𝑚(2𝑝−1)
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
         validate_providers(&providers, 2, 2, &self.name())?;
+        if providers.iter().any(|p| p.rank() == 0) {
+            bail!("MatMul is not defined for scalars");
+        }
         let a_shape = &providers[0].shape;
         let b_shape = &providers[1].shape;
         let c_shape = compute_shapes(a_shape.clone(), b_shape.clone())?;
 
-        let m = a_shape[0];
-        let n = b_shape[1];
-        let p = a_shape[1];
+        //Each element of the output is a dot product of length p
+        let p = a_shape.last().copied().unwrap_or(1);
+        let flops = c_shape.iter().product::<usize>() * (2 * p).saturating_sub(1);
 
         let res = Tensor::new(providers[0].dt, c_shape);
 
         Ok(RealizedOp {
             cost: OpCost {
-                flops: m * n * (2 * p - 1),
+                flops,
                 parameters: 0,
             },
             outputs: pvec![res.into_arc_tensor()],
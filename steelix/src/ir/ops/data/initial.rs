@@ -1,7 +1,4 @@
-use crate::{
-    ir::{DType, ValueInfo},
-    prelude::*,
-};
+use crate::{ir::ValueInfo, prelude::*};
 use std::{borrow::Cow, sync::Arc};
 
 #[derive(Debug, Clone)]
@@ -22,8 +19,15 @@ impl Op for Initial {
 }
 
 pub fn build_initial(value_info: ValueInfo) -> Result<BoxOp, anyhow::Error> {
-    //TODO: this needs to be dynamic on DTYPE
+    let fact = value_info.fact();
     Ok(Box::new(Initial(
-        Tensor::new(DType::F32, value_info.dimensions).into_arc_tensor(),
+        Tensor::new(value_info.dt, value_info.dimensions)
+            .with_fact(fact)
+            .into_arc_tensor(),
     )) as BoxOp)
 }
+
+///An input whose type could not be parsed
+pub fn build_unknown_initial(dt: DType) -> Result<BoxOp, anyhow::Error> {
+    Ok(Box::new(Initial(Tensor::unknown(dt).into_arc_tensor())) as BoxOp)
+}
@@ -0,0 +1,30 @@
+use crate::{ir::LazyTensor, prelude::*};
+use std::borrow::Cow;
+
+///A Constant whose tensor is only decoded when the graph is run
+#[derive(Debug, Clone)]
+pub struct LazyConstant(pub LazyTensor);
+
+impl Op for LazyConstant {
+    fn name(&self) -> Cow<str> {
+        "Constant".into()
+    }
+
+    fn op_group(&self) -> OpGroup {
+        OpGroup::Constant
+    }
+
+    fn realize(&self, _: PVec) -> anyhow::Result<RealizedOp> {
+        Ok(RealizedOp {
+            cost: OpCost {
+                parameters: self.0.numel(),
+                ..OpCost::default()
+            },
+            outputs: pvec![self.0.materialize()?],
+        })
+    }
+}
+
+pub fn build_lazy_constant(t: LazyTensor) -> Result<BoxOp, anyhow::Error> {
+    Ok(Box::new(LazyConstant(t)) as BoxOp)
+}
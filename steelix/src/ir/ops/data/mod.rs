@@ -1,7 +1,9 @@
 mod constant;
 mod initial;
+mod lazy_constant;
 mod unimplemented;
 
 pub use constant::*;
 pub use initial::*;
+pub use lazy_constant::*;
 pub use unimplemented::*;
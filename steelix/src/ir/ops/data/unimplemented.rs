@@ -19,10 +19,6 @@ impl Op for Unimplemented {
     }
 }
 
-pub fn build_unimplemented(proto: &onnx_pb::NodeProto) -> Result<BoxOp, anyhow::Error> {
-    println!(
-        "Unimplemented operation found: {:?}. Try plotting the model with `--disable-shapes`",
-        proto.op_type
-    );
+pub fn build_unimplemented(_proto: &onnx_pb::NodeProto) -> Result<BoxOp, anyhow::Error> {
     Ok(Box::new(Unimplemented) as BoxOp)
 }
@@ -25,8 +25,8 @@ impl Gemm {
         let m = a_shape[0];
         let n = a_shape[1];
         let p = b_shape[1];
-        let ab_flops = m * n * (2 * p - 1);
-        let ab_c_flops = ab_shape[0] * ab_shape[1] * (2 * c_shape[1] - 1);
+        let ab_flops = m * n * (2 * p).saturating_sub(1);
+        let ab_c_flops = ab_shape[0] * ab_shape[1] * (2 * c_shape[1]).saturating_sub(1);
         OpCost {
             flops: ab_flops + ab_c_flops,
             ..Default::default()
@@ -46,6 +46,8 @@ impl Op for Gemm {
     //TODO: support transpose
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
         validate_providers(&providers, 2, 3, &self.name())?;
+        validate_rank(&providers[0], 2, &self.name())?;
+        validate_rank(&providers[1], 2, &self.name())?;
 
         let a = &providers[0];
         let b = &providers[1];
@@ -85,7 +87,7 @@ impl Op for Gemm {
         };
 
         let c_shape = multi_broadcast(&[ab_shape.clone(), c.shape.clone()])
-            .expect("Could not broadcast C -> A*B in GEMM");
+            .ok_or_else(|| anyhow::anyhow!("Could not broadcast C -> A*B in GEMM"))?;
 
         let res = Tensor::new(providers[0].dt, ab_shape.clone());
 
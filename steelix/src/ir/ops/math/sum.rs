@@ -1,4 +1,4 @@
-use crate::ir::ops::shape::multi_broadcast;
+use crate::ir::ops::shape::{broadcast_fact, multi_broadcast};
 use crate::prelude::*;
 use std::borrow::Cow;
 
@@ -21,9 +21,10 @@ impl Op for Sum {
                 .map(|p| p.shape.clone())
                 .collect::<Vec<_>>(),
         )
-        .expect("Sum: broadcast failed");
+        .ok_or_else(|| anyhow::anyhow!("Sum: broadcast failed"))?;
 
-        let res = Tensor::new(providers[0].dt, broadcasted_shape);
+        let res =
+            Tensor::new(providers[0].dt, broadcasted_shape).with_fact(broadcast_fact(&providers));
 
         Ok(RealizedOp {
             cost: OpCost {
@@ -33,4 +34,8 @@ impl Op for Sum {
             outputs: pvec![res.into_arc_tensor()],
         })
     }
+
+    fn propagates_facts(&self) -> bool {
+        true
+    }
 }
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use anyhow::ensure;
 use std::borrow::Cow;
 use steelix_onnx::onnx_pb;
 
@@ -12,7 +13,18 @@ pub struct Conv {
 }
 
 impl Conv {
-    fn output_dims(&self, input_shape: &[i64]) -> (usize, usize) {
+    fn output_dims(&self, input_shape: &[i64]) -> anyhow::Result<(usize, usize)> {
+        ensure!(
+            self.kernel_shape.len() >= 2
+                && self.pads.len() >= 4
+                && self.strides.len() >= 2
+                && self.dilations.len() >= 2,
+            "Conv expects 2D kernel_shape, pads, strides and dilations"
+        );
+        ensure!(
+            self.strides.iter().all(|&s| s > 0),
+            "Conv strides must be positive"
+        );
         let kernel_shape = self.kernel_shape.clone();
         let out_height = ((((input_shape[2] + (2 * self.pads[2])
             - self.dilations[0] * (kernel_shape[0] - 1)
@@ -28,7 +40,11 @@ impl Conv {
             + 1) as f32)
             .floor();
 
-        (out_height as usize, out_width as usize)
+        ensure!(
+            out_height >= 1. && out_width >= 1.,
+            "Conv kernel is larger than its padded input"
+        );
+        Ok((out_height as usize, out_width as usize))
     }
 }
 
@@ -43,6 +59,9 @@ impl Op for Conv {
 
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
         validate_providers(&providers, 2, 3, &self.name())?;
+        validate_rank(&providers[0], 4, &self.name())?;
+        validate_rank(&providers[1], 4, &self.name())?;
+        ensure!(self.group > 0, "Conv group must be positive");
         let x = providers[0].clone();
         let (n, cin, _, _) = (x.shape[0], x.shape[1], x.shape[2], x.shape[3]);
 
@@ -55,7 +74,7 @@ impl Op for Conv {
                 .cloned()
                 .map(|x| x as i64)
                 .collect::<Vec<_>>(),
-        );
+        )?;
 
         let mac = (cin / self.group as usize) * kh * kw * h_out * w_out * f;
 
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 
 use crate::prelude::*;
+use anyhow::ensure;
 use steelix_onnx::onnx_pb;
 
 #[derive(Debug, Clone)]
@@ -13,7 +14,15 @@ pub struct AvgPool {
 }
 
 impl AvgPool {
-    fn output_dims(&self, m: i64, n: i64) -> (usize, usize) {
+    fn output_dims(&self, m: i64, n: i64) -> anyhow::Result<(usize, usize)> {
+        ensure!(
+            self.kernel_shape.len() >= 2 && self.pads.len() >= 4 && self.strides.len() >= 2,
+            "AveragePool expects 2D kernel_shape, pads and strides"
+        );
+        ensure!(
+            self.strides.iter().all(|&s| s > 0),
+            "AveragePool strides must be positive"
+        );
         let k0 = self.kernel_shape.clone()[0];
         let k1 = self.kernel_shape.clone()[1];
 
@@ -24,7 +33,7 @@ impl AvgPool {
         let p1 = self.pads.clone()[3];
         let h_out = ((((m + (2 * p0) - k0) as i64 / s0) + 1) as f32).floor() as usize;
         let w_out = ((((n + (2 * p1) - k1) as i64 / s1) + 1) as f32).floor() as usize;
-        (h_out, w_out)
+        Ok((h_out, w_out))
     }
 }
 
@@ -38,8 +47,10 @@ impl Op for AvgPool {
     }
 
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
+        validate_providers(&providers, 1, 1, &self.name())?;
+        validate_rank(&providers[0], 4, &self.name())?;
         let input_shape = &providers[0].shape;
-        let (h_out, w_out) = self.output_dims(input_shape[2] as i64, input_shape[3] as i64);
+        let (h_out, w_out) = self.output_dims(input_shape[2] as i64, input_shape[3] as i64)?;
         let out_shape = shape![input_shape[0], input_shape[1], h_out, w_out];
         let out = Tensor::new(providers[0].dt, out_shape);
         Ok(RealizedOp {
@@ -16,6 +16,8 @@ impl Op for GlobalAveragePool {
     }
 
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
+        validate_providers(&providers, 1, 1, &self.name())?;
+        validate_rank(&providers[0], 4, &self.name())?;
         let input_shape = &providers[0].shape;
         let out = Tensor::new(
             providers[0].dt,
@@ -1,4 +1,8 @@
-use crate::{ir::Shape, shape};
+use crate::{
+    ir::{DimFact, Shape, ShapeFact, Tensor},
+    shape,
+};
+use std::sync::Arc;
 
 pub fn multi_broadcast(shapes: &[Shape]) -> Option<Shape> {
     let len = shapes.iter().map(|shape| shape.as_ref().len()).max()?;
@@ -25,6 +29,37 @@ pub fn multi_broadcast(shapes: &[Shape]) -> Option<Shape> {
     Some(shape)
 }
 
+///Facts of the shape broadcast from the given tensors.
+///An output dimension is known if any input fixes it to something other than 1,
+///otherwise it is unknown or symbolic if any input is.
+pub fn broadcast_fact(tensors: &[Arc<Tensor>]) -> ShapeFact {
+    if tensors.iter().any(|t| t.fact.is_unknown_rank()) {
+        return ShapeFact::UnknownRank;
+    }
+    let len = tensors.iter().map(|t| t.rank()).max().unwrap_or(0);
+    let mut dims: Vec<DimFact> = (0..len)
+        .map(|i| {
+            let aligned = tensors
+                .iter()
+                .filter(|t| i < t.rank())
+                .map(|t| (t.shape[t.rank() - i - 1], t.fact.dim(t.rank() - i - 1)));
+            let mut fact = DimFact::Known;
+            for (dim, dim_fact) in aligned {
+                match dim_fact {
+                    DimFact::Known if dim != 1 => return DimFact::Known,
+                    DimFact::Known => {}
+                    DimFact::Unknown => fact = DimFact::Unknown,
+                    DimFact::Symbolic(_) if fact == DimFact::Unknown => {}
+                    symbolic => fact = symbolic,
+                }
+            }
+            fact
+        })
+        .collect();
+    dims.reverse();
+    ShapeFact::from_dims(dims)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -10,7 +10,24 @@ pub struct Concat {
 
 impl Concat {
     pub fn concat(&self, providers: &PVec) -> Result<Shape, OpError> {
-        Ok(Tensor::stack_tensors(self.axis as usize, providers)?.shape)
+        let rank = providers[0].rank() as i64;
+        let axis = if self.axis < 0 {
+            self.axis + rank
+        } else {
+            self.axis
+        };
+        if !(0..rank).contains(&axis) {
+            return Err(OpError::ValidationError(format!(
+                "Concat axis {} is out of range for rank {}",
+                self.axis, rank
+            )));
+        }
+        if providers.iter().any(|p| p.rank() as i64 != rank) {
+            return Err(OpError::ValidationError(
+                "Concat inputs must have the same rank".to_string(),
+            ));
+        }
+        Ok(Tensor::stack_tensors(axis as usize, providers)?.shape)
     }
 }
 
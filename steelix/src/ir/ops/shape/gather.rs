@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use anyhow::{anyhow, bail};
 use ndarray::{Axis, Dimension};
 use std::{borrow::Cow, sync::Arc};
 use steelix_onnx::onnx_pb;
@@ -9,18 +10,39 @@ pub struct Gather {
 }
 
 impl Gather {
+    ///The gathered axis of data of the rank, counting negative axes from the back
+    fn data_axis(&self, rank: usize) -> anyhow::Result<usize> {
+        let axis = if self.axis < 0 {
+            self.axis + rank as i64
+        } else {
+            self.axis
+        };
+        if !(0..rank as i64).contains(&axis) {
+            bail!(
+                "Gather axis {} is out of range for rank {}",
+                self.axis,
+                rank
+            );
+        }
+        Ok(axis as usize)
+    }
+
     pub fn compute_output_shape<D: num::NumCast + std::clone::Clone>(
         &self,
         input_shape: &[D],
         indices_shape: &[D],
     ) -> anyhow::Result<Shape> {
+        let axis = self.data_axis(input_shape.len())?;
+        let cast = |dim: &D| {
+            num::cast::<D, usize>(dim.clone()).ok_or_else(|| anyhow!("Invalid Gather dimension"))
+        };
         let mut output_shape = shape!();
         for (s_idx, dim) in input_shape.iter().enumerate() {
-            if s_idx as i64 != self.axis {
-                output_shape.push(num::cast((*dim).clone()).unwrap());
+            if s_idx != axis {
+                output_shape.push(cast(dim)?);
             } else {
                 for ind_idx in indices_shape {
-                    output_shape.push(num::cast((*ind_idx).clone()).unwrap());
+                    output_shape.push(cast(ind_idx)?);
                 }
             }
         }
@@ -32,14 +54,24 @@ impl Gather {
         data: Arc<Tensor>,
         indices: &Arc<Tensor>,
     ) -> anyhow::Result<Arc<Tensor>> {
+        let axis = self.data_axis(data.rank())?;
         let data_view = data.to_array_view_unchecked::<T>();
-        if indices.shape.is_empty() {
-            let mut index = *indices.to_scalar::<i64>()?;
-            if index < 0 {
-                index += data_view.shape()[0] as i64;
+        let axis_len = data_view.shape()[axis] as i64;
+        let checked_index = |index: i64| {
+            let index = if index < 0 { index + axis_len } else { index };
+            if !(0..axis_len).contains(&index) {
+                bail!(
+                    "Gather index {} is out of range for {} entries",
+                    index,
+                    axis_len
+                );
             }
+            Ok(index as usize)
+        };
+        if indices.shape.is_empty() {
+            let index = checked_index(*indices.to_scalar::<i64>()?)?;
             return Ok(data_view
-                .index_axis(Axis(self.axis as usize), index as usize)
+                .index_axis(Axis(axis), index)
                 .to_owned()
                 .into_arc_tensor());
         }
@@ -49,16 +81,12 @@ impl Gather {
 
         let mut view = output.to_array_view_mut_unchecked::<T>();
         for (indices_coords, indices_value) in indices.to_array_view::<i64>()?.indexed_iter() {
-            let mut to_update = view.index_axis_mut(Axis(self.axis as usize), indices_coords[0]);
+            let mut to_update = view.index_axis_mut(Axis(axis), indices_coords[0]);
             for idx in 1..indices_coords.ndim() {
                 to_update = to_update.index_axis_move(Axis(0), indices_coords[idx]);
             }
-            let index_value = if *indices_value >= 0 {
-                *indices_value
-            } else {
-                indices_value + data_view.shape()[self.axis as usize] as i64
-            } as usize;
-            to_update.assign(&data_view.index_axis(Axis(self.axis as usize), index_value));
+            let index_value = checked_index(*indices_value)?;
+            to_update.assign(&data_view.index_axis(Axis(axis), index_value));
         }
         Ok(output.into_arc_tensor())
     }
@@ -74,6 +102,13 @@ impl Op for Gather {
     }
 
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
+        validate_providers(&providers, 2, 2, &self.name())?;
+        if !providers[0].has_data() {
+            //Weights skipped when loading, e.g. an embedding table
+            let shape = self.compute_output_shape(&providers[0].shape, &providers[1].shape)?;
+            let result = Tensor::shape_only(providers[0].dt, shape);
+            return Ok(RealizedOp::zero_cost(pvec![result.into_arc_tensor()]));
+        }
         unsafe {
             let result = as_std!(Self::eval(providers[0].dt)(
                 self,
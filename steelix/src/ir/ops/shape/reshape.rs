@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use anyhow::{anyhow, bail};
 use std::borrow::Cow;
 use steelix_onnx::onnx_pb;
 
@@ -10,36 +11,70 @@ pub struct Reshape {
 //Reshape is a problematic operation, as it depends on the data contents of the tensor
 impl Reshape {
     pub fn reshape<D: DataType + ndarray::LinalgScalar + num::NumCast>(
-        original_shape: Shape,
+        &self,
+        input: &Tensor,
         shape_tensor: &Tensor,
-    ) -> Shape {
-        let mut shape_data: Vec<D> = shape_tensor.as_slice().unwrap().into();
+    ) -> anyhow::Result<Tensor> {
+        let original_shape = &input.shape;
+        let shape_data: Vec<i64> = shape_tensor
+            .as_slice::<D>()?
+            .iter()
+            .map(|&dim| num::cast(dim).ok_or_else(|| anyhow!("Failed to cast: {:?}", dim)))
+            .collect::<anyhow::Result<_>>()?;
 
-        let mut product = D::one();
+        //Placeholder dimensions cancel out when copied by a zero, any others leave the
+        //inferred dimension unknown
+        let copied = |axis: usize| shape_data.get(axis) == Some(&0) && self.allow_zero == 0;
+        let inferable = !input.fact.is_unknown_rank()
+            && (0..input.rank()).all(|axis| copied(axis) || input.fact.dim(axis) == DimFact::Known);
+
+        let mut new_shape = shape!();
+        let mut facts = vec![];
         let mut unknown_dim = None;
-        for (i, dim) in shape_data.iter().enumerate() {
-            if *dim == D::from(-1).unwrap() {
-                if unknown_dim.is_some() {
-                    panic!("Reshape: only one unknown dimension is allowed");
+        for (i, &dim) in shape_data.iter().enumerate() {
+            match dim {
+                -1 if unknown_dim.is_some() => {
+                    bail!("Reshape: only one unknown dimension is allowed")
+                }
+                -1 => {
+                    unknown_dim = Some(i);
+                    new_shape.push(1);
                 }
-                unknown_dim = Some(i);
-            } else if *dim != D::zero() {
-                product = product * *dim;
+                0 if self.allow_zero == 0 && input.fact.is_unknown_dim(i) => new_shape.push(1),
+                //Zero copies the input dimension, unless zero sized dimensions are allowed
+                0 if self.allow_zero == 0 => new_shape.push(
+                    *original_shape
+                        .get(i)
+                        .ok_or_else(|| anyhow!("Reshape: no input dimension {} to copy", i))?,
+                ),
+                dim if dim < 0 => bail!("Reshape: invalid dimension {}", dim),
+                dim => new_shape.push(dim as usize),
             }
+            facts.push(match dim {
+                -1 if !inferable => DimFact::Unknown,
+                0 if self.allow_zero == 0 => input.fact.dim(i),
+                _ => DimFact::Known,
+            });
         }
 
-        if let Some(unknown_dim) = unknown_dim {
-            shape_data[unknown_dim] =
-                D::from(original_shape.iter().product::<usize>() / product.to_usize().unwrap())
-                    .expect("Failed to compute unknown dimension in Reshape");
-        }
-
-        let mut new_shape = shape!();
-        for elem in shape_data {
-            new_shape.push(num::cast(elem).unwrap_or_else(|| panic!("Failed to cast: {:?}", elem)));
+        if let Some(unknown_dim) = unknown_dim.filter(|_| inferable) {
+            let product = new_shape.iter().product::<usize>();
+            let numel = original_shape.iter().product::<usize>();
+            if product == 0 || numel % product != 0 {
+                bail!(
+                    "Reshape: cannot infer a dimension of {} from {}",
+                    original_shape.to_string().trim(),
+                    shape_data
+                        .iter()
+                        .map(i64::to_string)
+                        .collect::<Vec<_>>()
+                        .join("x")
+                );
+            }
+            new_shape[unknown_dim] = numel / product;
         }
 
-        new_shape
+        Ok(Tensor::new(input.dt, new_shape).with_fact(ShapeFact::from_dims(facts)))
     }
 }
 
@@ -54,14 +89,26 @@ impl Op for Reshape {
 
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
         validate_providers(&providers, 2, 2, &self.name())?;
-        let new_shape = as_std!(Reshape::reshape(providers[1].dt)(
-            providers[0].shape.clone(),
-            &providers[1]
-        ));
+        let (input, shape_tensor) = (&providers[0], &providers[1]);
+        if !shape_tensor.has_data() || !shape_tensor.is_known() {
+            //Without the values of the shape only its length, the output rank, may be known
+            let reshaped = match shape_tensor.rank() {
+                1 if shape_tensor.is_known() => {
+                    Tensor::shape_only(input.dt, shape![1; shape_tensor.shape[0]])
+                        .with_unknown_dims()
+                }
+                _ => Tensor::unknown(input.dt),
+            };
+            return Ok(RealizedOp::zero_cost(pvec![reshaped.into_arc_tensor()]));
+        }
 
-        let reshaped = Tensor::new(providers[0].dt, new_shape).into_arc_tensor();
+        let reshaped = as_std!(Self::reshape(shape_tensor.dt)(self, input, shape_tensor))?;
+
+        Ok(RealizedOp::zero_cost(pvec![reshaped.into_arc_tensor()]))
+    }
 
-        Ok(RealizedOp::zero_cost(pvec![reshaped]))
+    fn propagates_facts(&self) -> bool {
+        true
     }
 }
 
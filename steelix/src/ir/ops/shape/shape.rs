@@ -20,13 +20,26 @@ impl Op for Shape {
 
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
         validate_providers(&providers, 1, 1, &self.name())?;
-        let input_shape = &providers[0].shape;
+        let input = &providers[0];
+        if input.fact.is_unknown_rank() {
+            //A vector of unknown length
+            let out = Tensor::shape_only(DType::I64, shape![1]).with_unknown_dims();
+            return Ok(RealizedOp::zero_cost(pvec![out.into_arc_tensor()]));
+        }
+
         let end = if self.end == -1 {
-            input_shape.len() as i64
+            input.rank() as i64
         } else {
             self.end
         };
-        let new_shape = providers[0].shape[self.start as usize..end as usize]
+        let axes = self.start as usize..end as usize;
+        if axes.clone().any(|axis| input.fact.is_unknown_dim(axis)) {
+            //The length is known, the values are not
+            let out = Tensor::shape_only(DType::I64, shape![axes.len()]);
+            return Ok(RealizedOp::zero_cost(pvec![out.into_arc_tensor()]));
+        }
+
+        let new_shape = input.shape[axes]
             .iter()
             .cloned()
             .map(|i| i as i64)
@@ -35,6 +48,10 @@ impl Op for Shape {
         let out = Tensor::from_vec(shape![new_shape.len()], new_shape);
         Ok(RealizedOp::zero_cost(pvec![out.into_arc_tensor()]))
     }
+
+    fn propagates_facts(&self) -> bool {
+        true
+    }
 }
 
 pub fn build_shape(proto: &onnx_pb::NodeProto) -> Result<BoxOp, anyhow::Error> {
@@ -10,20 +10,24 @@ pub struct Squeeze {
 }
 
 impl Squeeze {
-    pub fn squeeze(&self, to_squeeze: &Tensor) -> Shape {
-        let shape_iter = to_squeeze.shape.iter();
-        let new_shape: Vec<usize> = if self.axes.is_some() {
-            let all_axes = self.axes.as_ref().unwrap();
-            shape_iter
-                .enumerate()
-                .filter(|(idx, _)| !all_axes.contains(idx))
-                .map(|tup| to_squeeze.shape[tup.0])
+    pub fn squeeze(&self, to_squeeze: &Tensor) -> Tensor {
+        //Without axes the squeezed dimensions depend on their sizes, which placeholders hide
+        if to_squeeze.fact.is_unknown_rank() || (self.axes.is_none() && !to_squeeze.is_known()) {
+            return Tensor::unknown(to_squeeze.dt);
+        }
+        let kept: Vec<usize> = if let Some(all_axes) = &self.axes {
+            (0..to_squeeze.rank())
+                .filter(|idx| !all_axes.contains(idx))
                 .collect()
         } else {
-            shape_iter.filter(|ax| **ax != 1).copied().collect()
+            (0..to_squeeze.rank())
+                .filter(|&idx| to_squeeze.shape[idx] != 1)
+                .collect()
         };
 
-        Shape(new_shape.into())
+        let new_shape = kept.iter().map(|&idx| to_squeeze.shape[idx]).collect();
+        Tensor::new(to_squeeze.dt, Shape(new_shape))
+            .with_fact(to_squeeze.fact.select(kept.iter().copied()))
     }
 }
 
@@ -37,13 +41,17 @@ impl Op for Squeeze {
     }
 
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
-        let new_shape = self.squeeze(&providers[0]);
-        let output = Tensor::new(providers[0].dt, new_shape);
+        validate_providers(&providers, 1, 2, &self.name())?;
+        let output = self.squeeze(&providers[0]);
         Ok(RealizedOp {
             cost: OpCost::zero_cost(),
             outputs: pvec![output.into_arc_tensor()],
         })
     }
+
+    fn propagates_facts(&self) -> bool {
+        true
+    }
 }
 
 pub fn build_squeeze(proto: &onnx_pb::NodeProto) -> Result<BoxOp, anyhow::Error> {
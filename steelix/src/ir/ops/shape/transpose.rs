@@ -2,6 +2,7 @@ use std::borrow::Cow;
 use steelix_onnx::onnx_pb;
 
 use crate::prelude::*;
+use anyhow::ensure;
 
 #[derive(Debug, Clone)]
 pub struct Transpose {
@@ -9,22 +10,22 @@ pub struct Transpose {
 }
 
 impl Transpose {
-    fn transpose(&self, input: &Tensor, axes: &[usize]) -> Vec<usize> {
-        let mut usage_counts = [0, 0, 0, 0];
-        for axis in axes {
-            usage_counts[*axis] += 1;
+    fn transpose(&self, input: &Tensor, axes: &[usize]) -> anyhow::Result<Vec<usize>> {
+        let mut usage_counts = vec![0; input.rank()];
+        for &axis in axes {
+            ensure!(
+                axis < input.rank(),
+                "Transpose axis {} is out of range for rank {}",
+                axis,
+                input.rank()
+            );
+            usage_counts[axis] += 1;
         }
-        for count in usage_counts {
-            assert_eq!(count, 1, "each axis must be listed exactly once");
-        }
-        let mut new_dim = usage_counts;
-        {
-            let dim = &input.shape;
-            for (new_axis, &axis) in axes.iter().enumerate() {
-                new_dim[new_axis] = dim[axis];
-            }
-        }
-        new_dim.into()
+        ensure!(
+            usage_counts.iter().all(|&count| count == 1),
+            "each axis must be listed exactly once"
+        );
+        Ok(axes.iter().map(|&axis| input.shape[axis]).collect())
     }
 }
 
@@ -39,12 +40,24 @@ impl Op for Transpose {
 
     fn realize(&self, providers: PVec) -> anyhow::Result<RealizedOp> {
         validate_providers(&providers, 1, 1, &self.name())?;
+        let input = &providers[0];
+        if input.fact.is_unknown_rank() {
+            return Ok(RealizedOp::zero_cost(pvec!(
+                Tensor::unknown(input.dt).into_arc_tensor()
+            )));
+        }
 
-        let transposed_shape = self.transpose(&providers[0], &self.perm).into();
-        let result = Tensor::new(providers[0].dt, Shape(transposed_shape)).into_arc_tensor();
+        let transposed_shape = self.transpose(input, &self.perm)?.into();
+        let result = Tensor::new(input.dt, Shape(transposed_shape))
+            .with_fact(input.fact.select(self.perm.iter().copied()))
+            .into_arc_tensor();
 
         Ok(RealizedOp::zero_cost(pvec!(result)))
     }
+
+    fn propagates_facts(&self) -> bool {
+        true
+    }
 }
 
 pub fn build_transpose(proto: &onnx_pb::NodeProto) -> Result<BoxOp, anyhow::Error> {
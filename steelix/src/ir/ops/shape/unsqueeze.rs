@@ -1,6 +1,5 @@
-use crate::ir::{DType, DataType};
 use crate::prelude::*;
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 use std::borrow::Cow;
 use steelix_onnx::onnx_pb;
 
@@ -10,19 +9,32 @@ pub struct Unsqueeze {
 }
 
 impl Unsqueeze {
-    pub fn unsqueeze<D: DataType + ndarray::LinalgScalar + std::cmp::PartialOrd + num::NumCast>(
-        &self,
-        input: &Tensor,
-        mut axes: Vec<i64>,
-    ) -> anyhow::Result<Tensor> {
-        axes.sort_by(|a, b| a.partial_cmp(b).expect("Failed to sort."));
+    pub fn unsqueeze(&self, input: &Tensor, axes: Vec<i64>) -> anyhow::Result<Tensor> {
+        //Axes index the output, negative axes count from its back
+        let rank = (input.rank() + axes.len()) as i64;
+        let mut axes = axes
+            .iter()
+            .map(|&axis| match if axis < 0 { axis + rank } else { axis } {
+                axis if (0..rank).contains(&axis) => Ok(axis as usize),
+                _ => Err(anyhow!(
+                    "Unsqueeze axis {} is out of range for rank {}",
+                    axis,
+                    rank
+                )),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        axes.sort_unstable();
         let mut new_shape = input.shape.clone();
+        let mut facts = (0..input.rank())
+            .map(|axis| input.fact.dim(axis))
+            .collect::<Vec<_>>();
 
-        axes.iter().for_each(|new_axis| {
-            new_shape.insert(num::cast(*new_axis).unwrap(), 1);
-        });
+        for new_axis in axes {
+            new_shape.insert(new_axis, 1);
+            facts.insert(new_axis, DimFact::Known);
+        }
 
-        Ok(Tensor::new(input.dt, new_shape))
+        Ok(Tensor::new(input.dt, new_shape).with_fact(ShapeFact::from_dims(facts)))
     }
 }
 
@@ -40,6 +52,10 @@ impl Op for Unsqueeze {
         if providers.len() == 1 && self.axes.is_none() {
             bail!("Invalid parameters for Unsqueeze.")
         }
+        if providers[0].fact.is_unknown_rank() {
+            let output = Tensor::unknown(providers[0].dt);
+            return Ok(RealizedOp::zero_cost(pvec![output.into_arc_tensor()]));
+        }
 
         let axes = if let Some(ax) = &self.axes {
             ax.clone()
@@ -47,14 +63,14 @@ impl Op for Unsqueeze {
             providers[1].as_slice()?.to_vec()
         };
 
-        let new_tensor = as_std!(Unsqueeze::unsqueeze(providers[0].dt)(
-            self,
-            &providers[0],
-            axes
-        ))?;
+        let new_tensor = self.unsqueeze(&providers[0], axes)?;
 
         Ok(RealizedOp::zero_cost(pvec![new_tensor.into_arc_tensor()]))
     }
+
+    fn propagates_facts(&self) -> bool {
+        true
+    }
 }
 
 pub fn build_unsqueeze(proto: &onnx_pb::NodeProto) -> Result<BoxOp, anyhow::Error> {
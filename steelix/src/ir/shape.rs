@@ -1,6 +1,77 @@
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Shape(pub SmallVec<[usize; 4]>);
 
+///What is known about a single dimension of a tensor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DimFact {
+    Known,
+    ///A named dimension such as a batch, computed as 1
+    Symbolic(String),
+    ///A placeholder of 1 is held in the `Shape`
+    Unknown,
+}
+
+///What is known about a tensor's shape during propagation.
+///Unknown dimensions hold a placeholder of 1 in the `Shape`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ShapeFact {
+    #[default]
+    Known,
+    ///The rank is known, but not every dimension is
+    Dims(SmallVec<[DimFact; 4]>),
+    ///Nothing is known about the shape
+    UnknownRank,
+}
+
+impl ShapeFact {
+    ///Collects per dimension facts, a fully known shape is `ShapeFact::Known`
+    pub fn from_dims(dims: impl IntoIterator<Item = DimFact>) -> Self {
+        let dims: SmallVec<[DimFact; 4]> = dims.into_iter().collect();
+        if dims.iter().all(|dim| *dim == DimFact::Known) {
+            ShapeFact::Known
+        } else {
+            ShapeFact::Dims(dims)
+        }
+    }
+
+    pub fn unknown_dims(rank: usize) -> Self {
+        Self::from_dims(std::iter::repeat(DimFact::Unknown).take(rank))
+    }
+
+    ///Whether the shape can be computed with, symbolic dimensions are taken to be 1
+    pub fn is_known(&self) -> bool {
+        match self {
+            ShapeFact::Known => true,
+            ShapeFact::Dims(dims) => !dims.contains(&DimFact::Unknown),
+            ShapeFact::UnknownRank => false,
+        }
+    }
+
+    pub fn is_unknown_rank(&self) -> bool {
+        *self == ShapeFact::UnknownRank
+    }
+
+    pub fn dim(&self, axis: usize) -> DimFact {
+        match self {
+            ShapeFact::Known => DimFact::Known,
+            ShapeFact::Dims(dims) => dims.get(axis).cloned().unwrap_or(DimFact::Known),
+            ShapeFact::UnknownRank => DimFact::Unknown,
+        }
+    }
+
+    pub fn is_unknown_dim(&self, axis: usize) -> bool {
+        self.dim(axis) == DimFact::Unknown
+    }
+
+    ///Facts of the given axes, in order
+    pub fn select(&self, axes: impl IntoIterator<Item = usize>) -> Self {
+        match self {
+            ShapeFact::UnknownRank => ShapeFact::UnknownRank,
+            _ => Self::from_dims(axes.into_iter().map(|axis| self.dim(axis))),
+        }
+    }
+}
+
 use std::{
     fmt::Display,
     ops::{Deref, DerefMut},
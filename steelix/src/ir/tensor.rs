@@ -1,15 +1,25 @@
-use half::f16;
+use half::{bf16, f16};
+use num::complex::{Complex32, Complex64};
 use std::{fmt, mem::size_of, sync::Arc};
 
-use crate::ir::OpError;
+use crate::ir::{OpError, ShapeFact, F8E4M3FN, F8E4M3FNUZ, F8E5M2, F8E5M2FNUZ};
 use crate::prelude::*;
+use anyhow::{anyhow, bail, ensure};
 use bytes::BytesMut;
 use ndarray::{Array, ArrayViewD, ArrayViewMutD};
 use steelix_onnx::onnx_pb::{self, tensor_proto::DataType as ProtoDType};
 
+///Dispatches over the dtypes we can do arithmetic on.
+///Other dtypes return an error from the calling function.
 #[macro_export]
 macro_rules! as_std {
-    ($($path:ident)::* ($dt:expr) ($($args:expr),*)) => { {
+    ($($path:ident)::* ($dt:expr) ($($args:expr),*)) => {
+        $crate::as_std!(@dispatch $($path)::*($dt)($($args),*) else anyhow::bail!(
+            "Called numeric op with non-numeric dtype: {:?}",
+            $dt
+        ))
+    };
+    (@dispatch $($path:ident)::* ($dt:expr) ($($args:expr),*) else $fallback:expr) => { {
         match $dt {
           DType::U8   => $($path)::*::<u8>($($args),*),
           DType::U16  => $($path)::*::<u16>($($args),*),
@@ -19,9 +29,29 @@ macro_rules! as_std {
           DType::I16  => $($path)::*::<i16>($($args),*),
           DType::I32  => $($path)::*::<i32>($($args),*),
           DType::I64  => $($path)::*::<i64>($($args),*),
-          DType::F16  => $($path)::*::<i16>($($args),*),
+          DType::F16  => $($path)::*::<half::f16>($($args),*),
+          DType::BF16 => $($path)::*::<half::bf16>($($args),*),
           DType::F32  => $($path)::*::<f32>($($args),*),
           DType::F64  => $($path)::*::<f64>($($args),*),
+          _ => $fallback,
+        }
+    } }
+}
+
+///Dispatches over every fixed width dtype, including those we cannot do arithmetic on.
+#[macro_export]
+macro_rules! as_datum {
+    ($($path:ident)::* ($dt:expr) ($($args:expr),*)) => { {
+        match $dt {
+          DType::Bool       => $($path)::*::<bool>($($args),*),
+          DType::C64        => $($path)::*::<num::complex::Complex32>($($args),*),
+          DType::C128       => $($path)::*::<num::complex::Complex64>($($args),*),
+          DType::F8E4M3FN   => $($path)::*::<$crate::F8E4M3FN>($($args),*),
+          DType::F8E4M3FNUZ => $($path)::*::<$crate::F8E4M3FNUZ>($($args),*),
+          DType::F8E5M2     => $($path)::*::<$crate::F8E5M2>($($args),*),
+          DType::F8E5M2FNUZ => $($path)::*::<$crate::F8E5M2FNUZ>($($args),*),
+          DType::String     => panic!("String tensors have no fixed width representation"),
+          _ => $crate::as_std!(@dispatch $($path)::*($dt)($($args),*) else unreachable!()),
         }
     } }
 }
@@ -42,15 +72,18 @@ pub struct Tensor {
     pub shape: Shape,
     pub len: usize, //actual entry count
     pub data: BytesMut,
+    pub string_data: Vec<String>, //only populated for DType::String
+    pub fact: ShapeFact,
 }
 
 impl std::fmt::Debug for Tensor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Tensor {{\n dt: {:?}, \n shape: {}, \n len: {:?}, \n data: {} \n}}",
+            "Tensor {{\n dt: {:?}, \n shape: {}, \n fact: {:?}, \n len: {:?}, \n data: {} \n}}",
             self.dt,
             self.shape,
+            self.fact,
             self.len,
             self.stringify_data()
         )
@@ -63,24 +96,99 @@ impl PartialEq for Tensor {
             cur.as_slice_unchecked::<D>() == other.as_slice_unchecked::<D>()
         }
 
-        self.dt == other.dt
-            && self.shape == other.shape
-            && self.len == other.len
-            && unsafe { as_std!(eq_t(self.dt)(self, other)) }
+        if self.dt != other.dt
+            || self.shape != other.shape
+            || self.len != other.len
+            || self.fact != other.fact
+        {
+            return false;
+        }
+        match self.dt {
+            DType::String => self.string_data == other.string_data,
+            _ => unsafe { as_datum!(eq_t(self.dt)(self, other)) },
+        }
     }
 }
 
 impl Tensor {
     pub fn new(dt: DType, shape: Shape) -> Self {
         let len = shape.iter().product::<usize>();
+        let string_data = if dt == DType::String {
+            vec![String::new(); len]
+        } else {
+            vec![]
+        };
         Self {
             dt,
             shape,
             len,
             data: BytesMut::zeroed(len * dt.size_of()),
+            string_data,
+            ..Default::default()
         }
     }
 
+    ///A placeholder for a tensor we know nothing about, other than its type
+    pub fn unknown(dt: DType) -> Self {
+        Self {
+            dt,
+            fact: ShapeFact::UnknownRank,
+            ..Default::default()
+        }
+    }
+
+    ///A tensor of known type and shape with no buffer behind it, for values that are never read
+    pub fn shape_only(dt: DType, shape: Shape) -> Self {
+        Self {
+            dt,
+            shape,
+            ..Default::default()
+        }
+    }
+
+    ///Whether the values of the tensor are held, rather than just its shape
+    #[inline]
+    pub fn has_data(&self) -> bool {
+        self.len == self.numel()
+    }
+
+    ///Marks every dimension of the tensor as a placeholder, keeping the rank
+    pub fn with_unknown_dims(mut self) -> Self {
+        if self.fact.is_known() {
+            self.fact = ShapeFact::unknown_dims(self.rank());
+        }
+        self
+    }
+
+    pub fn with_fact(mut self, fact: ShapeFact) -> Self {
+        self.fact = fact;
+        self
+    }
+
+    #[inline]
+    pub fn is_known(&self) -> bool {
+        self.fact.is_known()
+    }
+
+    pub fn from_strings(shape: Shape, strings: Vec<String>) -> anyhow::Result<Self> {
+        let len = shape.iter().product::<usize>();
+        ensure!(
+            len == strings.len(),
+            "Shape {} holds {} strings, got {}.",
+            shape.to_string().trim(),
+            len,
+            strings.len()
+        );
+        Ok(Self {
+            dt: DType::String,
+            shape,
+            len,
+            data: BytesMut::new(),
+            string_data: strings,
+            ..Default::default()
+        })
+    }
+
     pub fn zeros<T: DataType>(shape: Shape) -> Self {
         let len = shape.iter().product::<usize>();
         let byte_count = len * T::to_internal().size_of();
@@ -89,6 +197,7 @@ impl Tensor {
             shape,
             len,
             data: BytesMut::zeroed(byte_count),
+            ..Default::default()
         }
     }
 
@@ -107,6 +216,7 @@ impl Tensor {
             shape,
             len,
             data: bytes,
+            ..Default::default()
         }
     }
 
@@ -119,9 +229,21 @@ impl Tensor {
     }
 
     pub fn numel(&self) -> usize {
+        if self.fact == ShapeFact::UnknownRank {
+            return 0;
+        }
         self.shape.iter().product::<usize>()
     }
 
+    ///Number of bytes occupied by the elements of the tensor.
+    ///Strings are variable width, so we count their UTF-8 bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        match self.dt {
+            DType::String => self.string_data.iter().map(|s| s.len()).sum(),
+            _ => self.numel() * self.dt.size_of(),
+        }
+    }
+
     pub fn update_shape(&mut self, new_shape: Shape) {
         //todo: err check
         self.shape = new_shape;
@@ -140,6 +262,11 @@ impl Tensor {
     }
 
     pub fn as_slice<D: DataType>(&self) -> Result<&[D], OpError> {
+        if !self.has_data() {
+            return Err(OpError::ValidationError(
+                "Values of the tensor were not loaded".to_string(),
+            ));
+        }
         unsafe { Ok(self.as_slice_unchecked()) }
     }
 
@@ -151,6 +278,7 @@ impl Tensor {
     /// Transform the data as a `ndarray::Array`.
     pub fn to_array_view<A: DataType>(&self) -> anyhow::Result<ArrayViewD<A>> {
         //TODO: error checking
+        ensure!(self.has_data(), "Values of the tensor were not loaded");
         unsafe { Ok(self.to_array_view_unchecked()) }
     }
 
@@ -238,12 +366,19 @@ impl Tensor {
                 .expect("Failed to convert to array view")
                 .to_string()
         }
-        unsafe { as_std!(pretty_print(self.dt)(self)) }
+        if self.fact == ShapeFact::UnknownRank || !self.has_data() {
+            return "?".to_string();
+        }
+        match self.dt {
+            DType::String => format!("{:?}", self.string_data),
+            _ => unsafe { as_datum!(pretty_print(self.dt)(self)) },
+        }
     }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Default)]
 pub enum DType {
+    Bool,
     U8,
     U16,
     U32,
@@ -252,16 +387,29 @@ pub enum DType {
     I16,
     I32,
     I64,
+    F8E4M3FN,
+    F8E4M3FNUZ,
+    F8E5M2,
+    F8E5M2FNUZ,
     F16,
+    BF16,
     #[default]
     F32,
     F64,
+    C64,
+    C128,
+    String,
 }
 
 impl DType {
+    ///Size of a single element in bytes.
+    ///Strings have no fixed width and report 0, see `Tensor::size_in_bytes`.
     #[inline]
     pub fn size_of(&self) -> usize {
-        as_std!(std::mem::size_of(self)())
+        match self {
+            DType::String => 0,
+            _ => as_datum!(std::mem::size_of(self)()),
+        }
     }
 }
 
@@ -280,6 +428,7 @@ macro_rules! map_type {
     };
 }
 
+map_type!(bool, Bool);
 map_type!(u8, U8);
 map_type!(u16, U16);
 map_type!(u32, U32);
@@ -288,73 +437,182 @@ map_type!(i8, I8);
 map_type!(i16, I16);
 map_type!(i32, I32);
 map_type!(i64, I64);
+map_type!(F8E4M3FN, F8E4M3FN);
+map_type!(F8E4M3FNUZ, F8E4M3FNUZ);
+map_type!(F8E5M2, F8E5M2);
+map_type!(F8E5M2FNUZ, F8E5M2FNUZ);
 map_type!(f16, F16); //half crate
+map_type!(bf16, BF16); //half crate
 map_type!(f32, F32);
 map_type!(f64, F64);
+map_type!(Complex32, C64);
+map_type!(Complex64, C128);
 
 impl TryFrom<ProtoDType> for DType {
     type Error = anyhow::Error;
 
     fn try_from(proto_dt: ProtoDType) -> Result<Self, Self::Error> {
         match proto_dt {
-            ProtoDType::Int8 => Ok(DType::I8),
-            ProtoDType::Undefined => todo!(),
+            ProtoDType::Undefined => bail!("Tensor data type is undefined."),
             ProtoDType::Float => Ok(DType::F32),
             ProtoDType::Uint8 => Ok(DType::U8),
+            ProtoDType::Int8 => Ok(DType::I8),
             ProtoDType::Uint16 => Ok(DType::U16),
             ProtoDType::Int16 => Ok(DType::I16),
             ProtoDType::Int32 => Ok(DType::I32),
             ProtoDType::Int64 => Ok(DType::I64),
-            ProtoDType::String => todo!(),
-            ProtoDType::Bool => todo!(),
+            ProtoDType::String => Ok(DType::String),
+            ProtoDType::Bool => Ok(DType::Bool),
             ProtoDType::Float16 => Ok(DType::F16),
-            ProtoDType::Double => Ok(DType::F32),
+            ProtoDType::Double => Ok(DType::F64),
             ProtoDType::Uint32 => Ok(DType::U32),
             ProtoDType::Uint64 => Ok(DType::U64),
-            ProtoDType::Complex64 => todo!(),
-            ProtoDType::Complex128 => todo!(),
-            ProtoDType::Bfloat16 => todo!(),
+            ProtoDType::Complex64 => Ok(DType::C64),
+            ProtoDType::Complex128 => Ok(DType::C128),
+            ProtoDType::Bfloat16 => Ok(DType::BF16),
+            ProtoDType::Float8e4m3fn => Ok(DType::F8E4M3FN),
+            ProtoDType::Float8e4m3fnuz => Ok(DType::F8E4M3FNUZ),
+            ProtoDType::Float8e5m2 => Ok(DType::F8E5M2),
+            ProtoDType::Float8e5m2fnuz => Ok(DType::F8E5M2FNUZ),
+        }
+    }
+}
+
+impl From<DType> for ProtoDType {
+    fn from(dt: DType) -> Self {
+        match dt {
+            DType::Bool => ProtoDType::Bool,
+            DType::U8 => ProtoDType::Uint8,
+            DType::U16 => ProtoDType::Uint16,
+            DType::U32 => ProtoDType::Uint32,
+            DType::U64 => ProtoDType::Uint64,
+            DType::I8 => ProtoDType::Int8,
+            DType::I16 => ProtoDType::Int16,
+            DType::I32 => ProtoDType::Int32,
+            DType::I64 => ProtoDType::Int64,
+            DType::F8E4M3FN => ProtoDType::Float8e4m3fn,
+            DType::F8E4M3FNUZ => ProtoDType::Float8e4m3fnuz,
+            DType::F8E5M2 => ProtoDType::Float8e5m2,
+            DType::F8E5M2FNUZ => ProtoDType::Float8e5m2fnuz,
+            DType::F16 => ProtoDType::Float16,
+            DType::BF16 => ProtoDType::Bfloat16,
+            DType::F32 => ProtoDType::Float,
+            DType::F64 => ProtoDType::Double,
+            DType::C64 => ProtoDType::Complex64,
+            DType::C128 => ProtoDType::Complex128,
+            DType::String => ProtoDType::String,
         }
     }
 }
 
+//16 and 8 bit types are stored in the low bits of int32_data
+//see: https://github.com/onnx/onnx/blob/main/onnx/onnx.proto#L574
+fn unpack_int32<T: DataType>(shape: Shape, packed: &[i32], f: impl Fn(i32) -> T) -> Tensor {
+    Tensor::from_vec(shape, packed.iter().map(|&x| f(x)).collect())
+}
+
 impl TryFrom<onnx_pb::TensorProto> for Tensor {
     type Error = anyhow::Error;
 
     fn try_from(tproto: onnx_pb::TensorProto) -> Result<Self, Self::Error> {
-        let dt = ProtoDType::from_i32(tproto.data_type).unwrap().try_into()?;
+        let dt: DType = ProtoDType::from_i32(tproto.data_type)
+            .ok_or_else(|| anyhow!("Unknown tensor data type: {}", tproto.data_type))?
+            .try_into()?;
         let shape: Shape = Shape(tproto.dims.iter().map(|&i| i as usize).collect());
 
         let tensor = if !tproto.raw_data.is_empty() {
             let len = shape.iter().cloned().product::<usize>();
+            ensure!(
+                dt != DType::String,
+                "String tensor {} cannot be stored in raw_data.",
+                tproto.name
+            );
+            ensure!(
+                tproto.raw_data.len() == len * dt.size_of(),
+                "Tensor {} has {} bytes of raw_data, expected {} for {} elements of {:?}.",
+                tproto.name,
+                tproto.raw_data.len(),
+                len * dt.size_of(),
+                len,
+                dt
+            );
             let data: BytesMut = (*tproto.raw_data).into();
             Tensor {
                 dt,
                 shape,
                 len,
                 data,
+                ..Default::default()
             }
         } else {
+            let ints = &tproto.int32_data;
+            //Complex values are stored as interleaved real and imaginary parts
+            let (field, stored, per_element) = match dt {
+                DType::U32 | DType::U64 => ("uint64_data", tproto.uint64_data.len(), 1),
+                DType::I64 => ("int64_data", tproto.int64_data.len(), 1),
+                DType::F32 => ("float_data", tproto.float_data.len(), 1),
+                DType::C64 => ("float_data", tproto.float_data.len(), 2),
+                DType::F64 => ("double_data", tproto.double_data.len(), 1),
+                DType::C128 => ("double_data", tproto.double_data.len(), 2),
+                DType::String => ("string_data", tproto.string_data.len(), 1),
+                _ => ("int32_data", ints.len(), 1),
+            };
+            let len = shape.iter().product::<usize>();
+            ensure!(
+                stored == len * per_element,
+                "Tensor {} has {} values in {}, expected {} for {} elements of {:?}.",
+                tproto.name,
+                stored,
+                field,
+                len * per_element,
+                len,
+                dt
+            );
             match dt {
-                DType::U8 => {
-                    Tensor::from_vec(shape, tproto.int32_data.iter().map(|&x| x as u8).collect())
-                }
-                DType::U16 => {
-                    Tensor::from_vec(shape, tproto.int32_data.iter().map(|&x| x as u16).collect())
-                }
-                DType::U32 => Tensor::from_vec(shape, tproto.int32_data.to_vec()),
-                DType::U64 => Tensor::from_vec(shape, tproto.int64_data.to_vec()),
-                DType::I8 => {
-                    Tensor::from_vec(shape, tproto.int32_data.iter().map(|&x| x as i8).collect())
-                }
-                DType::I16 => {
-                    Tensor::from_vec(shape, tproto.int32_data.iter().map(|&x| x as i16).collect())
-                }
-                DType::I32 => Tensor::from_vec(shape, tproto.int32_data.to_vec()),
+                DType::Bool => unpack_int32(shape, ints, |x| x != 0),
+                DType::U8 => unpack_int32(shape, ints, |x| x as u8),
+                DType::U16 => unpack_int32(shape, ints, |x| x as u16),
+                DType::I8 => unpack_int32(shape, ints, |x| x as i8),
+                DType::I16 => unpack_int32(shape, ints, |x| x as i16),
+                DType::I32 => Tensor::from_vec(shape, ints.to_vec()),
+                DType::F8E4M3FN => unpack_int32(shape, ints, |x| F8E4M3FN(x as u8)),
+                DType::F8E4M3FNUZ => unpack_int32(shape, ints, |x| F8E4M3FNUZ(x as u8)),
+                DType::F8E5M2 => unpack_int32(shape, ints, |x| F8E5M2(x as u8)),
+                DType::F8E5M2FNUZ => unpack_int32(shape, ints, |x| F8E5M2FNUZ(x as u8)),
+                DType::F16 => unpack_int32(shape, ints, |x| f16::from_bits(x as u16)),
+                DType::BF16 => unpack_int32(shape, ints, |x| bf16::from_bits(x as u16)),
+                DType::U32 => Tensor::from_vec(
+                    shape,
+                    tproto.uint64_data.iter().map(|&x| x as u32).collect(),
+                ),
+                DType::U64 => Tensor::from_vec(shape, tproto.uint64_data.to_vec()),
                 DType::I64 => Tensor::from_vec(shape, tproto.int64_data.to_vec()),
-                DType::F16 => Tensor::from_vec(shape, tproto.float_data.to_vec()),
                 DType::F32 => Tensor::from_vec(shape, tproto.float_data.to_vec()),
                 DType::F64 => Tensor::from_vec(shape, tproto.double_data.to_vec()),
+                DType::C64 => Tensor::from_vec(
+                    shape,
+                    tproto
+                        .float_data
+                        .chunks_exact(2)
+                        .map(|c| Complex32::new(c[0], c[1]))
+                        .collect(),
+                ),
+                DType::C128 => Tensor::from_vec(
+                    shape,
+                    tproto
+                        .double_data
+                        .chunks_exact(2)
+                        .map(|c| Complex64::new(c[0], c[1]))
+                        .collect(),
+                ),
+                DType::String => Tensor::from_strings(
+                    shape,
+                    tproto
+                        .string_data
+                        .iter()
+                        .map(|s| String::from_utf8_lossy(s).into_owned())
+                        .collect(),
+                )?,
             }
         };
 
@@ -420,6 +678,7 @@ impl<A: DataType, D: ::ndarray::Dimension> From<Array<A, D>> for Tensor {
             shape: Shape(shape.into()),
             len,
             data: data.into(),
+            ..Default::default()
         }
     }
 }
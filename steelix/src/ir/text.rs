@@ -0,0 +1,217 @@
+//Values in the ONNX textual syntax, shared by the text parser and writer and by the printed
+//attributes of nodes. See: https://onnx.ai/onnx/repo-docs/Syntax.html
+use half::{bf16, f16};
+use steelix_onnx::onnx_pb::{
+    self,
+    attribute_proto::AttributeType,
+    tensor_proto::{DataLocation, DataType as ProtoDType},
+};
+
+///Element types as written in the ONNX textual syntax
+pub(crate) const ELEM_TYPES: &[(&str, ProtoDType)] = &[
+    ("float", ProtoDType::Float),
+    ("uint8", ProtoDType::Uint8),
+    ("int8", ProtoDType::Int8),
+    ("uint16", ProtoDType::Uint16),
+    ("int16", ProtoDType::Int16),
+    ("int32", ProtoDType::Int32),
+    ("int64", ProtoDType::Int64),
+    ("string", ProtoDType::String),
+    ("bool", ProtoDType::Bool),
+    ("float16", ProtoDType::Float16),
+    ("double", ProtoDType::Double),
+    ("uint32", ProtoDType::Uint32),
+    ("uint64", ProtoDType::Uint64),
+    ("bfloat16", ProtoDType::Bfloat16),
+    ("float8e4m3fn", ProtoDType::Float8e4m3fn),
+    ("float8e4m3fnuz", ProtoDType::Float8e4m3fnuz),
+    ("float8e5m2", ProtoDType::Float8e5m2),
+    ("float8e5m2fnuz", ProtoDType::Float8e5m2fnuz),
+];
+
+///Initializers with more elements are printed without their values unless asked otherwise
+pub const MAX_PRINTED_ELEMENTS: usize = 16;
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        && !matches!(name, "inf" | "nan")
+}
+
+pub(crate) fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+///Names which are not valid identifiers in the syntax are quoted
+pub(crate) fn name(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+pub(crate) fn elem_type_name(elem_type: i32) -> String {
+    ELEM_TYPES
+        .iter()
+        .find(|(_, dt)| *dt as i32 == elem_type)
+        .map_or_else(
+            || format!("elem_type_{}", elem_type),
+            |(n, _)| n.to_string(),
+        )
+}
+
+pub(crate) fn float(x: f64) -> String {
+    if x.is_nan() {
+        "nan".to_string()
+    } else if x.is_infinite() {
+        (if x > 0. { "inf" } else { "-inf" }).to_string()
+    } else {
+        format!("{:?}", x) //always has a decimal point or exponent
+    }
+}
+
+pub(crate) fn float32(x: f32) -> String {
+    //Printed at single precision, so 0.1 is not written as 0.10000000149011612
+    if x.is_finite() {
+        format!("{:?}", x)
+    } else {
+        float(x as f64)
+    }
+}
+
+///Values of a tensor, or None if they are stored outside the model
+pub(crate) fn tensor_values(tensor: &onnx_pb::TensorProto) -> Option<Vec<String>> {
+    if tensor.data_location == DataLocation::External as i32 {
+        return None;
+    }
+    let dt = ProtoDType::from_i32(tensor.data_type)?;
+    let raw = &tensor.raw_data;
+    macro_rules! from_raw {
+        ($t:ty, $fmt:expr) => {
+            raw.chunks_exact(std::mem::size_of::<$t>())
+                .map(|b| $fmt(<$t>::from_le_bytes(b.try_into().unwrap())))
+                .collect()
+        };
+    }
+    let values: Vec<String> = if raw.is_empty() {
+        match dt {
+            ProtoDType::Float => tensor.float_data.iter().map(|&x| float32(x)).collect(),
+            ProtoDType::Double => tensor.double_data.iter().map(|&x| float(x)).collect(),
+            ProtoDType::Int64 => tensor.int64_data.iter().map(i64::to_string).collect(),
+            ProtoDType::Uint32 | ProtoDType::Uint64 => {
+                tensor.uint64_data.iter().map(u64::to_string).collect()
+            }
+            ProtoDType::Float16 => tensor
+                .int32_data
+                .iter()
+                .map(|&b| float32(f16::from_bits(b as u16).to_f32()))
+                .collect(),
+            ProtoDType::Bfloat16 => tensor
+                .int32_data
+                .iter()
+                .map(|&b| float32(bf16::from_bits(b as u16).to_f32()))
+                .collect(),
+            ProtoDType::String => tensor
+                .string_data
+                .iter()
+                .map(|s| quote(&String::from_utf8_lossy(s)))
+                .collect(),
+            _ => tensor.int32_data.iter().map(i32::to_string).collect(),
+        }
+    } else {
+        match dt {
+            ProtoDType::Float => from_raw!(f32, float32),
+            ProtoDType::Double => from_raw!(f64, float),
+            ProtoDType::Int64 => from_raw!(i64, |x: i64| x.to_string()),
+            ProtoDType::Int32 => from_raw!(i32, |x: i32| x.to_string()),
+            ProtoDType::Int16 => from_raw!(i16, |x: i16| x.to_string()),
+            ProtoDType::Int8 => from_raw!(i8, |x: i8| x.to_string()),
+            ProtoDType::Uint64 => from_raw!(u64, |x: u64| x.to_string()),
+            ProtoDType::Uint32 => from_raw!(u32, |x: u32| x.to_string()),
+            ProtoDType::Uint16 => from_raw!(u16, |x: u16| x.to_string()),
+            ProtoDType::Uint8
+            | ProtoDType::Bool
+            | ProtoDType::Float8e4m3fn
+            | ProtoDType::Float8e4m3fnuz
+            | ProtoDType::Float8e5m2
+            | ProtoDType::Float8e5m2fnuz => from_raw!(u8, |x: u8| x.to_string()),
+            ProtoDType::Float16 => from_raw!(u16, |b| float32(f16::from_bits(b).to_f32())),
+            ProtoDType::Bfloat16 => from_raw!(u16, |b| float32(bf16::from_bits(b).to_f32())),
+            _ => return None,
+        }
+    };
+    Some(values)
+}
+
+pub(crate) fn tensor_type(tensor: &onnx_pb::TensorProto) -> String {
+    let dims: Vec<String> = tensor.dims.iter().map(i64::to_string).collect();
+    format!("{}[{}]", elem_type_name(tensor.data_type), dims.join(", "))
+}
+
+///Value of an attribute holding numbers or strings, None for tensors, graphs and types the
+///syntax can't express
+pub(crate) fn plain_attribute_value(attr: &onnx_pb::AttributeProto) -> Option<String> {
+    let strings = |values: &[Vec<u8>]| -> Vec<String> {
+        values
+            .iter()
+            .map(|s| quote(&String::from_utf8_lossy(s)))
+            .collect()
+    };
+    let value = match AttributeType::from_i32(attr.r#type) {
+        Some(AttributeType::Int) => attr.i.to_string(),
+        Some(AttributeType::Float) => float32(attr.f),
+        Some(AttributeType::String) => quote(&String::from_utf8_lossy(&attr.s)),
+        Some(AttributeType::Ints) => {
+            let ints: Vec<String> = attr.ints.iter().map(i64::to_string).collect();
+            format!("[{}]", ints.join(", "))
+        }
+        Some(AttributeType::Floats) => {
+            let floats: Vec<String> = attr.floats.iter().map(|&x| float32(x)).collect();
+            format!("[{}]", floats.join(", "))
+        }
+        Some(AttributeType::Strings) => format!("[{}]", strings(&attr.strings).join(", ")),
+        _ => return None,
+    };
+    Some(value)
+}
+
+///Value of an attribute for display, in the textual syntax. Subgraphs and large tensors are
+///summarized rather than printed.
+pub fn format_attribute(attr: &onnx_pb::AttributeProto) -> Option<String> {
+    match AttributeType::from_i32(attr.r#type) {
+        Some(AttributeType::Graph) => {
+            let g = attr.g.as_ref()?;
+            Some(format!("graph {} ({} nodes)", name(&g.name), g.node.len()))
+        }
+        Some(AttributeType::Tensor) => {
+            let t = attr.t.as_ref()?;
+            match tensor_values(t).filter(|v| v.len() <= MAX_PRINTED_ELEMENTS) {
+                Some(values) => Some(format!("{} {{{}}}", tensor_type(t), values.join(", "))),
+                None => Some(tensor_type(t)),
+            }
+        }
+        _ => plain_attribute_value(attr),
+    }
+}
+
+///Printed attributes of a node, leaving out those without a textual form
+pub(crate) fn node_attributes(node: &onnx_pb::NodeProto) -> Vec<(String, String)> {
+    node.attribute
+        .iter()
+        .filter_map(|attr| Some((attr.name.clone(), format_attribute(attr)?)))
+        .collect()
+}
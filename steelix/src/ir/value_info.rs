@@ -1,14 +1,71 @@
 use crate::{
-    ir::{ModelError, Shape},
+    ir::{DType, DimFact, ModelError, Shape, ShapeFact, Tensor},
     shape,
 };
 
-use steelix_onnx::onnx_pb;
+use steelix_onnx::onnx_pb::{self, tensor_proto::DataType as ProtoDType};
 
 #[derive(Debug, Clone)]
 pub struct ValueInfo {
     pub name: String,
+    pub dt: DType,
     pub dimensions: Shape,
+    pub dim_params: Vec<Option<String>>, //symbolic name of each dimension, if any
+}
+
+impl ValueInfo {
+    ///An annotation with every dimension known
+    pub fn new(name: &str, dt: DType, dimensions: Shape) -> Self {
+        Self {
+            name: name.to_string(),
+            dt,
+            dim_params: vec![None; dimensions.len()],
+            dimensions,
+        }
+    }
+
+    pub fn from_tensor(name: &str, tensor: &Tensor) -> Self {
+        Self {
+            name: name.to_string(),
+            dt: tensor.dt,
+            dimensions: tensor.shape.clone(),
+            dim_params: (0..tensor.rank())
+                .map(|axis| match tensor.fact.dim(axis) {
+                    DimFact::Known => None,
+                    DimFact::Symbolic(name) => Some(name),
+                    DimFact::Unknown => Some("?".to_string()),
+                })
+                .collect(),
+        }
+    }
+
+    ///Facts of the annotated shape, unnamed dimensions are symbolic too
+    pub fn fact(&self) -> ShapeFact {
+        ShapeFact::from_dims(self.dim_params.iter().map(|param| match param {
+            Some(name) => DimFact::Symbolic(name.clone()),
+            None => DimFact::Known,
+        }))
+    }
+
+    ///A shape only tensor of the annotated type
+    pub fn to_tensor(&self) -> Tensor {
+        Tensor::shape_only(self.dt, self.dimensions.clone()).with_fact(self.fact())
+    }
+
+    ///Bytes occupied by the tensor, unknown dimensions count as 1
+    pub fn size_in_bytes(&self) -> usize {
+        self.dimensions.iter().product::<usize>() * self.dt.size_of()
+    }
+
+    ///Checks a shape against the annotation, symbolic dimensions match anything
+    pub fn matches(&self, shape: &Shape) -> bool {
+        shape.len() == self.dimensions.len()
+            && shape
+                .iter()
+                .zip(self.dimensions.iter())
+                .zip(self.dim_params.iter())
+                .all(|((actual, annotated), param)| param.is_some() || actual == annotated)
+    }
 }
 
 impl TryFrom<onnx_pb::ValueInfoProto> for ValueInfo {
@@ -20,22 +77,42 @@ impl TryFrom<onnx_pb::ValueInfoProto> for ValueInfo {
             if let Some(v) = &value_type.value {
                 match v {
                     onnx_pb::type_proto::Value::TensorType(t) => {
-                        let pb_dims = t.shape.clone().unwrap().dim;
+                        let dt = ProtoDType::from_i32(t.elem_type)
+                            .ok_or_else(|| {
+                                ModelError::UnsupportedType(format!("elem_type {}", t.elem_type))
+                            })?
+                            .try_into()?;
+                        let pb_dims = t
+                            .shape
+                            .clone()
+                            .ok_or_else(|| {
+                                ModelError::ValidationError(format!("{} has no shape.", name))
+                            })?
+                            .dim;
 
                         let mut dimensions = shape!();
-                        pb_dims
-                            .into_iter()
-                            .for_each(|dim| match dim.value.unwrap() {
-                                onnx_pb::tensor_shape_proto::dimension::Value::DimValue(v) => {
-                                    dimensions.push(v as usize);
-                                }
-                                onnx_pb::tensor_shape_proto::dimension::Value::DimParam(_) => {
-                                    println!("Parameterized dimension found, using 1");
-                                    dimensions.push(1); //Pushing 1 for a N batch
-                                }
-                            });
-
-                        return Ok(Self { name, dimensions });
+                        let mut dim_params = vec![];
+                        pb_dims.into_iter().for_each(|dim| match dim.value {
+                            Some(onnx_pb::tensor_shape_proto::dimension::Value::DimValue(v)) => {
+                                dimensions.push(v as usize);
+                                dim_params.push(None);
+                            }
+                            Some(onnx_pb::tensor_shape_proto::dimension::Value::DimParam(p)) => {
+                                dimensions.push(1); //Pushing 1 for a N batch
+                                dim_params.push(Some(p));
+                            }
+                            None => {
+                                dimensions.push(1); //Unknown dimension
+                                dim_params.push(Some("?".to_string()));
+                            }
+                        });
+
+                        return Ok(Self {
+                            name,
+                            dt,
+                            dimensions,
+                            dim_params,
+                        });
                     }
                     onnx_pb::type_proto::Value::SequenceType(_) => {
                         return Err(ModelError::UnsupportedType("SequenceType".to_string()));
@@ -58,3 +135,37 @@ impl TryFrom<onnx_pb::ValueInfoProto> for ValueInfo {
         )))
     }
 }
+
+impl From<&ValueInfo> for onnx_pb::ValueInfoProto {
+    fn from(vi: &ValueInfo) -> Self {
+        use onnx_pb::tensor_shape_proto::{dimension::Value as DimValue, Dimension};
+
+        let dim = vi
+            .dimensions
+            .iter()
+            .zip(vi.dim_params.iter())
+            .map(|(&d, param)| Dimension {
+                value: match param {
+                    Some(p) if p == "?" => None, //unknown dimension
+                    Some(p) => Some(DimValue::DimParam(p.clone())),
+                    None => Some(DimValue::DimValue(d as i64)),
+                },
+                ..Default::default()
+            })
+            .collect();
+
+        onnx_pb::ValueInfoProto {
+            name: vi.name.clone(),
+            r#type: Some(onnx_pb::TypeProto {
+                value: Some(onnx_pb::type_proto::Value::TensorType(
+                    onnx_pb::type_proto::Tensor {
+                        elem_type: ProtoDType::from(vi.dt) as i32,
+                        shape: Some(onnx_pb::TensorShapeProto { dim }),
+                    },
+                )),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+}
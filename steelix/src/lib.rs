@@ -1,18 +1,22 @@
 #![feature(vec_into_raw_parts)]
+mod analysis;
 mod build_cli;
 mod display;
 mod ir;
 mod parser;
+mod writer;
 
+pub use analysis::*;
 pub use build_cli::*;
 pub use display::*;
 pub use ir::*;
 pub use parser::*;
+pub use writer::*;
 
 pub mod prelude {
     pub use crate::ir::{
-        validate_providers, BoxOp, DType, DataType, IntoArcTensor, Op, OpCost, OpGroup, PVec,
-        RealizedOp, Shape, Tensor,
+        validate_providers, validate_rank, BoxOp, DType, DataType, DimFact, IntoArcTensor, Op,
+        OpCost, OpGroup, PVec, RealizedOp, Shape, ShapeFact, Tensor,
     };
-    pub use crate::{as_std, pvec, shape};
+    pub use crate::{as_datum, as_std, pvec, shape};
 }
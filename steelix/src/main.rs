@@ -1,54 +1,255 @@
 use clap::ArgMatches;
+use prost::Message;
 use std::process::Command as ProcessCommand;
 use steelix::{
-    build_cli, hardware_table, metrics_table, opcount_table, parse_model, render_to,
-    RenderableGraph,
+    build_cli, collectives_table, critical_path, critical_path_table, cuts_table, decode_model,
+    diagnostics_table, extract_subgraph, fingerprint, fingerprint_table, graph_stats,
+    hardware_table, is_external, load_devices, metrics_table, model_from_proto, opcount_table,
+    parallelism_table, parse_dims, parse_model_with, partition_pipeline, pipeline_table,
+    rename_node, rename_tensor, render_html, render_svg, render_to, repeated_subgraphs,
+    repeats_table, residuals_table, select_region, set_batch_size, set_input_shape,
+    shape_mismatch_table, shard_tensor_parallel, sharded_nodes_table, sharding_table, stats_table,
+    structures_table, to_dot, to_gexf, to_graphml, to_mermaid, to_text, write_edits,
+    write_inferred_shapes, BalanceBy, ConstantDisplay, CostMetric, Device, Diagnostic, Model,
+    ModelSummary, PathWeight, PlotFormat, Region, RenderableGraph, Renderer, SummaryFormat,
+    SummaryReport, Theme, WeightLoading,
 };
+use steelix_onnx::onnx_pb;
 use tabled::{object::Rows, Alignment, Disable, Modify, Panel, Style, Table, Tabled};
 use tempfile::NamedTempFile;
 
-fn main() {
+fn main() -> anyhow::Result<()> {
     let matches = build_cli().get_matches();
     match matches.subcommand().unwrap() {
-        ("plot", matches) => run_plot_command(matches).unwrap(),
-        ("summary", matches) => run_summary_command(matches).unwrap(),
+        ("plot", matches) => run_plot_command(matches),
+        ("summary", matches) => run_summary_command(matches),
+        ("infer-shapes", matches) => run_infer_shapes_command(matches),
+        ("edit", matches) => run_edit_command(matches),
+        ("pipeline", matches) => run_pipeline_command(matches),
+        ("shard", matches) => run_shard_command(matches),
+        ("critical-path", matches) => run_critical_path_command(matches),
+        ("stats", matches) => run_stats_command(matches),
+        ("fingerprint", matches) => run_fingerprint_command(matches),
+        ("print", matches) => run_print_command(matches),
         _ => unreachable!("Invalid command provided."),
     }
 }
 
+///Parses and runs the model, keeping it for analyses which need the node graph
+fn analyze_model(
+    model_path: &std::path::PathBuf,
+    matches: &ArgMatches,
+) -> anyhow::Result<(Model, ModelSummary)> {
+    let mut model = parse_model_with(model_path, weight_loading(matches)?)?.build_traversal_order();
+    let summary = if matches.is_present("KEEP_GOING") {
+        model.run_keep_going()?
+    } else {
+        model.run()?
+    };
+    Ok((model, summary))
+}
+
+///Prints every diagnostic, failing in strict mode if there were errors
+fn report_diagnostics(diagnostics: &[Diagnostic], matches: &ArgMatches) -> anyhow::Result<()> {
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+    check_strict(diagnostics, matches)
+}
+
+///Warnings, such as those for unimplemented operators, never fail strict mode
+fn check_strict(diagnostics: &[Diagnostic], matches: &ArgMatches) -> anyhow::Result<()> {
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    if matches.is_present("STRICT") && errors > 0 {
+        anyhow::bail!("{} errors reported in strict mode", errors);
+    }
+    Ok(())
+}
+
+fn weight_loading(matches: &ArgMatches) -> anyhow::Result<WeightLoading> {
+    matches
+        .get_one::<String>("WEIGHTS")
+        .expect("Weight loading mode has a default.")
+        .parse()
+}
+
+fn find_device(name: &str) -> anyhow::Result<Device> {
+    load_devices()?
+        .into_iter()
+        .find(|d| d.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| anyhow::anyhow!("Unknown device: {}", name))
+}
+
+fn cost_metric(matches: &ArgMatches) -> anyhow::Result<Option<CostMetric>> {
+    let metric = match matches
+        .get_one::<String>("COLOR_BY")
+        .expect("Color scale has a default.")
+        .as_str()
+    {
+        "group" => None,
+        "flops" => Some(CostMetric::Flops),
+        "params" => Some(CostMetric::Params),
+        "bytes" => Some(CostMetric::Bytes),
+        "latency" => {
+            let name = matches
+                .get_one::<String>("DEVICE")
+                .ok_or_else(|| anyhow::anyhow!("Coloring by latency needs a --device."))?;
+            Some(CostMetric::Latency(find_device(name)?))
+        }
+        other => anyhow::bail!("Unknown color scale: {}", other),
+    };
+    Ok(metric)
+}
+
 fn run_plot_command(matches: &ArgMatches) -> anyhow::Result<()> {
-    let model_path = &matches
+    let model_path: &std::path::PathBuf = &matches
         .get_one::<String>("MODEL_PATH")
         .expect("Failed to find model at path.")
         .into();
+    let format: PlotFormat = matches
+        .get_one::<String>("FORMAT")
+        .expect("Format has a default.")
+        .parse()?;
+    let renderer: Renderer = matches
+        .get_one::<String>("RENDERER")
+        .expect("Renderer has a default.")
+        .parse()?;
     let output_path = matches
         .get_one::<String>("OUTPUT_PATH")
-        .expect("Invalid output path provided.");
+        .cloned()
+        .unwrap_or_else(|| format!("model.{}", format.extension()));
     let disable_shapes = matches.is_present("DISABLE_SHAPES");
     let open = matches.is_present("OPEN_IN_BROWSER");
+    let loading = weight_loading(matches)?;
+
+    let highlight = matches.is_present("CRITICAL_PATH");
+    let metric = cost_metric(matches)?;
+    let scale_edges = matches.is_present("SCALE_EDGES");
+    let theme = Theme::load(
+        matches
+            .get_one::<String>("THEME")
+            .expect("Theme has a default."),
+    )?;
+    let constants: ConstantDisplay = matches
+        .get_one::<String>("CONSTANTS")
+        .expect("Constants has a default.")
+        .parse()?;
+    let collapse_repeats = matches.is_present("COLLAPSE_REPEATS");
+    let collapse_depth = matches.get_one::<usize>("COLLAPSE_DEPTH").copied();
+    if collapse_depth == Some(0) {
+        anyhow::bail!("Collapse depth must be at least 1.");
+    }
 
-    let model = parse_model(model_path)?;
-    let mut model_summary = None;
-    if !disable_shapes {
-        model_summary = Some(parse_model(model_path)?.build_traversal_order().run()?);
+    //The HTML viewer shows shapes and costs in its panels, even when edges are unlabelled
+    let needs_summary = !disable_shapes
+        || highlight
+        || collapse_repeats
+        || metric.is_some()
+        || format == PlotFormat::Html;
+    let (model, model_summary) = if needs_summary {
+        let (model, summary) = analyze_model(model_path, matches)?;
+        (model, Some(summary))
+    } else {
+        (parse_model_with(model_path, loading)?, None)
+    };
+    let diagnostics = model_summary
+        .as_ref()
+        .map_or_else(|| model.diagnostics.clone(), |s| s.diagnostics.clone());
+    let path = match &model_summary {
+        Some(summary) if highlight => critical_path(&model, summary, &PathWeight::Flops)?.path,
+        _ => vec![],
+    };
+    let region = plot_region(matches);
+    let edge_summary = model_summary.as_ref().filter(|_| !disable_shapes);
+    let mut plottable = RenderableGraph::build_graph_with(&model, edge_summary, &theme);
+    if let Some(region) = &region {
+        plottable.restrict(&select_region(&model, region)?);
+    }
+    plottable.show_constants(&model, edge_summary, constants);
+    if matches.is_present("ATTRIBUTES") {
+        plottable.label_attributes(&model);
+    }
+    plottable.clustered = matches.is_present("CLUSTER");
+    plottable.highlight_path(&path);
+    if let Some(depth) = collapse_depth {
+        plottable.collapse_scopes(depth);
+    }
+    if let (true, Some(summary)) = (collapse_repeats, &model_summary) {
+        plottable.collapse_repeats(&repeated_subgraphs(&model, summary)?);
+    }
+    if let (Some(metric), Some(summary)) = (&metric, &model_summary) {
+        plottable.color_by_cost(&model, summary, metric)?;
+    }
+    if scale_edges {
+        plottable.scale_edges();
     }
-    let plottable: RenderableGraph = RenderableGraph::build_graph(model, model_summary);
 
+    let svg = || match renderer {
+        Renderer::Builtin => Ok(render_svg(&plottable)),
+        Renderer::Dot => dot_svg(&plottable),
+    };
+    let output = match format {
+        PlotFormat::Svg => svg()?,
+        PlotFormat::Html => {
+            let title = model_path.file_stem().unwrap().to_str().unwrap();
+            render_html(&svg()?, title, &plottable, &model, model_summary.as_ref())
+        }
+        PlotFormat::Mermaid => to_mermaid(&plottable),
+        PlotFormat::GraphMl => to_graphml(&plottable),
+        PlotFormat::Gexf => to_gexf(&plottable),
+        PlotFormat::Dot => to_dot(&plottable),
+    };
+    std::fs::write(&output_path, output)?;
+
+    if open {
+        opener::open(&output_path)?;
+    }
+
+    report_diagnostics(&diagnostics, matches)
+}
+
+///Part of the model selected by --from and --to, or by --around
+fn plot_region(matches: &ArgMatches) -> Option<Region> {
+    let names = |id: &str| -> Vec<String> {
+        matches
+            .get_many::<String>(id)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect()
+    };
+    if let Some(center) = matches.get_one::<String>("AROUND") {
+        return Some(Region::Around {
+            center: center.clone(),
+            depth: *matches
+                .get_one::<usize>("DEPTH")
+                .expect("Depth has a default."),
+        });
+    }
+    let (from, to) = (names("FROM"), names("TO"));
+    if from.is_empty() && to.is_empty() {
+        return None;
+    }
+    Some(Region::Range { from, to })
+}
+
+///Lays the graph out with Graphviz, failing if it is missing or rejects the graph
+fn dot_svg(graph: &RenderableGraph) -> anyhow::Result<String> {
     let mut f = NamedTempFile::new().expect("Failed to create temp file.");
-    render_to(&mut f, plottable);
-    ProcessCommand::new("dot")
+    render_to(&mut f, graph)?;
+    let output = ProcessCommand::new("dot")
         .arg("-Tsvg")
         .arg(f.path())
-        .arg("-o")
-        .arg(output_path)
         .output()
-        .expect("Failed to call Dot, is it installed?");
-
-    if open {
-        opener::open(output_path)?;
+        .map_err(|e| anyhow::anyhow!("Failed to call dot, is Graphviz installed? {}", e))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "dot failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
     }
-
-    Ok(())
+    Ok(String::from_utf8(output.stdout)?)
 }
 
 #[derive(Tabled)]
@@ -65,11 +266,44 @@ fn run_summary_command(matches: &ArgMatches) -> anyhow::Result<()> {
         .expect("Failed to find model at path.")
         .into();
 
-    let summary = parse_model(&model_path)?.build_traversal_order().run()?;
+    let format: SummaryFormat = matches
+        .get_one::<String>("FORMAT")
+        .expect("Format has a default.")
+        .parse()?;
+
+    let (model, summary) = analyze_model(&model_path, matches)?;
+    let repeats = repeated_subgraphs(&model, &summary)?;
+    let fingerprint = fingerprint(&model, &summary, &graph_stats(&model, &repeats)?);
+    if format != SummaryFormat::Table {
+        let model_name = model_path.file_stem().unwrap().to_str().unwrap();
+        let report = SummaryReport::new(
+            model_name,
+            &model,
+            &summary,
+            &fingerprint,
+            &repeats,
+            &load_devices()?,
+        );
+        match format {
+            SummaryFormat::Json => println!("{}", report.to_json()),
+            SummaryFormat::Csv => print!("{}", report.to_csv()),
+            _ => print!("{}", report.to_markdown()),
+        }
+        return report_diagnostics(&summary.diagnostics, matches);
+    }
+    let repeated = (!repeats.is_empty()).then(|| repeats_table(&model, &summary, &repeats));
     let op_frequencies = summary.op_frequencies.clone();
     let flops = summary.total_flops;
+    let shape_mismatches = summary.shape_mismatches.clone();
+    let diagnostics = summary.diagnostics.clone();
+    if !summary.hinted_nodes.is_empty() {
+        println!(
+            "{} unimplemented nodes used value_info shapes.",
+            summary.hinted_nodes.len()
+        );
+    }
 
-    let summary = vec![
+    let mut summary = vec![
         SummaryTable {
             table: "Operations".to_string(),
             subtable: opcount_table(op_frequencies),
@@ -82,7 +316,29 @@ fn run_summary_command(matches: &ArgMatches) -> anyhow::Result<()> {
             table: "Hardware".to_string(),
             subtable: hardware_table(flops),
         },
+        SummaryTable {
+            table: "Architecture".to_string(),
+            subtable: fingerprint_table(&fingerprint),
+        },
     ];
+    if let Some(repeated) = repeated {
+        summary.push(SummaryTable {
+            table: "Repeated Blocks".to_string(),
+            subtable: repeated,
+        });
+    }
+    if !shape_mismatches.is_empty() {
+        summary.push(SummaryTable {
+            table: "Shape Mismatches".to_string(),
+            subtable: shape_mismatch_table(&shape_mismatches),
+        });
+    }
+    if !diagnostics.is_empty() {
+        summary.push(SummaryTable {
+            table: "Diagnostics".to_string(),
+            subtable: diagnostics_table(&diagnostics),
+        });
+    }
 
     let res = Table::new(summary)
         .with(Panel::header(format!(
@@ -95,6 +351,287 @@ fn run_summary_command(matches: &ArgMatches) -> anyhow::Result<()> {
         .to_owned();
 
     println!("{}", res);
+    check_strict(&diagnostics, matches)
+}
+
+fn run_infer_shapes_command(matches: &ArgMatches) -> anyhow::Result<()> {
+    let model_path = matches
+        .get_one::<String>("MODEL_PATH")
+        .expect("Failed to find model at path.")
+        .into();
+    let output_path = matches
+        .get_one::<String>("OUTPUT_PATH")
+        .expect("Invalid output path provided.");
+
+    let summary = analyze_model(&model_path, matches)?.1;
+
+    //Binary models are annotated in place, text models are encoded first
+    let is_text = |path: &std::path::Path| path.extension().map_or(false, |ext| ext == "onnxtxt");
+    let original = if is_text(&model_path) {
+        decode_model(&model_path)?.encode_to_vec()
+    } else {
+        std::fs::read(&model_path)?
+    };
+    let mut annotated = Vec::with_capacity(original.len());
+    let count = write_inferred_shapes(&original, &summary, &mut annotated)?;
+    if is_text(output_path.as_ref()) {
+        let model = onnx_pb::ModelProto::decode(&annotated[..])?;
+        std::fs::write(output_path, to_text(&model, true)?)?;
+    } else {
+        std::fs::write(output_path, annotated)?;
+    }
+
+    println!("Annotated {} tensors in {}", count, output_path);
+    report_diagnostics(&summary.diagnostics, matches)
+}
 
+///Splits an `old=new` style argument
+fn split_pair(arg: &str) -> anyhow::Result<(&str, &str)> {
+    arg.split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("Expected a value of the form `a=b`, got {}", arg))
+}
+
+fn write_model(
+    model: onnx_pb::ModelProto,
+    model_path: &std::path::Path,
+    output_path: &std::path::Path,
+) -> anyhow::Result<()> {
+    let moved = model_path.parent() != output_path.parent();
+    if let Some(graph) = model.graph.as_ref() {
+        if moved && graph.initializer.iter().any(is_external) {
+            eprintln!(
+                "External data locations are relative to {}, copy the weight files next to {}",
+                model_path.display(),
+                output_path.display()
+            );
+        }
+    }
+    //Text output is written in full, as the printed model should load back the same. Models
+    //the text can't hold, such as those with external data, are an error.
+    let bytes = if output_path
+        .extension()
+        .map_or(false, |ext| ext == "onnxtxt")
+    {
+        to_text(&model, true)?.into_bytes()
+    } else {
+        model.encode_to_vec()
+    };
+    drop(model); //release the memory map before the output overwrites it
+    std::fs::write(output_path, bytes)?;
+    Ok(())
+}
+
+///Decodes the model for editing along with the parsed model the edits are made to.
+///Weights are not needed to edit, so are left unloaded.
+fn parse_for_edit(model_path: &std::path::Path) -> anyhow::Result<(onnx_pb::ModelProto, Model)> {
+    let proto = decode_model(&model_path.to_path_buf())?;
+    let model_dir = model_path
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new(""));
+    let model = model_from_proto(proto.clone(), model_dir, WeightLoading::ShapesOnly)?;
+    Ok((proto, model))
+}
+
+fn run_edit_command(matches: &ArgMatches) -> anyhow::Result<()> {
+    let (command, matches) = matches.subcommand().unwrap();
+    let model_path: std::path::PathBuf = matches
+        .get_one::<String>("MODEL_PATH")
+        .expect("Failed to find model at path.")
+        .into();
+    let output_path = std::path::PathBuf::from(
+        matches
+            .get_one::<String>("OUTPUT_PATH")
+            .expect("Invalid output path provided."),
+    );
+
+    let model = match command {
+        "set-shape" => {
+            let (mut proto, mut model) = parse_for_edit(&model_path)?;
+            let shapes: Vec<&String> = matches.get_many("SHAPE").into_iter().flatten().collect();
+            let batch_size = matches.get_one::<String>("BATCH_SIZE");
+            if shapes.is_empty() && batch_size.is_none() {
+                anyhow::bail!("Provide at least one of --shape or --batch-size");
+            }
+            for shape in shapes {
+                let (input, dims) = split_pair(shape)?;
+                set_input_shape(&mut model, input, &parse_dims(dims)?)?;
+            }
+            if let Some(batch_size) = batch_size {
+                let changed = set_batch_size(&mut model, &batch_size.parse()?)?;
+                println!("Changed the batch dimension of {} tensors", changed);
+            }
+            write_edits(&model, &mut proto)?;
+            proto
+        }
+        "rename" => {
+            let (mut proto, mut model) = parse_for_edit(&model_path)?;
+            for pair in matches.get_many::<String>("TENSOR").into_iter().flatten() {
+                let (old, new) = split_pair(pair)?;
+                rename_tensor(&mut model, old, new)?;
+            }
+            for pair in matches.get_many::<String>("NODE").into_iter().flatten() {
+                let (old, new) = split_pair(pair)?;
+                rename_node(&mut model, old, new)?;
+            }
+            write_edits(&model, &mut proto)?;
+            proto
+        }
+        "extract" => {
+            let inputs: Vec<String> = matches.get_many("INPUTS").unwrap().cloned().collect();
+            let outputs: Vec<String> = matches.get_many("OUTPUTS").unwrap().cloned().collect();
+            //Types of tensors which become inputs or outputs come from shape inference
+            let summary = analyze_model(&model_path, matches)?.1;
+            for diagnostic in summary.diagnostics.iter() {
+                eprintln!("{}", diagnostic);
+            }
+            let original = decode_model(&model_path)?;
+            let extracted =
+                extract_subgraph(&original, &inputs, &outputs, &summary.inferred_tensors)?;
+            let nodes = extracted.graph.as_ref().map_or(0, |g| g.node.len());
+            println!("Extracted {} nodes", nodes);
+            extracted
+        }
+        _ => unreachable!("Invalid edit command provided."),
+    };
+
+    write_model(model, &model_path, &output_path)?;
+    println!("Wrote {}", output_path.display());
+    Ok(())
+}
+
+fn run_pipeline_command(matches: &ArgMatches) -> anyhow::Result<()> {
+    let model_path: std::path::PathBuf = matches
+        .get_one::<String>("MODEL_PATH")
+        .expect("Failed to find model at path.")
+        .into();
+    let n_stages = *matches
+        .get_one::<usize>("STAGES")
+        .expect("Number of stages is required.");
+    let balance: BalanceBy = matches
+        .get_one::<String>("BALANCE")
+        .expect("Balance metric has a default.")
+        .parse()?;
+
+    let devices = matches
+        .get_many::<String>("DEVICE")
+        .into_iter()
+        .flatten()
+        .map(|name| find_device(name))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let (model, summary) = analyze_model(&model_path, matches)?;
+    let plan = partition_pipeline(&model, &summary, n_stages, balance, &devices)?;
+
+    println!("{}", pipeline_table(&plan));
+    if !plan.cuts.iter().all(|cut| cut.tensors.is_empty()) {
+        println!("{}", cuts_table(&plan));
+    }
+
+    if let Some(output_dir) = matches.get_one::<String>("OUTPUT_DIR") {
+        let output_dir = std::path::Path::new(output_dir);
+        std::fs::create_dir_all(output_dir)?;
+        let original = decode_model(&model_path)?;
+        let stages = plan
+            .stages
+            .iter()
+            .map(|stage| {
+                extract_subgraph(
+                    &original,
+                    &stage.inputs,
+                    &stage.outputs,
+                    &summary.inferred_tensors,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(original);
+        for (idx, stage) in stages.into_iter().enumerate() {
+            let stage_path = output_dir.join(format!("stage_{}.onnx", idx));
+            write_model(stage, &model_path, &stage_path)?;
+            println!("Wrote {}", stage_path.display());
+        }
+    }
+    report_diagnostics(&summary.diagnostics, matches)
+}
+
+fn run_shard_command(matches: &ArgMatches) -> anyhow::Result<()> {
+    let model_path: std::path::PathBuf = matches
+        .get_one::<String>("MODEL_PATH")
+        .expect("Failed to find model at path.")
+        .into();
+    let degree = *matches
+        .get_one::<usize>("DEGREE")
+        .expect("Degree is required.");
+
+    let (model, summary) = analyze_model(&model_path, matches)?;
+    let plan = shard_tensor_parallel(&model, &summary, degree)?;
+
+    println!("{}", sharding_table(&plan));
+    println!("{}", sharded_nodes_table(&model, &plan));
+    if !plan.collectives.is_empty() {
+        println!("{}", collectives_table(&plan));
+    }
+    report_diagnostics(&summary.diagnostics, matches)
+}
+
+fn run_critical_path_command(matches: &ArgMatches) -> anyhow::Result<()> {
+    let model_path: std::path::PathBuf = matches
+        .get_one::<String>("MODEL_PATH")
+        .expect("Failed to find model at path.")
+        .into();
+    let weight = match matches.get_one::<String>("DEVICE") {
+        Some(name) => PathWeight::Latency(find_device(name)?),
+        None => PathWeight::Flops,
+    };
+
+    let (model, summary) = analyze_model(&model_path, matches)?;
+    let critical_path = critical_path(&model, &summary, &weight)?;
+
+    println!("{}", parallelism_table(&critical_path, &weight));
+    println!("{}", critical_path_table(&model, &critical_path));
+    report_diagnostics(&summary.diagnostics, matches)
+}
+
+fn run_stats_command(matches: &ArgMatches) -> anyhow::Result<()> {
+    let model_path: std::path::PathBuf = matches
+        .get_one::<String>("MODEL_PATH")
+        .expect("Failed to find model at path.")
+        .into();
+
+    let (model, summary) = analyze_model(&model_path, matches)?;
+    let stats = graph_stats(&model, &repeated_subgraphs(&model, &summary)?)?;
+
+    println!("{}", stats_table(&model, &stats));
+    if !stats.residuals.is_empty() {
+        println!("{}", residuals_table(&model, &stats));
+    }
+    report_diagnostics(&summary.diagnostics, matches)
+}
+
+fn run_fingerprint_command(matches: &ArgMatches) -> anyhow::Result<()> {
+    let mut fingerprints = vec![];
+    let mut diagnostics = vec![];
+    for path in matches.get_many::<String>("MODEL_PATH").unwrap() {
+        let (model, summary) = analyze_model(&path.into(), matches)?;
+        //Repeated blocks play no part in the fingerprint
+        let stats = graph_stats(&model, &[])?;
+        fingerprints.push((path.clone(), fingerprint(&model, &summary, &stats)));
+        diagnostics.extend(summary.diagnostics);
+    }
+
+    println!("{}", structures_table(&fingerprints));
+    report_diagnostics(&diagnostics, matches)
+}
+
+fn run_print_command(matches: &ArgMatches) -> anyhow::Result<()> {
+    let model_path: std::path::PathBuf = matches
+        .get_one::<String>("MODEL_PATH")
+        .expect("Failed to find model at path.")
+        .into();
+    let text = to_text(&decode_model(&model_path)?, matches.is_present("FULL"))?;
+
+    match matches.get_one::<String>("OUTPUT_PATH") {
+        Some(output_path) => std::fs::write(output_path, text)?,
+        None => print!("{}", text),
+    }
     Ok(())
 }
@@ -0,0 +1,216 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Component, Path, PathBuf},
+};
+
+use bytes::Bytes;
+use memmap2::MmapOptions;
+use steelix_onnx::onnx_pb::{self, tensor_proto::DataLocation};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExternalDataError {
+    #[error("Tensor {0} is stored externally but has no location entry.")]
+    MissingLocation(String),
+    #[error("Tensor {tensor} has an invalid external data location: {location}")]
+    InvalidLocation { tensor: String, location: String },
+    #[error("Tensor {tensor} has an invalid {key} entry: {value}")]
+    InvalidEntry {
+        tensor: String,
+        key: String,
+        value: String,
+    },
+    #[error("External data file {path} for tensor {tensor} does not exist.")]
+    MissingFile { tensor: String, path: PathBuf },
+    #[error(
+        "Tensor {tensor} requests bytes {offset}..{end} of {path}, which is only {size} bytes."
+    )]
+    OutOfBounds {
+        tensor: String,
+        path: PathBuf,
+        offset: u64,
+        end: u64,
+        size: u64,
+    },
+    #[error("Tensor {tensor} requests {length} bytes at offset {offset}, which overflows.")]
+    RangeOverflow {
+        tensor: String,
+        offset: u64,
+        length: u64,
+    },
+    #[error("Tensor {tensor} failed checksum verification, expected {expected} got {actual}.")]
+    ChecksumMismatch {
+        tensor: String,
+        expected: String,
+        actual: String,
+    },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+///Location of a tensor's raw bytes outside of the ONNX protobuf.
+///See: https://github.com/onnx/onnx/blob/main/docs/ExternalData.md
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalDataInfo {
+    pub location: PathBuf,
+    pub offset: u64,
+    pub length: Option<u64>,
+    pub checksum: Option<String>,
+}
+
+impl ExternalDataInfo {
+    pub fn from_proto(tproto: &onnx_pb::TensorProto) -> Result<Self, ExternalDataError> {
+        let mut location = None;
+        let mut offset = 0;
+        let mut length = None;
+        let mut checksum = None;
+
+        let parse_int = |key: &str, value: &str| {
+            value
+                .parse::<u64>()
+                .map_err(|_| ExternalDataError::InvalidEntry {
+                    tensor: tproto.name.clone(),
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+        };
+
+        for entry in tproto.external_data.iter() {
+            match &*entry.key {
+                "location" => location = Some(PathBuf::from(&entry.value)),
+                "offset" => offset = parse_int("offset", &entry.value)?,
+                "length" => length = Some(parse_int("length", &entry.value)?),
+                "checksum" => checksum = Some(entry.value.to_lowercase()),
+                _ => {} //unrecognized keys are ignored, as per the spec
+            }
+        }
+
+        let location =
+            location.ok_or_else(|| ExternalDataError::MissingLocation(tproto.name.clone()))?;
+
+        //Locations must stay within the model directory
+        if location
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(ExternalDataError::InvalidLocation {
+                tensor: tproto.name.clone(),
+                location: location.display().to_string(),
+            });
+        }
+
+        Ok(Self {
+            location,
+            offset,
+            length,
+            checksum,
+        })
+    }
+
+    ///Opens the referenced file, checking that the requested range lies within it
+    fn open(&self, tensor: &str, model_dir: &Path) -> Result<(File, u64), ExternalDataError> {
+        let path = model_dir.join(&self.location);
+        if !path.is_file() {
+            return Err(ExternalDataError::MissingFile {
+                tensor: tensor.to_string(),
+                path,
+            });
+        }
+
+        let file = File::open(&path)?;
+        let size = file.metadata()?.len();
+        let end =
+            match self.length {
+                Some(length) => self.offset.checked_add(length).ok_or_else(|| {
+                    ExternalDataError::RangeOverflow {
+                        tensor: tensor.to_string(),
+                        offset: self.offset,
+                        length,
+                    }
+                })?,
+                None => size,
+            };
+        if self.offset > size || end > size {
+            return Err(ExternalDataError::OutOfBounds {
+                tensor: tensor.to_string(),
+                path,
+                offset: self.offset,
+                end,
+                size,
+            });
+        }
+        Ok((file, end))
+    }
+
+    ///Reads the referenced bytes, resolving the location relative to `model_dir`
+    pub fn read(&self, tensor: &str, model_dir: &Path) -> Result<Vec<u8>, ExternalDataError> {
+        let (mut file, end) = self.open(tensor, model_dir)?;
+        let mut data = vec![0; (end - self.offset) as usize];
+        file.seek(SeekFrom::Start(self.offset))?;
+        file.read_exact(&mut data)?;
+
+        if let Some(expected) = &self.checksum {
+            let actual = sha1_smol::Sha1::from(&data).digest().to_string();
+            if &actual != expected {
+                return Err(ExternalDataError::ChecksumMismatch {
+                    tensor: tensor.to_string(),
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        Ok(data)
+    }
+
+    ///Memory maps the referenced bytes without reading them.
+    ///Checksums are not verified, as that would require touching every page.
+    pub fn map(&self, tensor: &str, model_dir: &Path) -> Result<Bytes, ExternalDataError> {
+        let (file, end) = self.open(tensor, model_dir)?;
+        if end == self.offset {
+            return Ok(Bytes::new());
+        }
+        //SAFETY: the weights file must not be modified while the model is being analyzed
+        let mmap = unsafe {
+            MmapOptions::new()
+                .offset(self.offset)
+                .len((end - self.offset) as usize)
+                .map(&file)?
+        };
+        Ok(Bytes::from_owner(mmap))
+    }
+}
+
+#[inline]
+pub fn is_external(tproto: &onnx_pb::TensorProto) -> bool {
+    tproto.data_location == DataLocation::External as i32
+}
+
+///Moves externally stored data into `raw_data`, so the tensor can be converted as usual.
+pub fn resolve_external_data(
+    tproto: &mut onnx_pb::TensorProto,
+    model_dir: &Path,
+) -> Result<(), ExternalDataError> {
+    if !is_external(tproto) {
+        return Ok(());
+    }
+    let info = ExternalDataInfo::from_proto(tproto)?;
+    tproto.raw_data = info.read(&tproto.name, model_dir)?.into();
+    tproto.external_data.clear();
+    tproto.data_location = DataLocation::Default as i32;
+    Ok(())
+}
+
+///As `resolve_external_data`, but `raw_data` borrows from a memory map of the external file.
+pub fn map_external_data(
+    tproto: &mut onnx_pb::TensorProto,
+    model_dir: &Path,
+) -> Result<(), ExternalDataError> {
+    if !is_external(tproto) {
+        return Ok(());
+    }
+    let info = ExternalDataInfo::from_proto(tproto)?;
+    tproto.raw_data = info.map(&tproto.name, model_dir)?;
+    tproto.external_data.clear();
+    tproto.data_location = DataLocation::Default as i32;
+    Ok(())
+}
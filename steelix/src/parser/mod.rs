@@ -1,3 +1,9 @@
+mod external_data;
 mod onnx_parser;
+mod onnx_text;
+mod tensor_index;
 
+pub use external_data::*;
 pub use onnx_parser::*;
+pub use onnx_text::*;
+pub use tensor_index::*;
@@ -1,70 +1,185 @@
-use crate::ir::{ops::data::Unimplemented, *};
+use crate::ir::{ops::data::Unimplemented, text::node_attributes, *};
+use memmap2::Mmap;
 use prost::Message;
-use std::collections::{HashMap, HashSet};
-use steelix_onnx::onnx_pb;
+use std::{collections::HashMap, fs::File, path::Path};
+use steelix_onnx::onnx_pb::{self, tensor_proto::DataType as ProtoDType};
+
+use super::{map_external_data, parse_text, resolve_external_data, TensorIndex, TensorSource};
 
 ///Parses a valid ONNX model at the provided path
 pub fn parse_model(model_path: &std::path::PathBuf) -> Result<Model, anyhow::Error> {
-    let pb_model = onnx_pb::ModelProto::decode(bytes::Bytes::from(std::fs::read(model_path)?))?;
-    let pb_graph = pb_model.graph.expect("No model graph found.");
+    parse_model_with(model_path, WeightLoading::Eager)
+}
+
+///Parses a valid ONNX model at the provided path, loading initializers as specified
+pub fn parse_model_with(
+    model_path: &std::path::PathBuf,
+    loading: WeightLoading,
+) -> Result<Model, anyhow::Error> {
+    //External data locations are relative to the directory containing the model
+    let model_dir = model_path.parent().unwrap_or_else(|| Path::new(""));
+    model_from_proto(decode_model(model_path)?, model_dir, loading)
+}
+
+///Parses a model written in the ONNX textual syntax, see `parse_text`
+pub fn parse_text_model(text: &str) -> Result<Model, anyhow::Error> {
+    model_from_proto(parse_text(text)?, Path::new(""), WeightLoading::Eager)
+}
+
+///Builds the model graph from a decoded proto.
+///External data is resolved relative to `model_dir`.
+pub fn model_from_proto(
+    pb_model: onnx_pb::ModelProto,
+    model_dir: &Path,
+    loading: WeightLoading,
+) -> Result<Model, anyhow::Error> {
+    let mut pb_graph = pb_model
+        .graph
+        .ok_or_else(|| anyhow::anyhow!("No model graph found."))?;
 
     let mut model = Model::new();
+    model.opset_imports = pb_model
+        .opset_import
+        .iter()
+        .map(|opset| match &*opset.domain {
+            "ai.onnx" => (String::new(), opset.version),
+            domain => (domain.to_string(), opset.version),
+        })
+        .collect();
 
-    let mut initializers_map = parse_graph_initializers(&pb_graph.initializer);
-    let inputs_map = parse_graph_inputs(&pb_graph.input, &mut initializers_map, &mut model);
+    //Node ids follow the order of the file, so repeated parses of a model agree
+    let initializer_order: Vec<String> = pb_graph
+        .initializer
+        .iter()
+        .map(|i| i.name.clone())
+        .collect();
+    let mut initializers_map = parse_graph_initializers(
+        std::mem::take(&mut pb_graph.initializer),
+        model_dir,
+        loading,
+        &mut model.diagnostics,
+    );
+    let mut tensor_ids = parse_graph_inputs(&pb_graph.input, &mut initializers_map, &mut model);
 
-    let initializer_ids =
-        initializers_map
-            .into_iter()
-            .fold(HashMap::new(), |mut acc, (name, tensor)| {
-                acc.insert(
-                    name.clone(),
-                    model.add_node(name, ops::data::build_constant(tensor).unwrap()),
-                );
-                acc
-            });
+    for name in initializer_order {
+        let constant = match initializers_map.remove(&name) {
+            Some(constant) => constant,
+            None => continue, //listed as a graph input
+        };
+        let id = model.add_node(name.clone(), constant);
+        model.nodes[id].outputs = vec![name.clone()];
+        tensor_ids.insert(name, id);
+    }
 
+    let graph_offset = model.nodes.len();
     create_graph_nodes(&mut model, &pb_graph.node, OpRegister::default());
 
     let outputs_map = parse_graph_outputs(&pb_graph.output, &mut model);
-    link_nodes(
-        &mut model,
-        &pb_graph,
-        inputs_map,
-        outputs_map,
-        initializer_ids,
-    );
+    model.value_info = parse_graph_value_info(&pb_graph);
+    link_nodes(&mut model, &pb_graph, tensor_ids, outputs_map, graph_offset);
     Ok(model)
 }
 
-///Model initializers from ONNX file
-fn parse_graph_initializers(initializers: &[onnx_pb::TensorProto]) -> HashMap<String, Tensor> {
-    initializers.iter().fold(HashMap::new(), |mut acc, ip| {
-        acc.insert(ip.name.to_owned(), ip.clone().try_into().unwrap());
-        acc
-    })
+///Decodes the protobuf from a memory map of the model.
+///Tensor `raw_data` borrows from the map rather than being copied.
+///
+///Files with the `.onnxtxt` extension are parsed as the ONNX textual syntax.
+pub fn decode_model(model_path: &std::path::PathBuf) -> Result<onnx_pb::ModelProto, anyhow::Error> {
+    if model_path.extension().map_or(false, |ext| ext == "onnxtxt") {
+        return Ok(parse_text(&std::fs::read_to_string(model_path)?)?);
+    }
+    let file = File::open(model_path)?;
+    //SAFETY: the model file must not be modified while it is being analyzed
+    let mmap = unsafe { Mmap::map(&file)? };
+    Ok(onnx_pb::ModelProto::decode(bytes::Bytes::from_owner(mmap))?)
 }
 
-///User provided graph inputs
+///Model initializers from ONNX file, as Constant operations.
+///Initializers which fail to load are replaced by constants of unknown shape.
+fn parse_graph_initializers(
+    initializers: Vec<onnx_pb::TensorProto>,
+    model_dir: &Path,
+    loading: WeightLoading,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, BoxOp> {
+    let mut constants = HashMap::new();
+    for mut ip in initializers.into_iter() {
+        let name = ip.name.clone();
+        let dt = ProtoDType::from_i32(ip.data_type)
+            .and_then(|dt| DType::try_from(dt).ok())
+            .unwrap_or_default();
+        let constant = match loading {
+            WeightLoading::Eager => resolve_external_data(&mut ip, model_dir)
+                .map_err(anyhow::Error::from)
+                .and_then(|_| ops::data::build_constant(ip.try_into()?)),
+            WeightLoading::Lazy | WeightLoading::ShapesOnly => {
+                map_external_data(&mut ip, model_dir)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|_| ops::data::build_lazy_constant(LazyTensor::new(ip, loading)?))
+            }
+        };
+        let constant = constant.unwrap_or_else(|e| {
+            diagnostics.push(
+                Diagnostic::error(Stage::Parse, format!("{:#}", e))
+                    .with_node(None, &name, "Constant"),
+            );
+            ops::data::build_constant(Tensor::unknown(dt)).unwrap()
+        });
+        constants.insert(name, constant);
+    }
+    constants
+}
+
+///User provided graph inputs, returning the node id of each input tensor
 fn parse_graph_inputs(
     inputs: &[onnx_pb::ValueInfoProto],
-    initializers_map: &mut HashMap<String, Tensor>,
+    initializers_map: &mut HashMap<String, BoxOp>,
     model: &mut Model,
 ) -> HashMap<String, usize> {
     let mut inputs_map = HashMap::new();
-    for (input_idx, input) in inputs.iter().enumerate() {
+    for input in inputs.iter() {
         if let Some(init) = initializers_map.remove(&*input.name) {
-            model.add_node(
-                input.name.to_owned(),
-                ops::data::build_constant(init).unwrap(), //static constants
-            );
+            let id = model.add_node(input.name.to_owned(), init); //static constants
+            model.nodes[id].outputs = vec![input.name.to_owned()];
+            inputs_map.insert(input.name.to_owned(), id);
         } else {
-            let input_node_id = model.add_node(
-                input.name.to_owned(),
-                ops::data::build_initial((*input).clone().try_into().unwrap()).unwrap(),
-            );
+            let initial = match ValueInfo::try_from((*input).clone()) {
+                Ok(value_info) => {
+                    let params: Vec<&str> = value_info
+                        .dim_params
+                        .iter()
+                        .flatten()
+                        .map(|p| &**p)
+                        .collect();
+                    if !params.is_empty() {
+                        model.diagnostics.push(
+                            Diagnostic::warning(
+                                Stage::Parse,
+                                format!(
+                                    "symbolic dimensions {} are taken to be 1",
+                                    params.join(", ")
+                                ),
+                            )
+                            .with_node(None, &input.name, "Input"),
+                        );
+                    }
+                    ops::data::build_initial(value_info).unwrap()
+                }
+                Err(e) => {
+                    model.diagnostics.push(
+                        Diagnostic::error(Stage::Parse, e.to_string()).with_node(
+                            None,
+                            &input.name,
+                            "Input",
+                        ),
+                    );
+                    ops::data::build_unknown_initial(DType::default()).unwrap()
+                }
+            };
+            let input_node_id = model.add_node(input.name.to_owned(), initial);
+            model.nodes[input_node_id].outputs = vec![input.name.to_owned()];
             model.inputs.push(input_node_id);
-            inputs_map.insert(input.name.to_owned(), input_idx);
+            inputs_map.insert(input.name.to_owned(), input_node_id);
         }
     }
     inputs_map
@@ -79,52 +194,45 @@ fn parse_graph_outputs(
     for (output_idx, output) in outputs.iter().enumerate() {
         let output_node_id =
             model.add_node(output.name.to_owned(), Box::new(Unimplemented) as BoxOp);
+        model.nodes[output_node_id].inputs = vec![output.name.to_owned()];
         model.outputs.push(output_node_id);
         outputs_map.insert(output.name.to_owned(), offset + output_idx);
     }
     outputs_map
 }
 
+///Adds an edge from the producer of each node input, in input order.
+///`tensor_ids` holds the node ids of graph inputs and initializers.
 fn link_nodes(
     model: &mut Model,
     model_graph: &onnx_pb::GraphProto,
-    inputs_map: HashMap<String, usize>,
+    tensor_ids: HashMap<String, usize>,
     outputs_map: HashMap<String, usize>,
-    initializers_map: HashMap<String, usize>,
+    graph_offset: usize,
 ) {
-    let graph_nodes = &model_graph.node;
-
-    let graph_offset = model.inputs.len() + initializers_map.len();
-    for (op_idx, op_node) in graph_nodes.iter().enumerate() {
-        op_node.input.iter().for_each(|input| {
-            if inputs_map.contains_key(input) {
-                model.add_edge(*inputs_map.get(input).unwrap(), op_idx + graph_offset);
-            }
-
-            if initializers_map.contains_key(input) {
-                model.add_edge(*initializers_map.get(input).unwrap(), op_idx + graph_offset);
-            }
-        });
-
-        op_node.output.iter().for_each(|output| {
-            if outputs_map.contains_key(output) {
-                model.add_edge(op_idx + graph_offset, *outputs_map.get(output).unwrap());
+    let index = TensorIndex::new(model_graph);
+    for (op_idx, op_node) in model_graph.node.iter().enumerate() {
+        let consumer_id = op_idx + graph_offset;
+        let mut producer_ids = vec![];
+        for input in op_node.input.iter() {
+            //Initializers were taken out of the graph, so are only found in `tensor_ids`.
+            //Optional inputs left empty are found in neither.
+            let producer_id = match index.producer(input) {
+                Some(TensorSource::Node(producer_idx)) => Some(producer_idx + graph_offset),
+                _ => tensor_ids.get(input).copied(),
+            };
+            match producer_id {
+                Some(id) if !producer_ids.contains(&id) => producer_ids.push(id),
+                _ => {}
             }
-        });
+        }
+        for producer_id in producer_ids {
+            model.add_edge(producer_id, consumer_id);
+        }
 
-        let output_set: HashSet<String> = HashSet::from_iter(op_node.output.iter().cloned());
-        let target_offset = op_idx + 1; //topo offset
-        for (target_idx, target_node) in graph_nodes[target_offset..graph_nodes.len()]
-            .iter()
-            .enumerate()
-        {
-            let target_inp_set: HashSet<String> =
-                HashSet::from_iter(target_node.input.iter().cloned());
-
-            if !target_inp_set.is_disjoint(&output_set) {
-                let producer_id = op_idx + graph_offset;
-                let consumer_id = target_idx + target_offset + graph_offset;
-                model.add_edge(producer_id, consumer_id);
+        for output in op_node.output.iter() {
+            if let Some(&output_id) = outputs_map.get(output) {
+                model.add_edge(consumer_id, output_id);
             }
         }
     }
@@ -136,11 +244,48 @@ fn create_graph_nodes(
     op_register: OpRegister,
 ) {
     for op_node in graph_nodes.iter() {
-        let op = match op_register.get(&op_node.op_type.clone()) {
-            Some(builder) => (builder)(op_node).unwrap(),
-            None => ops::data::build_unimplemented(op_node).unwrap(),
+        let id = model.nodes.len();
+        let (op, diagnostic) = match op_register.get(&op_node.op_type.clone()) {
+            Some(builder) => match (builder)(op_node) {
+                Ok(op) => (op, None),
+                Err(e) => (
+                    ops::data::build_unimplemented(op_node).unwrap(),
+                    Some(Diagnostic::error(Stage::Parse, format!("{:#}", e))),
+                ),
+            },
+            None => (
+                ops::data::build_unimplemented(op_node).unwrap(),
+                Some(Diagnostic::warning(
+                    Stage::Parse,
+                    "unimplemented operator, shapes depending on it will be unknown",
+                )),
+            ),
         };
 
         model.add_node(op_node.op_type.clone(), op);
+        let node = &mut model.nodes[id];
+        node.onnx_name = op_node.name.clone();
+        node.domain = op_node.domain.clone();
+        node.inputs = op_node.input.clone();
+        node.outputs = op_node.output.clone();
+        node.attributes = node_attributes(op_node);
+        if let Some(diagnostic) = diagnostic {
+            let diagnostic = model.node_diagnostic(id, diagnostic);
+            model.diagnostics.push(diagnostic);
+        }
     }
 }
+
+///Shape annotations of graph inputs, intermediate tensors and graph outputs, where parseable
+fn parse_graph_value_info(graph: &onnx_pb::GraphProto) -> HashMap<String, ValueInfo> {
+    graph
+        .input
+        .iter()
+        .chain(graph.value_info.iter())
+        .chain(graph.output.iter())
+        .filter_map(|vip| {
+            let value_info: ValueInfo = vip.clone().try_into().ok()?;
+            Some((value_info.name.clone(), value_info))
+        })
+        .collect()
+}
@@ -0,0 +1,637 @@
+use half::{bf16, f16};
+use steelix_onnx::onnx_pb::{
+    self,
+    attribute_proto::AttributeType,
+    tensor_proto::DataType as ProtoDType,
+    tensor_shape_proto::{dimension::Value as DimValue, Dimension},
+};
+
+use crate::ir::text::ELEM_TYPES;
+
+fn elem_type(name: &str) -> Option<ProtoDType> {
+    ELEM_TYPES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, dt)| *dt)
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("line {line}: {message}")]
+pub struct TextParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Arrow,
+    Punct(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Str(s) => write!(f, "{:?}", s),
+            Token::Int(i) => write!(f, "{}", i),
+            Token::Float(x) => write!(f, "{}", x),
+            Token::Arrow => write!(f, "=>"),
+            Token::Punct(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, TextParseError> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let error = |line, message: String| TextParseError { line, message };
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '\n' => {
+                line += 1;
+                chars.next();
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '#' => {
+                while chars.peek().map_or(false, |&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(c) => s.push(c),
+                            None => return Err(error(line, "unterminated string".to_string())),
+                        },
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            s.push(c)
+                        }
+                        None => return Err(error(line, "unterminated string".to_string())),
+                    }
+                }
+                tokens.push((Token::Str(s), line));
+            }
+            '=' => {
+                chars.next();
+                if chars.peek() == Some(&'>') {
+                    chars.next();
+                    tokens.push((Token::Arrow, line));
+                } else {
+                    tokens.push((Token::Punct('='), line));
+                }
+            }
+            '(' | ')' | '[' | ']' | '{' | '}' | '<' | '>' | ',' | ':' | '?' => {
+                chars.next();
+                tokens.push((Token::Punct(c), line));
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' => {
+                let mut number = String::new();
+                number.push(c);
+                chars.next();
+                while let Some(&c) = chars.peek() {
+                    let exponent_sign =
+                        (c == '-' || c == '+') && matches!(number.chars().last(), Some('e' | 'E'));
+                    if c.is_ascii_alphanumeric() || c == '.' || exponent_sign {
+                        number.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let token = if let Ok(i) = number.parse::<i64>() {
+                    Token::Int(i)
+                } else if let Ok(x) = number.parse::<f64>() {
+                    Token::Float(x)
+                } else {
+                    return Err(error(line, format!("invalid number {}", number)));
+                };
+                tokens.push((token, line));
+            }
+            c if is_ident_char(c) => {
+                let mut ident = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !is_ident_char(c) {
+                        break;
+                    }
+                    ident.push(c);
+                    chars.next();
+                }
+                let token = match &*ident {
+                    "inf" => Token::Float(f64::INFINITY),
+                    "nan" => Token::Float(f64::NAN),
+                    _ => Token::Ident(ident),
+                };
+                tokens.push((token, line));
+            }
+            c => return Err(error(line, format!("unexpected character '{}'", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n).map(|(t, _)| t)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, TextParseError> {
+        Err(TextParseError {
+            line: self.line(),
+            message: message.into(),
+        })
+    }
+
+    fn next(&mut self) -> Result<Token, TextParseError> {
+        match self.tokens.get(self.pos) {
+            Some((token, _)) => {
+                self.pos += 1;
+                Ok(token.clone())
+            }
+            None => self.error("unexpected end of input"),
+        }
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    ///Consumes the punctuation if it is next
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.is_punct(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), TextParseError> {
+        match self.next()? {
+            Token::Punct(p) if p == c => Ok(()),
+            other => {
+                self.pos -= 1;
+                self.error(format!("expected '{}', found '{}'", c, other))
+            }
+        }
+    }
+
+    ///Parses `item` until the closing punctuation, separated by commas
+    fn list<T>(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<T, TextParseError>,
+    ) -> Result<Vec<T>, TextParseError> {
+        let mut items = vec![];
+        if self.eat(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    ///Identifiers which are not valid in the syntax, such as `/conv/Conv_output_0`, are quoted
+    fn name(&mut self) -> Result<String, TextParseError> {
+        match self.next()? {
+            Token::Ident(s) | Token::Str(s) => Ok(s),
+            other => {
+                self.pos -= 1;
+                self.error(format!("expected a name, found '{}'", other))
+            }
+        }
+    }
+
+    fn int(&mut self) -> Result<i64, TextParseError> {
+        match self.next()? {
+            Token::Int(i) => Ok(i),
+            other => {
+                self.pos -= 1;
+                self.error(format!("expected an integer, found '{}'", other))
+            }
+        }
+    }
+
+    fn model(&mut self) -> Result<onnx_pb::ModelProto, TextParseError> {
+        let mut model = onnx_pb::ModelProto::default();
+        if self.eat('<') {
+            self.list('>', |p| p.header_field(&mut model))?;
+        }
+        model.graph = Some(self.graph()?);
+        if let Some(token) = self.peek() {
+            return self.error(format!("unexpected '{}' after the graph", token));
+        }
+        Ok(model)
+    }
+
+    fn header_field(&mut self, model: &mut onnx_pb::ModelProto) -> Result<(), TextParseError> {
+        let key = self.name()?;
+        self.expect(':')?;
+        match &*key {
+            "ir_version" => model.ir_version = self.int()?,
+            "model_version" => model.model_version = self.int()?,
+            "producer_name" => model.producer_name = self.name()?,
+            "producer_version" => model.producer_version = self.name()?,
+            "domain" => model.domain = self.name()?,
+            "doc_string" => model.doc_string = self.name()?,
+            "opset_import" => {
+                self.expect('[')?;
+                model.opset_import = self.list(']', |p| {
+                    let domain = p.name()?;
+                    p.expect(':')?;
+                    Ok(onnx_pb::OperatorSetIdProto {
+                        domain,
+                        version: p.int()?,
+                    })
+                })?;
+            }
+            _ => return self.error(format!("unknown model field {}", key)),
+        }
+        Ok(())
+    }
+
+    fn graph(&mut self) -> Result<onnx_pb::GraphProto, TextParseError> {
+        let mut graph = onnx_pb::GraphProto {
+            name: self.name()?,
+            ..Default::default()
+        };
+        self.expect('(')?;
+        for (input, initializer) in self.list(')', Self::value_info)? {
+            graph.input.push(input);
+            graph.initializer.extend(initializer);
+        }
+        match self.next()? {
+            Token::Arrow => {}
+            other => {
+                self.pos -= 1;
+                return self.error(format!("expected '=>', found '{}'", other));
+            }
+        }
+        self.expect('(')?;
+        graph.output = self
+            .list(')', Self::value_info)?
+            .into_iter()
+            .map(|(output, _)| output)
+            .collect();
+        //Initializers, and shapes of intermediate tensors
+        if self.eat('<') {
+            for (value_info, initializer) in self.list('>', Self::value_info)? {
+                match initializer {
+                    Some(initializer) => graph.initializer.push(initializer),
+                    None => graph.value_info.push(value_info),
+                }
+            }
+        }
+        self.expect('{')?;
+        while !self.eat('}') {
+            graph.node.push(self.node()?);
+        }
+        Ok(graph)
+    }
+
+    ///`float[N, 128] X`, with an optional initializer value `= {...}`
+    fn value_info(
+        &mut self,
+    ) -> Result<(onnx_pb::ValueInfoProto, Option<onnx_pb::TensorProto>), TextParseError> {
+        let (dt, dims) = self.tensor_type()?;
+        let name = self.name()?;
+        let initializer = if self.eat('=') {
+            Some(self.tensor_value(&name, dt, &dims)?)
+        } else {
+            None
+        };
+        let value_info = onnx_pb::ValueInfoProto {
+            name,
+            r#type: Some(onnx_pb::TypeProto {
+                value: Some(onnx_pb::type_proto::Value::TensorType(
+                    onnx_pb::type_proto::Tensor {
+                        elem_type: dt as i32,
+                        shape: dims.map(|dim| onnx_pb::TensorShapeProto { dim }),
+                    },
+                )),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        Ok((value_info, initializer))
+    }
+
+    ///Element type and dimensions, a type without brackets has an unknown shape
+    fn tensor_type(&mut self) -> Result<(ProtoDType, Option<Vec<Dimension>>), TextParseError> {
+        let type_name = self.name()?;
+        let dt = match elem_type(&type_name) {
+            Some(dt) => dt,
+            None => return self.error(format!("unknown element type {}", type_name)),
+        };
+        if !self.eat('[') {
+            return Ok((dt, None));
+        }
+        let dims = self.list(']', |p| {
+            let value = match p.next()? {
+                Token::Int(i) => Some(DimValue::DimValue(i)),
+                Token::Ident(s) | Token::Str(s) => Some(DimValue::DimParam(s)),
+                Token::Punct('?') => None,
+                other => {
+                    p.pos -= 1;
+                    return p.error(format!("expected a dimension, found '{}'", other));
+                }
+            };
+            Ok(Dimension {
+                value,
+                ..Default::default()
+            })
+        })?;
+        Ok((dt, Some(dims)))
+    }
+
+    ///`{1, 2, 3}` for a tensor of the given type
+    fn tensor_value(
+        &mut self,
+        name: &str,
+        dt: ProtoDType,
+        dims: &Option<Vec<Dimension>>,
+    ) -> Result<onnx_pb::TensorProto, TextParseError> {
+        let mut dim_values = vec![];
+        for dim in dims.iter().flatten() {
+            match dim.value {
+                Some(DimValue::DimValue(d)) => dim_values.push(d),
+                _ => return self.error(format!("initializer {} must have a static shape", name)),
+            }
+        }
+        let mut tensor = onnx_pb::TensorProto {
+            name: name.to_string(),
+            data_type: dt as i32,
+            dims: dim_values,
+            ..Default::default()
+        };
+        self.expect('{')?;
+        let values = self.list('}', |p| p.next())?;
+        //A tensor without dims is a scalar, holding a single value
+        let expected = tensor.dims.iter().product::<i64>();
+        if values.len() as i64 != expected {
+            return self.error(format!(
+                "tensor {} has {} values, but its shape holds {}",
+                name,
+                values.len(),
+                expected
+            ));
+        }
+        for value in values {
+            let as_f64 = match value {
+                Token::Int(i) => Some(i as f64),
+                Token::Float(x) => Some(x),
+                _ => None,
+            };
+            match (dt, value) {
+                (ProtoDType::String, Token::Str(s)) => tensor.string_data.push(s.into_bytes()),
+                (ProtoDType::Int64, Token::Int(i)) => tensor.int64_data.push(i),
+                (ProtoDType::Uint32 | ProtoDType::Uint64, Token::Int(i)) => {
+                    tensor.uint64_data.push(i as u64)
+                }
+                (
+                    ProtoDType::Int32
+                    | ProtoDType::Int16
+                    | ProtoDType::Int8
+                    | ProtoDType::Uint16
+                    | ProtoDType::Uint8,
+                    Token::Int(i),
+                ) => tensor.int32_data.push(i as i32),
+                //8 bit floats are written as their bit patterns
+                (
+                    ProtoDType::Float8e4m3fn
+                    | ProtoDType::Float8e4m3fnuz
+                    | ProtoDType::Float8e5m2
+                    | ProtoDType::Float8e5m2fnuz,
+                    Token::Int(i),
+                ) => tensor.int32_data.push(i as i32),
+                (ProtoDType::Bool, Token::Int(i)) => tensor.int32_data.push((i != 0) as i32),
+                (ProtoDType::Bool, Token::Ident(b)) if b == "true" || b == "false" => {
+                    tensor.int32_data.push((b == "true") as i32)
+                }
+                (ProtoDType::Float, _) if as_f64.is_some() => {
+                    tensor.float_data.push(as_f64.unwrap() as f32)
+                }
+                (ProtoDType::Double, _) if as_f64.is_some() => {
+                    tensor.double_data.push(as_f64.unwrap())
+                }
+                //Half precision values are stored as their bit patterns
+                (ProtoDType::Float16, _) if as_f64.is_some() => tensor
+                    .int32_data
+                    .push(f16::from_f64(as_f64.unwrap()).to_bits() as i32),
+                (ProtoDType::Bfloat16, _) if as_f64.is_some() => tensor
+                    .int32_data
+                    .push(bf16::from_f64(as_f64.unwrap()).to_bits() as i32),
+                (_, value) => {
+                    return self.error(format!("invalid value '{}' for {:?} tensor", value, dt))
+                }
+            }
+        }
+        Ok(tensor)
+    }
+
+    ///`[name] Y1, Y2 = domain.Op <attr = value> (X1, , X3)`
+    fn node(&mut self) -> Result<onnx_pb::NodeProto, TextParseError> {
+        let mut node = onnx_pb::NodeProto::default();
+        if self.eat('[') {
+            node.name = self.name()?;
+            self.expect(']')?;
+        }
+        loop {
+            node.output.push(self.name()?);
+            if !self.eat(',') {
+                break;
+            }
+        }
+        self.expect('=')?;
+        let op = match self.next()? {
+            Token::Ident(op) => op,
+            other => {
+                self.pos -= 1;
+                return self.error(format!("expected an operator, found '{}'", other));
+            }
+        };
+        match op.rsplit_once('.') {
+            Some((domain, op_type)) => {
+                node.domain = domain.to_string();
+                node.op_type = op_type.to_string();
+            }
+            None => node.op_type = op,
+        }
+        if self.eat('<') {
+            node.attribute = self.list('>', Self::attribute)?;
+        }
+        self.expect('(')?;
+        //Omitted optional inputs are left empty
+        if !self.eat(')') {
+            loop {
+                if self.is_punct(',') || self.is_punct(')') {
+                    node.input.push(String::new());
+                } else {
+                    node.input.push(self.name()?);
+                }
+                if self.eat(')') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+        Ok(node)
+    }
+
+    ///`name = value`, the type may be given as in `name: int = 1`
+    fn attribute(&mut self) -> Result<onnx_pb::AttributeProto, TextParseError> {
+        let mut attribute = onnx_pb::AttributeProto {
+            name: self.name()?,
+            ..Default::default()
+        };
+        let declared = if self.eat(':') {
+            Some(self.name()?)
+        } else {
+            None
+        };
+        self.expect('=')?;
+
+        let attribute_type = match (self.peek().cloned(), self.peek_nth(1).cloned()) {
+            (Some(Token::Int(i)), _) => {
+                self.pos += 1;
+                match declared.as_deref() {
+                    Some("float") => {
+                        attribute.f = i as f32;
+                        AttributeType::Float
+                    }
+                    _ => {
+                        attribute.i = i;
+                        AttributeType::Int
+                    }
+                }
+            }
+            (Some(Token::Float(x)), _) => {
+                self.pos += 1;
+                attribute.f = x as f32;
+                AttributeType::Float
+            }
+            (Some(Token::Str(s)), _) => {
+                self.pos += 1;
+                attribute.s = s.into_bytes();
+                AttributeType::String
+            }
+            (Some(Token::Punct('[')), _) => {
+                self.pos += 1;
+                let values = self.list(']', |p| p.next())?;
+                self.list_attribute(&mut attribute, values, declared.as_deref())?
+            }
+            (Some(Token::Ident(type_name)), Some(Token::Punct('[' | '{')))
+                if elem_type(&type_name).is_some() =>
+            {
+                let (dt, dims) = self.tensor_type()?;
+                attribute.t = Some(self.tensor_value("", dt, &dims)?);
+                AttributeType::Tensor
+            }
+            (Some(Token::Ident(_)), Some(Token::Punct('('))) => {
+                attribute.g = Some(self.graph()?);
+                AttributeType::Graph
+            }
+            (Some(token), _) => {
+                return self.error(format!("invalid value '{}' for {}", token, attribute.name))
+            }
+            (None, _) => return self.error("unexpected end of input"),
+        };
+        attribute.r#type = attribute_type as i32;
+        Ok(attribute)
+    }
+
+    fn list_attribute(
+        &self,
+        attribute: &mut onnx_pb::AttributeProto,
+        values: Vec<Token>,
+        declared: Option<&str>,
+    ) -> Result<AttributeType, TextParseError> {
+        let is_float =
+            declared == Some("floats") || values.iter().any(|v| matches!(v, Token::Float(_)));
+        if values.iter().all(|v| matches!(v, Token::Str(_))) && !values.is_empty() {
+            for value in values {
+                if let Token::Str(s) = value {
+                    attribute.strings.push(s.into_bytes());
+                }
+            }
+            return Ok(AttributeType::Strings);
+        }
+        for value in values {
+            match value {
+                Token::Int(i) if is_float => attribute.floats.push(i as f32),
+                Token::Int(i) => attribute.ints.push(i),
+                Token::Float(x) => attribute.floats.push(x as f32),
+                other => {
+                    return self.error(format!(
+                        "invalid list value '{}' for {}",
+                        other, attribute.name
+                    ))
+                }
+            }
+        }
+        Ok(if is_float {
+            AttributeType::Floats
+        } else {
+            AttributeType::Ints
+        })
+    }
+}
+
+///Parses a model written in the ONNX textual syntax, as accepted by `onnx.parser.parse_model`:
+///
+///```text
+///<ir_version: 8, opset_import: ["" : 17]>
+///agraph (float[N, 128] X) => (float[N, 10] Y)
+///   <float[128, 10] W = {...}>
+///{
+///    Y = MatMul(X, W)
+///}
+///```
+///
+///Names which are not valid identifiers, such as `/conv/Conv_output_0`, may be quoted.
+pub fn parse_text(text: &str) -> Result<onnx_pb::ModelProto, TextParseError> {
+    Parser {
+        tokens: tokenize(text)?,
+        pos: 0,
+    }
+    .model()
+}
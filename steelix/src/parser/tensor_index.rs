@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use steelix_onnx::onnx_pb;
+
+///Where a tensor in a graph comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TensorSource {
+    Input(usize),       //index into `graph.input`
+    Initializer(usize), //index into `graph.initializer`
+    Node(usize),        //index into `graph.node`
+}
+
+///Maps every tensor name in a graph to its producer and consumers.
+///Initializers which are also listed as graph inputs are indexed as initializers.
+#[derive(Debug, Clone, Default)]
+pub struct TensorIndex {
+    pub producers: HashMap<String, TensorSource>,
+    pub consumers: HashMap<String, Vec<usize>>, //indices into `graph.node`
+}
+
+impl TensorIndex {
+    pub fn new(graph: &onnx_pb::GraphProto) -> Self {
+        let mut producers = HashMap::new();
+        for (idx, input) in graph.input.iter().enumerate() {
+            producers.insert(input.name.clone(), TensorSource::Input(idx));
+        }
+        for (idx, init) in graph.initializer.iter().enumerate() {
+            producers.insert(init.name.clone(), TensorSource::Initializer(idx));
+        }
+
+        let mut consumers: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, node) in graph.node.iter().enumerate() {
+            for input in node.input.iter().filter(|i| !i.is_empty()) {
+                let entry = consumers.entry(input.clone()).or_default();
+                if entry.last() != Some(&idx) {
+                    entry.push(idx);
+                }
+            }
+            for output in node.output.iter().filter(|o| !o.is_empty()) {
+                producers.insert(output.clone(), TensorSource::Node(idx));
+            }
+        }
+        Self {
+            producers,
+            consumers,
+        }
+    }
+
+    #[inline]
+    pub fn producer(&self, tensor: &str) -> Option<TensorSource> {
+        self.producers.get(tensor).copied()
+    }
+
+    pub fn consumers(&self, tensor: &str) -> &[usize] {
+        self.consumers.get(tensor).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    #[inline]
+    pub fn contains(&self, tensor: &str) -> bool {
+        self.producers.contains_key(tensor) || self.consumers.contains_key(tensor)
+    }
+}
@@ -0,0 +1,521 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    str::FromStr,
+};
+
+use steelix_onnx::onnx_pb::{
+    self,
+    tensor_shape_proto::{dimension::Value as DimValue, Dimension},
+    type_proto,
+};
+
+use smallvec::SmallVec;
+
+use crate::{
+    ir::{ops::data::build_initial, Model, Shape, ValueInfo},
+    parser::{TensorIndex, TensorSource},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum EditError {
+    #[error("No model graph found.")]
+    MissingGraph,
+    #[error("Tensor {0} does not exist in the graph.")]
+    UnknownTensor(String),
+    #[error("{0} is not a graph input.")]
+    NotAnInput(String),
+    #[error("No node named {0} exists in the graph.")]
+    UnknownNode(String),
+    #[error("The name {0} is already in use.")]
+    NameTaken(String),
+    #[error("The type of tensor {0} could not be determined.")]
+    MissingType(String),
+    #[error("Invalid dimension {0}, expected an integer, a symbolic name or ?.")]
+    InvalidDim(String),
+    #[error("The parsed model does not match the ONNX graph.")]
+    ModelMismatch,
+}
+
+///A dimension as provided on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DimSpec {
+    Value(i64),
+    Param(String),
+    Unknown,
+}
+
+impl FromStr for DimSpec {
+    type Err = EditError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "?" {
+            return Ok(DimSpec::Unknown);
+        }
+        if let Ok(value) = s.parse::<i64>() {
+            return if value >= 0 {
+                Ok(DimSpec::Value(value))
+            } else {
+                Err(EditError::InvalidDim(s.to_string()))
+            };
+        }
+        let is_ident = s
+            .chars()
+            .next()
+            .map_or(false, |c| c.is_alphabetic() || c == '_')
+            && s.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if is_ident {
+            Ok(DimSpec::Param(s.to_string()))
+        } else {
+            Err(EditError::InvalidDim(s.to_string()))
+        }
+    }
+}
+
+impl From<&DimSpec> for Dimension {
+    fn from(spec: &DimSpec) -> Self {
+        Dimension {
+            value: match spec {
+                DimSpec::Value(v) => Some(DimValue::DimValue(*v)),
+                DimSpec::Param(p) => Some(DimValue::DimParam(p.clone())),
+                DimSpec::Unknown => None,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+///Parses a comma separated shape such as `N,3,224,224`. An empty string is a scalar.
+pub fn parse_dims(dims: &str) -> Result<Vec<DimSpec>, EditError> {
+    if dims.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    dims.split(',').map(DimSpec::from_str).collect()
+}
+
+fn graph_mut(model: &mut onnx_pb::ModelProto) -> Result<&mut onnx_pb::GraphProto, EditError> {
+    model.graph.as_mut().ok_or(EditError::MissingGraph)
+}
+
+impl DimSpec {
+    ///The dimension of a `ValueInfo`, with its symbolic name if it has one
+    fn dimension(&self) -> (usize, Option<String>) {
+        match self {
+            DimSpec::Value(v) => (*v as usize, None),
+            DimSpec::Param(p) => (1, Some(p.clone())),
+            DimSpec::Unknown => (1, Some("?".to_string())),
+        }
+    }
+}
+
+///Node id of the graph input called `name`
+fn input_id(model: &Model, name: &str) -> Option<usize> {
+    model
+        .inputs
+        .iter()
+        .copied()
+        .find(|&id| model.nodes[id].outputs.first().map(String::as_str) == Some(name))
+}
+
+///Drops the annotations of tensors other than the graph inputs and outputs
+fn drop_intermediate_annotations(model: &mut Model) {
+    let io: HashSet<String> = model
+        .inputs
+        .iter()
+        .flat_map(|&id| model.nodes[id].outputs.iter())
+        .chain(
+            model
+                .outputs
+                .iter()
+                .flat_map(|&id| model.nodes[id].inputs.iter()),
+        )
+        .cloned()
+        .collect();
+    model.value_info.retain(|name, _| io.contains(name));
+}
+
+///Replaces the shape of a graph input.
+///Intermediate annotations are dropped, as they may no longer hold.
+pub fn set_input_shape(model: &mut Model, input: &str, dims: &[DimSpec]) -> Result<(), EditError> {
+    let id = input_id(model, input).ok_or_else(|| EditError::NotAnInput(input.to_string()))?;
+    let dt = model
+        .value_info
+        .get(input)
+        .map(|vi| vi.dt)
+        .ok_or_else(|| EditError::MissingType(input.to_string()))?;
+    let (dimensions, dim_params): (SmallVec<_>, Vec<_>) =
+        dims.iter().map(DimSpec::dimension).unzip();
+    let value_info = ValueInfo {
+        name: input.to_string(),
+        dt,
+        dimensions: Shape(dimensions),
+        dim_params,
+    };
+    model.nodes[id].op = build_initial(value_info.clone()).unwrap();
+    model.value_info.insert(input.to_string(), value_info);
+    drop_intermediate_annotations(model);
+    Ok(())
+}
+
+///Sets the leading dimension of every graph input and output, returning how many were changed.
+///Initializers listed as inputs and scalars are left untouched.
+pub fn set_batch_size(model: &mut Model, batch: &DimSpec) -> Result<usize, EditError> {
+    let (size, param) = batch.dimension();
+    let inputs = model
+        .inputs
+        .iter()
+        .map(|&id| (Some(id), &model.nodes[id].outputs));
+    let outputs = model
+        .outputs
+        .iter()
+        .map(|&id| (None, &model.nodes[id].inputs));
+    let tensors: Vec<(Option<usize>, String)> = inputs
+        .chain(outputs)
+        .filter_map(|(id, names)| Some((id, names.first()?.clone())))
+        .collect();
+
+    let mut changed = 0;
+    for (input, name) in tensors {
+        let value_info = match model.value_info.get_mut(&name) {
+            Some(vi) if !vi.dimensions.is_empty() => vi,
+            _ => continue,
+        };
+        value_info.dimensions[0] = size;
+        value_info.dim_params[0] = param.clone();
+        if let Some(id) = input {
+            model.nodes[id].op = build_initial(value_info.clone()).unwrap();
+        }
+        changed += 1;
+    }
+    drop_intermediate_annotations(model);
+    Ok(changed)
+}
+
+///Renames a tensor, updating every producer, consumer and annotation of it
+pub fn rename_tensor(model: &mut Model, old: &str, new: &str) -> Result<(), EditError> {
+    let in_use = |name: &str| {
+        model.nodes.iter().any(|node| {
+            node.inputs
+                .iter()
+                .chain(node.outputs.iter())
+                .any(|tensor| tensor == name)
+        })
+    };
+    if !in_use(old) {
+        return Err(EditError::UnknownTensor(old.to_string()));
+    }
+    if in_use(new) {
+        return Err(EditError::NameTaken(new.to_string()));
+    }
+
+    for node in model.nodes.iter_mut() {
+        //Inputs, initializers and outputs are named after their tensor
+        let named_after = node.name == old
+            && node.onnx_name.is_empty()
+            && (node.outputs == [old] || node.inputs == [old]);
+        if named_after {
+            node.name = new.to_string();
+        }
+        for tensor in node.inputs.iter_mut().chain(node.outputs.iter_mut()) {
+            if tensor == old {
+                *tensor = new.to_string();
+            }
+        }
+    }
+    if let Some(mut value_info) = model.value_info.remove(old) {
+        value_info.name = new.to_string();
+        model.value_info.insert(new.to_string(), value_info);
+    }
+    Ok(())
+}
+
+///Renames every node called `old`
+pub fn rename_node(model: &mut Model, old: &str, new: &str) -> Result<(), EditError> {
+    if model.nodes.iter().any(|node| node.onnx_name == new) {
+        return Err(EditError::NameTaken(new.to_string()));
+    }
+    let mut found = false;
+    for node in model.nodes.iter_mut().filter(|node| node.onnx_name == old) {
+        node.onnx_name = new.to_string();
+        found = true;
+    }
+    if !found {
+        return Err(EditError::UnknownNode(old.to_string()));
+    }
+    Ok(())
+}
+
+///Renames tensors in every node of the graph, including nested subgraphs which
+///capture them from the outer scope
+fn rename_references(graph: &mut onnx_pb::GraphProto, renames: &HashMap<String, String>) {
+    for node in graph.node.iter_mut() {
+        for name in node.input.iter_mut().chain(node.output.iter_mut()) {
+            if let Some(new) = renames.get(name) {
+                *name = new.clone();
+            }
+        }
+        rename_captures(node, renames);
+    }
+}
+
+fn rename_captures(node: &mut onnx_pb::NodeProto, renames: &HashMap<String, String>) {
+    for attr in node.attribute.iter_mut() {
+        for subgraph in attr.g.iter_mut().chain(attr.graphs.iter_mut()) {
+            rename_references(subgraph, renames);
+        }
+    }
+}
+
+///Sets the name of a tensor from the parsed model, recording the rename for its readers
+fn rename(renames: &mut HashMap<String, String>, name: &mut String, new: &str) {
+    if name != new {
+        renames.insert(name.clone(), new.to_string());
+        *name = new.to_string();
+    }
+}
+
+///Replaces the type of a graph input or output if the parsed model changed its shape
+fn update_type(vi: &mut onnx_pb::ValueInfoProto, model: &Model) {
+    let edited = match model.value_info.get(&vi.name) {
+        Some(edited) => edited,
+        None => return,
+    };
+    let unchanged = ValueInfo::try_from(vi.clone()).map_or(false, |original| {
+        original.dt == edited.dt
+            && original.dimensions == edited.dimensions
+            && original.dim_params == edited.dim_params
+    });
+    if !unchanged {
+        vi.r#type = onnx_pb::ValueInfoProto::from(edited).r#type;
+    }
+}
+
+///Writes the names and the input and output shapes of an edited model back into the ONNX
+///model it was parsed from, which holds the attributes and weights the parsed model does not.
+///
+///Node ids follow the order of the file, see `model_from_proto`, which pairs each node of the
+///parsed model with the graph entry it was built from.
+pub fn write_edits(model: &Model, proto: &mut onnx_pb::ModelProto) -> Result<(), EditError> {
+    let graph = graph_mut(proto)?;
+    let input_names: HashSet<String> = graph.input.iter().map(|vi| vi.name.clone()).collect();
+    let mut seen = HashSet::new();
+    let constants: Vec<usize> = (0..graph.initializer.len())
+        .filter(|&idx| {
+            let name = &graph.initializer[idx].name;
+            !input_names.contains(name) && seen.insert(name.clone())
+        })
+        .collect();
+    let graph_offset = graph.input.len() + constants.len();
+    let output_offset = graph_offset + graph.node.len();
+    if model.nodes.len() != output_offset + graph.output.len() {
+        return Err(EditError::ModelMismatch);
+    }
+
+    //Producers are renamed first, the renames are then applied to everything reading them
+    let mut renames = HashMap::new();
+    for (id, vi) in graph.input.iter_mut().enumerate() {
+        rename(&mut renames, &mut vi.name, &model.nodes[id].outputs[0]);
+        if model.inputs.contains(&id) {
+            update_type(vi, model);
+        }
+    }
+    for (offset, &idx) in constants.iter().enumerate() {
+        let id = graph.input.len() + offset;
+        rename(
+            &mut renames,
+            &mut graph.initializer[idx].name,
+            &model.nodes[id].outputs[0],
+        );
+    }
+    for (offset, node) in graph.node.iter_mut().enumerate() {
+        let parsed = &model.nodes[graph_offset + offset];
+        for (name, new) in node.output.iter_mut().zip(parsed.outputs.iter()) {
+            rename(&mut renames, name, new);
+        }
+        node.input = parsed.inputs.clone();
+        node.name = parsed.onnx_name.clone();
+    }
+    for node in graph.node.iter_mut() {
+        rename_captures(node, &renames);
+    }
+
+    for init in graph.initializer.iter_mut() {
+        if let Some(new) = renames
+            .get(&init.name)
+            .filter(|_| input_names.contains(&init.name))
+        {
+            init.name = new.clone();
+        }
+    }
+    for (offset, vi) in graph.output.iter_mut().enumerate() {
+        vi.name = model.nodes[output_offset + offset].inputs[0].clone();
+        update_type(vi, model);
+    }
+    for vi in graph.value_info.iter_mut() {
+        if let Some(new) = renames.get(&vi.name) {
+            vi.name = new.clone();
+        }
+    }
+    //Annotations dropped from the parsed model no longer hold, unparsed ones are kept
+    graph.value_info.retain(|vi| {
+        model.value_info.contains_key(&vi.name) || ValueInfo::try_from(vi.clone()).is_err()
+    });
+    Ok(())
+}
+
+///Tensors read by the subgraphs of a node, which must be available in the outer scope
+fn captured_tensors(node: &onnx_pb::NodeProto) -> Vec<String> {
+    let mut captured = vec![];
+    for attr in node.attribute.iter() {
+        for subgraph in attr.g.iter().chain(attr.graphs.iter()) {
+            for inner in subgraph.node.iter() {
+                captured.extend(inner.input.iter().filter(|i| !i.is_empty()).cloned());
+                captured.extend(captured_tensors(inner));
+            }
+        }
+    }
+    captured
+}
+
+///Finds the type of a tensor from its annotations, initializer or the inferred shapes
+fn find_value_info(
+    graph: &onnx_pb::GraphProto,
+    name: &str,
+    inferred: &HashMap<String, ValueInfo>,
+) -> Result<onnx_pb::ValueInfoProto, EditError> {
+    if let Some(vi) = graph
+        .input
+        .iter()
+        .chain(graph.output.iter())
+        .chain(graph.value_info.iter())
+        .find(|vi| vi.name == name && vi.r#type.is_some())
+    {
+        return Ok(vi.clone());
+    }
+    if let Some(init) = graph.initializer.iter().find(|init| init.name == name) {
+        let dims = init
+            .dims
+            .iter()
+            .map(|&d| DimSpec::Value(d))
+            .collect::<Vec<_>>();
+        return Ok(onnx_pb::ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(onnx_pb::TypeProto {
+                value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                    elem_type: init.data_type,
+                    shape: Some(onnx_pb::TensorShapeProto {
+                        dim: dims.iter().map(Dimension::from).collect(),
+                    }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+    }
+    inferred
+        .get(name)
+        .map(onnx_pb::ValueInfoProto::from)
+        .ok_or_else(|| EditError::MissingType(name.to_string()))
+}
+
+///Extracts the nodes required to compute `outputs` from `inputs` into a new model, as
+///`onnx.utils.extract_model` does.
+///
+///Original graph inputs which are still required are kept as inputs of the new model.
+///Types of the new inputs and outputs come from the graph annotations, or else `inferred`.
+pub fn extract_subgraph(
+    model: &onnx_pb::ModelProto,
+    inputs: &[String],
+    outputs: &[String],
+    inferred: &HashMap<String, ValueInfo>,
+) -> Result<onnx_pb::ModelProto, EditError> {
+    let graph = model.graph.as_ref().ok_or(EditError::MissingGraph)?;
+    let index = TensorIndex::new(graph);
+    for name in inputs.iter() {
+        if !index.contains(name) {
+            return Err(EditError::UnknownTensor(name.clone()));
+        }
+    }
+
+    let boundary: HashSet<&str> = inputs.iter().map(String::as_str).collect();
+    let mut visited = HashSet::new();
+    let mut nodes = BTreeSet::new();
+    let mut initializers = BTreeSet::new();
+    let mut graph_inputs = BTreeSet::new();
+    let mut stack = outputs.to_vec();
+    while let Some(tensor) = stack.pop() {
+        if boundary.contains(&*tensor) || !visited.insert(tensor.clone()) {
+            continue;
+        }
+        match index.producer(&tensor) {
+            Some(TensorSource::Node(idx)) => {
+                if nodes.insert(idx) {
+                    let node = &graph.node[idx];
+                    stack.extend(node.input.iter().filter(|i| !i.is_empty()).cloned());
+                    //Names local to the subgraph are not indexed in the outer graph
+                    stack.extend(
+                        captured_tensors(node)
+                            .into_iter()
+                            .filter(|t| index.producer(t).is_some()),
+                    );
+                }
+            }
+            Some(TensorSource::Initializer(idx)) => {
+                initializers.insert(idx);
+            }
+            Some(TensorSource::Input(idx)) => {
+                graph_inputs.insert(idx);
+            }
+            None => return Err(EditError::UnknownTensor(tensor)),
+        }
+    }
+
+    let mut new_inputs = inputs
+        .iter()
+        .map(|name| find_value_info(graph, name, inferred))
+        .collect::<Result<Vec<_>, _>>()?;
+    new_inputs.extend(graph_inputs.into_iter().map(|idx| graph.input[idx].clone()));
+    let new_outputs = outputs
+        .iter()
+        .map(|name| find_value_info(graph, name, inferred))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let kept: HashSet<&str> = nodes
+        .iter()
+        .flat_map(|&idx| graph.node[idx].output.iter().map(String::as_str))
+        .filter(|name| !boundary.contains(name) && !outputs.iter().any(|o| o == name))
+        .collect();
+
+    let new_graph = onnx_pb::GraphProto {
+        name: graph.name.clone(),
+        doc_string: graph.doc_string.clone(),
+        node: nodes.iter().map(|&idx| graph.node[idx].clone()).collect(),
+        initializer: initializers
+            .iter()
+            .map(|&idx| graph.initializer[idx].clone())
+            .collect(),
+        input: new_inputs,
+        output: new_outputs,
+        value_info: graph
+            .value_info
+            .iter()
+            .filter(|vi| kept.contains(&*vi.name))
+            .cloned()
+            .collect(),
+        ..Default::default()
+    };
+
+    //Training info refers to tensors of the original graph, so is left out
+    Ok(onnx_pb::ModelProto {
+        ir_version: model.ir_version,
+        opset_import: model.opset_import.clone(),
+        producer_name: model.producer_name.clone(),
+        producer_version: model.producer_version.clone(),
+        domain: model.domain.clone(),
+        model_version: model.model_version,
+        doc_string: model.doc_string.clone(),
+        graph: Some(new_graph),
+        metadata_props: model.metadata_props.clone(),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+
+use prost::{
+    bytes::Buf,
+    encoding::{self, DecodeContext, WireType},
+    Message,
+};
+use steelix_onnx::onnx_pb;
+
+use crate::ir::ModelSummary;
+
+const MODEL_GRAPH_TAG: u32 = 7;
+const GRAPH_VALUE_INFO_TAG: u32 = 13;
+
+///Annotates every intermediate tensor of the model with its inferred shape and dtype.
+///
+///The original bytes are preserved exactly, the new `value_info` entries are appended to the
+///end of the serialized graph. Tensors which are already annotated, or are graph inputs, outputs
+///or initializers, are left untouched. Returns the number of tensors annotated.
+pub fn write_inferred_shapes(
+    original: &[u8],
+    summary: &ModelSummary,
+    output: &mut Vec<u8>,
+) -> anyhow::Result<usize> {
+    let pb_model = onnx_pb::ModelProto::decode(original)?;
+    let graph = pb_model
+        .graph
+        .ok_or_else(|| anyhow::anyhow!("No model graph found."))?;
+
+    let known: HashSet<&str> = graph
+        .value_info
+        .iter()
+        .chain(graph.input.iter())
+        .chain(graph.output.iter())
+        .map(|vi| &*vi.name)
+        .chain(graph.initializer.iter().map(|t| &*t.name))
+        .collect();
+
+    let mut entries: Vec<onnx_pb::ValueInfoProto> = summary
+        .inferred_tensors
+        .iter()
+        .filter(|(name, _)| !name.is_empty() && !known.contains(name.as_str()))
+        .map(|(_, vi)| vi.into())
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut buf = original;
+    while buf.has_remaining() {
+        let field_start = original.len() - buf.remaining();
+        let (tag, wire_type) = encoding::decode_key(&mut buf)?;
+        if tag == MODEL_GRAPH_TAG && wire_type == WireType::LengthDelimited {
+            let len = encoding::decode_varint(&mut buf)? as usize;
+            anyhow::ensure!(
+                len <= buf.len(),
+                "Model graph is {} bytes, but only {} remain.",
+                len,
+                buf.len()
+            );
+            let mut graph_bytes = buf[..len].to_vec();
+            for entry in entries.iter() {
+                encoding::message::encode(GRAPH_VALUE_INFO_TAG, entry, &mut graph_bytes);
+            }
+            encoding::encode_key(MODEL_GRAPH_TAG, WireType::LengthDelimited, output);
+            encoding::encode_varint(graph_bytes.len() as u64, output);
+            output.extend_from_slice(&graph_bytes);
+            buf.advance(len);
+        } else {
+            encoding::skip_field(wire_type, tag, &mut buf, DecodeContext::default())?;
+            output.extend_from_slice(&original[field_start..original.len() - buf.remaining()]);
+        }
+    }
+    Ok(entries.len())
+}
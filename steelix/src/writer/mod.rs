@@ -0,0 +1,7 @@
+mod edit;
+mod inferred_shapes;
+mod onnx_text;
+
+pub use edit::*;
+pub use inferred_shapes::*;
+pub use onnx_text::*;
@@ -0,0 +1,247 @@
+use std::fmt::Write;
+
+use steelix_onnx::onnx_pb::{
+    self, attribute_proto::AttributeType, tensor_shape_proto::dimension::Value as DimValue,
+};
+
+use crate::ir::text::{
+    elem_type_name, name, plain_attribute_value, quote, tensor_type, tensor_values,
+    MAX_PRINTED_ELEMENTS,
+};
+
+const INDENT: &str = "   ";
+
+#[derive(thiserror::Error, Debug)]
+pub enum TextWriteError {
+    #[error("{0} has no type.")]
+    MissingType(String),
+    #[error("{name} has a {kind} type, only tensor types can be printed.")]
+    NotATensor { name: String, kind: &'static str },
+    #[error("Attribute {attribute} of node {node} has a type with no textual form.")]
+    UnsupportedAttribute { node: String, attribute: String },
+    #[error("Values of tensor {0} can't be printed, they are stored externally or in an unsupported type.")]
+    MissingValues(String),
+}
+
+fn value_type(vi: &onnx_pb::ValueInfoProto) -> Result<String, TextWriteError> {
+    use onnx_pb::type_proto::Value;
+    let not_a_tensor = |kind| TextWriteError::NotATensor {
+        name: vi.name.clone(),
+        kind,
+    };
+    let tensor = match vi.r#type.as_ref().and_then(|t| t.value.as_ref()) {
+        Some(Value::TensorType(tensor)) => tensor,
+        Some(Value::SequenceType(_)) => return Err(not_a_tensor("sequence")),
+        Some(Value::MapType(_)) => return Err(not_a_tensor("map")),
+        Some(Value::OptionalType(_)) => return Err(not_a_tensor("optional")),
+        Some(Value::SparseTensorType(_)) => return Err(not_a_tensor("sparse tensor")),
+        None => return Err(TextWriteError::MissingType(vi.name.clone())),
+    };
+    let mut s = elem_type_name(tensor.elem_type);
+    if let Some(shape) = &tensor.shape {
+        let dims: Vec<String> = shape
+            .dim
+            .iter()
+            .map(|d| match &d.value {
+                Some(DimValue::DimValue(v)) => v.to_string(),
+                Some(DimValue::DimParam(p)) => name(p),
+                None => "?".to_string(),
+            })
+            .collect();
+        write!(s, "[{}]", dims.join(", ")).unwrap();
+    }
+    Ok(s)
+}
+
+fn attribute(
+    node: &str,
+    attr: &onnx_pb::AttributeProto,
+    depth: usize,
+    full: bool,
+) -> Result<String, TextWriteError> {
+    let value = attribute_value(node, attr, depth, full)?;
+    Ok(format!("{} = {}", name(&attr.name), value))
+}
+
+///Value of an attribute in the textual syntax, an error for types it can't express such as
+///sparse tensors
+fn attribute_value(
+    node: &str,
+    attr: &onnx_pb::AttributeProto,
+    depth: usize,
+    full: bool,
+) -> Result<String, TextWriteError> {
+    let unsupported = || TextWriteError::UnsupportedAttribute {
+        node: node.to_string(),
+        attribute: attr.name.clone(),
+    };
+    let value = match AttributeType::from_i32(attr.r#type) {
+        Some(AttributeType::Tensor) => {
+            let t = attr.t.as_ref().ok_or_else(unsupported)?;
+            let values =
+                tensor_values(t).ok_or_else(|| TextWriteError::MissingValues(t.name.clone()))?;
+            format!("{} {{{}}}", tensor_type(t), values.join(", "))
+        }
+        Some(AttributeType::Graph) => {
+            let mut s = String::new();
+            write_graph(
+                &mut s,
+                attr.g.as_ref().ok_or_else(unsupported)?,
+                depth + 1,
+                full,
+            )?;
+            s.trim_end().to_string()
+        }
+        _ => plain_attribute_value(attr).ok_or_else(unsupported)?,
+    };
+    Ok(value)
+}
+
+fn write_graph(
+    out: &mut String,
+    graph: &onnx_pb::GraphProto,
+    depth: usize,
+    full: bool,
+) -> Result<(), TextWriteError> {
+    let indent = INDENT.repeat(depth);
+    let initializers: std::collections::HashMap<&str, &onnx_pb::TensorProto> =
+        graph.initializer.iter().map(|t| (&*t.name, t)).collect();
+    let initializer = |t: &onnx_pb::TensorProto| -> Result<String, TextWriteError> {
+        match tensor_values(t) {
+            Some(values) if full || values.len() <= MAX_PRINTED_ELEMENTS => Ok(format!(
+                "{} {} = {{{}}}",
+                tensor_type(t),
+                name(&t.name),
+                values.join(", ")
+            )),
+            _ if full => Err(TextWriteError::MissingValues(t.name.clone())),
+            //Without its values the initializer is only declared, and parses as value_info
+            _ => Ok(format!("{} {}", tensor_type(t), name(&t.name))),
+        }
+    };
+
+    let inputs: Vec<String> = graph
+        .input
+        .iter()
+        .map(|i| match initializers.get(&*i.name) {
+            Some(t) => initializer(t),
+            None => Ok(format!("{} {}", value_type(i)?, name(&i.name))),
+        })
+        .collect::<Result<_, _>>()?;
+    let outputs: Vec<String> = graph
+        .output
+        .iter()
+        .map(|o| Ok(format!("{} {}", value_type(o)?, name(&o.name))))
+        .collect::<Result<_, TextWriteError>>()?;
+    writeln!(
+        out,
+        "{} ({}) => ({})",
+        name(&graph.name),
+        inputs.join(", "),
+        outputs.join(", ")
+    )
+    .unwrap();
+
+    let declarations: Vec<String> = graph
+        .initializer
+        .iter()
+        .filter(|t| !graph.input.iter().any(|i| i.name == t.name))
+        .map(initializer)
+        .chain(
+            graph
+                .value_info
+                .iter()
+                .map(|vi| Ok(format!("{} {}", value_type(vi)?, name(&vi.name)))),
+        )
+        .collect::<Result<_, _>>()?;
+    if !declarations.is_empty() {
+        let separator = format!(",\n{}{}", indent, INDENT);
+        writeln!(
+            out,
+            "{}{}<{}>",
+            indent,
+            INDENT,
+            declarations.join(&separator)
+        )
+        .unwrap();
+    }
+
+    writeln!(out, "{}{{", indent).unwrap();
+    for node in graph.node.iter() {
+        write!(out, "{}{}", indent, INDENT).unwrap();
+        if !node.name.is_empty() {
+            write!(out, "[{}] ", name(&node.name)).unwrap();
+        }
+        let outputs: Vec<String> = node.output.iter().map(|o| name(o)).collect();
+        let op = if node.domain.is_empty() || node.domain == "ai.onnx" {
+            node.op_type.clone()
+        } else {
+            format!("{}.{}", node.domain, node.op_type)
+        };
+        write!(out, "{} = {}", outputs.join(", "), op).unwrap();
+        if !node.attribute.is_empty() {
+            let node_name = if node.name.is_empty() {
+                &node.op_type
+            } else {
+                &node.name
+            };
+            let attributes: Vec<String> = node
+                .attribute
+                .iter()
+                .map(|a| attribute(node_name, a, depth + 1, full))
+                .collect::<Result<_, _>>()?;
+            write!(out, " <{}>", attributes.join(", ")).unwrap();
+        }
+        let inputs: Vec<String> = node
+            .input
+            .iter()
+            .map(|i| if i.is_empty() { String::new() } else { name(i) })
+            .collect();
+        writeln!(out, " ({})", inputs.join(", ")).unwrap();
+    }
+    writeln!(out, "{}}}", indent).unwrap();
+    Ok(())
+}
+
+///Prints the model in the ONNX textual syntax, which `parse_text` reads back.
+///
+///Initializers with more than `MAX_PRINTED_ELEMENTS` values, or stored externally, are printed
+///as declarations without values unless `full` is set. In full, and for anything the syntax
+///can't express, such as sequence types, an error is returned rather than a different model.
+pub fn to_text(model: &onnx_pb::ModelProto, full: bool) -> Result<String, TextWriteError> {
+    let mut out = String::new();
+    let mut header = vec![format!("ir_version: {}", model.ir_version)];
+    if !model.opset_import.is_empty() {
+        let opsets: Vec<String> = model
+            .opset_import
+            .iter()
+            .map(|o| format!("{} : {}", quote(&o.domain), o.version))
+            .collect();
+        header.push(format!("opset_import: [{}]", opsets.join(", ")));
+    }
+    for (key, value) in [
+        ("producer_name", &model.producer_name),
+        ("producer_version", &model.producer_version),
+        ("domain", &model.domain),
+        ("doc_string", &model.doc_string),
+    ] {
+        if !value.is_empty() {
+            header.push(format!("{}: {}", key, quote(value)));
+        }
+    }
+    if model.model_version != 0 {
+        header.push(format!("model_version: {}", model.model_version));
+    }
+    writeln!(
+        out,
+        "<\n{}{}\n>",
+        INDENT,
+        header.join(&format!(",\n{}", INDENT))
+    )
+    .unwrap();
+
+    if let Some(graph) = &model.graph {
+        write_graph(&mut out, graph, 0, full)?;
+    }
+    Ok(out)
+}
@@ -0,0 +1,53 @@
+use steelix::{critical_path, prelude::*, PathWeight};
+use steelix_onnx::onnx_pb::{self, tensor_proto::DataType as ProtoDType};
+
+use crate::{analyze, mlp_proto, node, node_id, value_info};
+
+///`y = MatMul(x, w) + Relu(x)`
+fn diamond_proto() -> onnx_pb::ModelProto {
+    onnx_pb::ModelProto {
+        ir_version: 8,
+        graph: Some(onnx_pb::GraphProto {
+            node: vec![
+                node("MatMul", &["x", "w"], "a"),
+                node("Relu", &["x"], "b"),
+                node("Add", &["a", "b"], "y"),
+            ],
+            initializer: vec![onnx_pb::TensorProto {
+                name: "w".to_string(),
+                data_type: ProtoDType::Float as i32,
+                dims: vec![32, 32],
+                float_data: vec![0.; 32 * 32],
+                ..Default::default()
+            }],
+            input: vec![value_info("x", shape![1, 32])],
+            output: vec![value_info("y", shape![1, 32])],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_diamond() {
+    let (model, summary) = analyze(&diamond_proto());
+    let cp = critical_path(&model, &summary, &PathWeight::Flops).unwrap();
+
+    assert_eq!(cp.path, vec![node_id(&model, "a"), node_id(&model, "y")]);
+    assert_eq!(cp.depth(), 2);
+    assert_eq!(cp.level_widths, vec![2, 1]);
+    assert_eq!(cp.max_width(), 2);
+    assert!(cp.speedup() > 1.);
+    assert!(cp.total_cost > cp.path_cost);
+}
+
+#[test]
+fn test_chain_has_no_speedup() {
+    let (model, summary) = analyze(&mlp_proto(3, 8));
+    let cp = critical_path(&model, &summary, &PathWeight::Flops).unwrap();
+
+    assert_eq!(cp.path.len(), 6);
+    assert_eq!(cp.depth(), 6);
+    assert_eq!(cp.max_width(), 1);
+    assert_eq!(cp.speedup(), 1.);
+}
@@ -0,0 +1,88 @@
+use steelix::{fingerprint, graph_stats, structural_hash};
+use steelix_onnx::onnx_pb::{attribute_proto::AttributeType, AttributeProto};
+
+use crate::{analyze, mlp_proto};
+
+fn attribute(name: &str, value: i64) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::Int as i32,
+        i: value,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_hash_ignores_weights_and_names() {
+    let (model, summary) = analyze(&mlp_proto(2, 8));
+    let expected = structural_hash(&model, &summary);
+
+    let mut proto = mlp_proto(2, 8);
+    let graph = proto.graph.as_mut().unwrap();
+    for initializer in graph.initializer.iter_mut() {
+        initializer.float_data.iter_mut().for_each(|w| *w = 1.);
+    }
+    let rename = |name: &mut String| *name = format!("renamed_{}", name);
+    for initializer in graph.initializer.iter_mut() {
+        rename(&mut initializer.name);
+    }
+    for value_info in graph.input.iter_mut().chain(graph.output.iter_mut()) {
+        rename(&mut value_info.name);
+    }
+    for (idx, node) in graph.node.iter_mut().enumerate() {
+        node.name = format!("renamed{}", idx);
+        node.input
+            .iter_mut()
+            .chain(node.output.iter_mut())
+            .for_each(rename);
+    }
+    graph.node.reverse();
+    let (model, summary) = analyze(&proto);
+
+    assert_eq!(structural_hash(&model, &summary), expected);
+}
+
+#[test]
+fn test_hash_includes_attributes() {
+    let with_attributes = |attributes: &[(&str, i64)]| {
+        let mut proto = mlp_proto(2, 8);
+        let node = &mut proto.graph.as_mut().unwrap().node[1];
+        node.attribute = attributes.iter().map(|&(n, v)| attribute(n, v)).collect();
+        let (model, summary) = analyze(&proto);
+        structural_hash(&model, &summary)
+    };
+    let expected = with_attributes(&[("a", 1), ("b", 2)]);
+
+    assert_eq!(with_attributes(&[("b", 2), ("a", 1)]), expected);
+    assert_ne!(with_attributes(&[("a", 1), ("b", 3)]), expected);
+    assert_ne!(with_attributes(&[]), expected);
+}
+
+#[test]
+fn test_hash_distinguishes_structure() {
+    let (model, summary) = analyze(&mlp_proto(2, 8));
+    let two_layers = structural_hash(&model, &summary);
+    let (model, summary) = analyze(&mlp_proto(3, 8));
+    let three_layers = structural_hash(&model, &summary);
+    let (model, summary) = analyze(&mlp_proto(2, 16));
+    let wider = structural_hash(&model, &summary);
+
+    assert_ne!(two_layers, three_layers);
+    assert_ne!(two_layers, wider);
+}
+
+#[test]
+fn test_matches_are_ranked() {
+    let (model, summary) = analyze(&mlp_proto(2, 8));
+    let fingerprint = fingerprint(&model, &summary, &graph_stats(&model, &[]).unwrap());
+
+    assert!(!fingerprint.matches.is_empty());
+    assert!(fingerprint
+        .matches
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+    assert!(fingerprint
+        .matches
+        .iter()
+        .all(|m| (0. ..=1.).contains(&m.score)));
+}
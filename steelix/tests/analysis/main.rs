@@ -0,0 +1,113 @@
+mod critical_path;
+mod fingerprint;
+mod pipeline;
+mod region;
+mod repeats;
+mod stats;
+mod tensor_parallel;
+
+use prost::Message;
+use steelix::{parse_model, prelude::*, Model, ModelSummary, ValueInfo};
+use steelix_onnx::onnx_pb::{self, tensor_proto::DataType as ProtoDType};
+
+pub fn value_info(name: &str, dimensions: Shape) -> onnx_pb::ValueInfoProto {
+    (&ValueInfo::new(name, DType::F32, dimensions)).into()
+}
+
+///Node named after its single output
+pub fn node(op_type: &str, inputs: &[&str], output: &str) -> onnx_pb::NodeProto {
+    onnx_pb::NodeProto {
+        op_type: op_type.to_string(),
+        name: output.to_string(),
+        input: inputs.iter().map(|s| s.to_string()).collect(),
+        output: vec![output.to_string()],
+        ..Default::default()
+    }
+}
+
+///`x -> (MatMul -> Relu) * layers -> y`, with [1, width] activations
+pub fn mlp_proto(layers: usize, width: usize) -> onnx_pb::ModelProto {
+    let mut graph = onnx_pb::GraphProto {
+        input: vec![value_info("x", shape![1, width])],
+        ..Default::default()
+    };
+    let mut prev = "x".to_string();
+    for layer in 0..layers {
+        let (weight, hidden) = (format!("w{}", layer), format!("h{}", layer));
+        let out = if layer + 1 == layers {
+            "y".to_string()
+        } else {
+            format!("a{}", layer)
+        };
+        graph.initializer.push(onnx_pb::TensorProto {
+            name: weight.clone(),
+            data_type: ProtoDType::Float as i32,
+            dims: vec![width as i64, width as i64],
+            float_data: vec![0.; width * width],
+            ..Default::default()
+        });
+        graph.node.push(onnx_pb::NodeProto {
+            op_type: "MatMul".to_string(),
+            name: format!("matmul{}", layer),
+            input: vec![prev.clone(), weight],
+            output: vec![hidden.clone()],
+            ..Default::default()
+        });
+        graph.node.push(onnx_pb::NodeProto {
+            op_type: "Relu".to_string(),
+            name: format!("relu{}", layer),
+            input: vec![hidden],
+            output: vec![out.clone()],
+            ..Default::default()
+        });
+        prev = out;
+    }
+    graph.output.push(value_info("y", shape![1, width]));
+    onnx_pb::ModelProto {
+        ir_version: 8,
+        graph: Some(graph),
+        ..Default::default()
+    }
+}
+
+///`y = Relu(Relu(a)) + a`, with `a = Relu(x)`
+pub fn residual_proto() -> onnx_pb::ModelProto {
+    onnx_pb::ModelProto {
+        ir_version: 8,
+        graph: Some(onnx_pb::GraphProto {
+            node: vec![
+                node("Relu", &["x"], "a"),
+                node("Relu", &["a"], "b"),
+                node("Relu", &["b"], "c"),
+                node("Add", &["c", "a"], "y"),
+            ],
+            input: vec![value_info("x", shape![1, 8])],
+            output: vec![value_info("y", shape![1, 8])],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+///Parses the proto from disk, without running it
+pub fn parse(proto: &onnx_pb::ModelProto) -> Model {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("model.onnx");
+    std::fs::write(&path, proto.encode_to_vec()).unwrap();
+    parse_model(&path).unwrap().build_traversal_order()
+}
+
+///Parses the proto and propagates shapes through it
+pub fn analyze(proto: &onnx_pb::ModelProto) -> (Model, ModelSummary) {
+    let mut model = parse(proto);
+    let summary = model.run().unwrap();
+    (model, summary)
+}
+
+pub fn node_id(model: &Model, onnx_name: &str) -> usize {
+    model
+        .nodes
+        .iter()
+        .position(|n| n.onnx_name == onnx_name)
+        .unwrap()
+}
@@ -0,0 +1,64 @@
+use steelix::{partition_pipeline, BalanceBy, PartitionError};
+
+use crate::{analyze, mlp_proto, node_id, residual_proto};
+
+#[test]
+fn test_balanced_by_flops() {
+    let (model, summary) = analyze(&mlp_proto(2, 64));
+    let plan = partition_pipeline(&model, &summary, 2, BalanceBy::Flops, &[]).unwrap();
+
+    let first = &plan.stages[0];
+    assert_eq!(
+        first.nodes,
+        vec![node_id(&model, "matmul0"), node_id(&model, "relu0")]
+    );
+    assert_eq!(first.flops, plan.stages[1].flops);
+    assert_eq!(first.param_bytes, 64 * 64 * 4);
+    assert_eq!(first.outputs, vec!["a0"]);
+    assert_eq!(plan.stages[1].inputs, vec!["a0"]);
+    assert_eq!(plan.stages[1].outputs, vec!["y"]);
+
+    assert_eq!(plan.cuts.len(), 1);
+    assert_eq!(plan.cuts[0].tensors, vec![("a0".to_string(), 64 * 4)]);
+}
+
+#[test]
+fn test_balanced_by_param_bytes() {
+    let (model, summary) = analyze(&mlp_proto(4, 16));
+    let plan = partition_pipeline(&model, &summary, 4, BalanceBy::ParamBytes, &[]).unwrap();
+    assert!(plan
+        .stages
+        .iter()
+        .all(|stage| stage.param_bytes == 16 * 16 * 4));
+}
+
+#[test]
+fn test_skipped_stages_forward_tensors() {
+    let (model, summary) = analyze(&residual_proto());
+    let plan = partition_pipeline(&model, &summary, 4, BalanceBy::Flops, &[]).unwrap();
+
+    //`a` is read by the last stage, so the two stages in between pass it on
+    assert_eq!(plan.stages[0].outputs, vec!["a"]);
+    for stage in plan.stages[1..3].iter() {
+        assert!(stage.inputs.contains(&"a".to_string()));
+        assert!(stage.outputs.contains(&"a".to_string()));
+    }
+    assert_eq!(plan.stages[3].inputs, vec!["c", "a"]);
+    assert!(plan
+        .cuts
+        .iter()
+        .all(|cut| cut.tensors.iter().any(|(name, _)| name == "a")));
+}
+
+#[test]
+fn test_invalid_partitions() {
+    let (model, summary) = analyze(&mlp_proto(1, 8));
+    assert!(matches!(
+        partition_pipeline(&model, &summary, 3, BalanceBy::Flops, &[]),
+        Err(PartitionError::TooFewNodes { .. })
+    ));
+    assert!(matches!(
+        partition_pipeline(&model, &summary, 2, BalanceBy::Latency, &[]),
+        Err(PartitionError::MissingDevice)
+    ));
+}
@@ -0,0 +1,63 @@
+use steelix::{select_region, Model, Region, RegionError};
+
+use crate::{mlp_proto, node_id, parse};
+
+fn names(model: &Model, ids: &[usize]) -> Vec<String> {
+    ids.iter()
+        .map(|&id| model.nodes[id].onnx_name.clone())
+        .collect()
+}
+
+fn range(from: &[&str], to: &[&str]) -> Region {
+    Region::Range {
+        from: from.iter().map(|s| s.to_string()).collect(),
+        to: to.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_range() {
+    let model = parse(&mlp_proto(4, 8));
+    let ids = select_region(&model, &range(&["matmul1"], &["relu2"])).unwrap();
+    assert_eq!(
+        names(&model, &ids),
+        vec!["matmul1", "relu1", "matmul2", "relu2"]
+    );
+    //Tensors bound the range at their consumers and producer
+    let ids = select_region(&model, &range(&["a0"], &["h2"])).unwrap();
+    assert_eq!(names(&model, &ids), vec!["matmul1", "relu1", "matmul2"]);
+    //An open end runs to the end of the model
+    let ids = select_region(&model, &range(&["relu3"], &[])).unwrap();
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&node_id(&model, "relu3")));
+}
+
+#[test]
+fn test_around() {
+    let model = parse(&mlp_proto(4, 8));
+    let around = |depth| Region::Around {
+        center: "relu1".to_string(),
+        depth,
+    };
+    let ids = select_region(&model, &around(1)).unwrap();
+    assert_eq!(names(&model, &ids), vec!["matmul1", "relu1", "matmul2"]);
+    //Weights are skipped over rather than counted as a step
+    let ids = select_region(&model, &around(2)).unwrap();
+    assert_eq!(
+        names(&model, &ids),
+        vec!["relu0", "matmul1", "relu1", "matmul2", "relu2"]
+    );
+}
+
+#[test]
+fn test_invalid_regions() {
+    let model = parse(&mlp_proto(4, 8));
+    assert!(matches!(
+        select_region(&model, &range(&["missing"], &[])),
+        Err(RegionError::UnknownName(name)) if name == "missing"
+    ));
+    assert!(matches!(
+        select_region(&model, &range(&["relu2"], &["matmul1"])),
+        Err(RegionError::Disconnected { .. })
+    ));
+}
@@ -0,0 +1,57 @@
+use steelix::{prelude::*, repeated_subgraphs, repeats_table, ModelBuilder};
+
+use crate::{analyze, mlp_proto, node_id, value_info};
+
+#[test]
+fn test_repeated_layers() {
+    let (model, summary) = analyze(&mlp_proto(4, 8));
+    let blocks = repeated_subgraphs(&model, &summary).unwrap();
+    assert_eq!(blocks.len(), 1);
+    let block = &blocks[0];
+    assert_eq!(block.count(), 4);
+    assert_eq!(block.ops(&model), vec!["MatMul", "Relu"]);
+    assert_eq!(
+        block.instances[2],
+        vec![node_id(&model, "matmul2"), node_id(&model, "relu2")]
+    );
+    //Each layer costs a quarter of the model
+    assert_eq!(block.cost(&model, &summary).flops * 4, summary.total_flops);
+    assert_eq!(
+        block.cost(&model, &summary).parameters * 4,
+        summary.total_params
+    );
+
+    let table = repeats_table(&model, &summary, &blocks).to_string();
+    assert!(table.contains("MatMul → Relu"));
+    assert!(table.contains("8 nodes in 1 repeated"));
+}
+
+#[test]
+fn test_different_weights_do_not_repeat() {
+    //The second layer narrows to 4 features, so its MatMul differs from the first
+    let mut proto = mlp_proto(2, 8);
+    let graph = proto.graph.as_mut().unwrap();
+    graph.initializer[1].dims = vec![8, 4];
+    graph.initializer[1].float_data = vec![0.; 32];
+    graph.output[0] = value_info("y", shape![1, 4]);
+    let (model, summary) = analyze(&proto);
+    assert!(repeated_subgraphs(&model, &summary).unwrap().is_empty());
+}
+
+#[test]
+fn test_external_inputs_must_pair_up() {
+    //The first block reads `z` twice, the second reads `z` and then `x`
+    let mut model = ModelBuilder::new()
+        .input("x", DType::F32, shape![1, 8])
+        .input("z", DType::F32, shape![1, 8])
+        .node("Add", &["x", "z"], &["s1"])
+        .node("Add", &["s1", "z"], &["t1"])
+        .node("Add", &["t1", "z"], &["s2"])
+        .node("Add", &["s2", "x"], &["t2"])
+        .output("t2")
+        .build()
+        .unwrap()
+        .build_traversal_order();
+    let summary = model.run().unwrap();
+    assert!(repeated_subgraphs(&model, &summary).unwrap().is_empty());
+}
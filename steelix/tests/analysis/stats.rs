@@ -0,0 +1,50 @@
+use steelix::{graph_stats, repeated_subgraphs, ResidualConnection};
+
+use crate::{analyze, mlp_proto, node_id, residual_proto};
+
+#[test]
+fn test_residual_connection() {
+    let (model, _) = analyze(&residual_proto());
+    let stats = graph_stats(&model, &[]).unwrap();
+
+    assert_eq!(stats.nodes, 4);
+    assert_eq!(stats.edges, 6);
+    assert_eq!(stats.depth, 4);
+    assert_eq!(stats.branch_points, vec![node_id(&model, "a")]);
+    assert_eq!(stats.merge_points, vec![node_id(&model, "y")]);
+    assert_eq!(stats.max_fan_in, Some((node_id(&model, "y"), 2)));
+    assert_eq!(stats.max_fan_out, Some((node_id(&model, "a"), 2)));
+    assert_eq!(
+        stats.residuals,
+        vec![ResidualConnection {
+            from: node_id(&model, "a"),
+            to: node_id(&model, "y"),
+            span: 2,
+        }]
+    );
+}
+
+#[test]
+fn test_repeated_block() {
+    let (model, summary) = analyze(&mlp_proto(3, 8));
+    let repeats = repeated_subgraphs(&model, &summary).unwrap();
+    let stats = graph_stats(&model, &repeats).unwrap();
+
+    assert_eq!(stats.nodes, 6);
+    assert_eq!(stats.constants, 3);
+    assert_eq!(stats.edges, 7);
+    assert!(stats.branch_points.is_empty());
+    assert!(stats.residuals.is_empty());
+
+    let block = stats.repeated_block.unwrap();
+    assert_eq!(block.ops(&model), vec!["MatMul", "Relu"]);
+    assert_eq!(block.count(), 3);
+    assert_eq!(
+        block.instances.iter().map(|i| i[0]).collect::<Vec<_>>(),
+        vec![
+            node_id(&model, "matmul0"),
+            node_id(&model, "matmul1"),
+            node_id(&model, "matmul2")
+        ]
+    );
+}
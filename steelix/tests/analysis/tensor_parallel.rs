@@ -0,0 +1,90 @@
+use steelix::{prelude::*, shard_tensor_parallel, CollectiveKind, ShardKind};
+use steelix_onnx::onnx_pb::{self, tensor_proto::DataType as ProtoDType};
+
+use crate::{analyze, mlp_proto, node, node_id, value_info};
+
+fn constant(name: &str, dims: &[i64]) -> onnx_pb::TensorProto {
+    onnx_pb::TensorProto {
+        name: name.to_string(),
+        data_type: ProtoDType::Float as i32,
+        dims: dims.to_vec(),
+        float_data: vec![1.; dims.iter().product::<i64>() as usize],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_column_then_row() {
+    let (model, summary) = analyze(&mlp_proto(2, 64));
+    let plan = shard_tensor_parallel(&model, &summary, 2).unwrap();
+
+    let kind = |name: &str| plan.shards[&node_id(&model, name)];
+    assert_eq!(kind("matmul0"), ShardKind::Column);
+    assert_eq!(kind("relu0"), ShardKind::Elementwise);
+    assert_eq!(kind("matmul1"), ShardKind::Row);
+    assert_eq!(kind("relu1"), ShardKind::Replicated);
+
+    assert_eq!(plan.collectives.len(), 1);
+    assert_eq!(plan.collectives[0].kind, CollectiveKind::AllReduce);
+    assert_eq!(plan.collectives[0].tensor, "h1");
+    assert_eq!(plan.collectives[0].bytes, 64 * 4);
+    assert_eq!(plan.collectives[0].bytes_per_device(2), 64. * 4.);
+
+    assert_eq!(plan.total_param_bytes, 2 * 64 * 64 * 4);
+    assert_eq!(plan.param_bytes_per_device, 64 * 64 * 4);
+    assert!(plan.flops_per_device < plan.total_flops);
+}
+
+#[test]
+fn test_sharded_outputs_are_gathered() {
+    let (model, summary) = analyze(&mlp_proto(1, 64));
+    let plan = shard_tensor_parallel(&model, &summary, 4).unwrap();
+
+    assert_eq!(plan.collectives.len(), 1);
+    assert_eq!(plan.collectives[0].kind, CollectiveKind::AllGather);
+    assert_eq!(plan.collectives[0].tensor, "y");
+}
+
+#[test]
+fn test_indivisible_features_are_replicated() {
+    let (model, summary) = analyze(&mlp_proto(1, 6));
+    let plan = shard_tensor_parallel(&model, &summary, 4).unwrap();
+
+    assert_eq!(
+        plan.shards[&node_id(&model, "matmul0")],
+        ShardKind::Replicated
+    );
+    assert_eq!(plan.flops_per_device, plan.total_flops);
+    assert!(plan.collectives.is_empty());
+}
+
+#[test]
+fn test_broadcast_constants_are_not_split() {
+    //`y = x @ w + bias + scale`, where only `w` and `bias` have the sharded features
+    let proto = onnx_pb::ModelProto {
+        ir_version: 8,
+        graph: Some(onnx_pb::GraphProto {
+            node: vec![
+                node("MatMul", &["x", "w"], "h"),
+                node("Add", &["h", "bias"], "a"),
+                node("Add", &["a", "scale"], "y"),
+            ],
+            initializer: vec![
+                constant("w", &[8, 8]),
+                constant("bias", &[8]),
+                constant("scale", &[]),
+            ],
+            input: vec![value_info("x", shape![1, 8])],
+            output: vec![value_info("y", shape![1, 8])],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let (model, summary) = analyze(&proto);
+    let plan = shard_tensor_parallel(&model, &summary, 2).unwrap();
+
+    assert_eq!(plan.shards[&node_id(&model, "a")], ShardKind::Elementwise);
+    assert_eq!(plan.shards[&node_id(&model, "y")], ShardKind::Elementwise);
+    assert_eq!(plan.total_param_bytes, (64 + 8 + 1) * 4);
+    assert_eq!(plan.param_bytes_per_device, (32 + 4 + 1) * 4);
+}
@@ -0,0 +1,85 @@
+use std::process::Output;
+
+///Runs steelix on the model, saved in the ONNX textual syntax
+fn run(model: &str, args: &[&str]) -> Output {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("model.onnxtxt");
+    std::fs::write(&path, model).unwrap();
+
+    std::process::Command::new(env!("CARGO_BIN_EXE_steelix"))
+        .args(args)
+        .arg("--model-path")
+        .arg(&path)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_json_output_with_symbolic_batch() {
+    let model = r#"
+        <ir_version: 8, opset_import: ["" : 17]>
+        g (float[N, 8] x) => (float[N, 8] y) {
+            y = Relu (x)
+        }
+    "#;
+    let output = run(model, &["summary", "--format", "json"]);
+    assert!(output.status.success());
+    //The symbolic dimension is reported as a diagnostic, leaving stdout as valid JSON
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["inputs"][0]["shape"], serde_json::json!(["N", 8]));
+    assert_eq!(json["outputs"][0]["shape"], serde_json::json!(["N", 8]));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("symbolic dimensions N"));
+}
+
+#[test]
+fn test_strict_ignores_warnings() {
+    //An unimplemented op only raises a warning
+    let model = r#"
+        <ir_version: 8, opset_import: ["" : 17]>
+        g (float[1, 8] x) => (float[1, 8] y) {
+            y = NotAnOp (x)
+        }
+    "#;
+    let output = run(model, &["summary", "--strict", "--format", "json"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unimplemented operator"));
+}
+
+#[test]
+fn test_strict_fails_on_errors() {
+    let model = r#"
+        <ir_version: 8, opset_import: ["" : 17]>
+        g (float[2, 3] a, float[4, 5] b) => (float[2, 3] y) {
+            y = Add (a, b)
+        }
+    "#;
+    let output = run(model, &["summary", "--keep-going", "--format", "json"]);
+    assert!(output.status.success());
+
+    let output = run(
+        model,
+        &["summary", "--keep-going", "--strict", "--format", "json"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 errors reported in strict mode"));
+}
+
+#[test]
+fn test_infer_shapes_of_text_model() {
+    let model = r#"
+        <ir_version: 8, opset_import: ["" : 17]>
+        g (float[N, 8] x) => (float[N, 8] y) {
+            h = Relu (x)
+            y = Relu (h)
+        }
+    "#;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("shapes.onnxtxt");
+    let output = run(
+        model,
+        &["infer-shapes", "--output-path", path.to_str().unwrap()],
+    );
+    assert!(output.status.success());
+    let annotated = std::fs::read_to_string(&path).unwrap();
+    assert!(annotated.contains("<float[N, 8] h>"));
+}
@@ -0,0 +1,47 @@
+use steelix::{render_svg, to_dot, ConstantDisplay, RenderableGraph};
+
+use crate::conv;
+
+fn conv_node(graph: &RenderableGraph) -> &steelix::Node {
+    graph.nodes.iter().find(|n| n.op_type == "Conv").unwrap()
+}
+
+#[test]
+fn test_inline_constants() {
+    let (model, summary) = conv("conv");
+    let mut graph = RenderableGraph::build_graph(&model, Some(&summary));
+    graph.show_constants(&model, Some(&summary), ConstantDisplay::Inline);
+    graph.label_attributes(&model);
+
+    let node = conv_node(&graph);
+    assert_eq!(node.constants, vec!["w: f32 4x3x3x3"]);
+    assert_eq!(node.attribute("shape"), Some("record"));
+    assert_eq!(
+        node.label,
+        "Conv\nkernel_shape=[3, 3]\nstrides=[2, 2]\npads=[1, 1, 1, 1]"
+    );
+    let dot = to_dot(&graph);
+    assert!(dot.contains(
+        r#"label="{Conv\nkernel_shape=[3, 3]\nstrides=[2, 2]\npads=[1, 1, 1, 1]|w: f32 4x3x3x3\l}""#
+    ));
+    //The constants are ruled off from the label
+    assert!(render_svg(&graph).contains("<line "));
+}
+
+#[test]
+fn test_constant_nodes() {
+    let (model, summary) = conv("conv");
+    let mut graph = RenderableGraph::build_graph(&model, Some(&summary));
+    let (nodes, edges) = (graph.nodes.len(), graph.edges.len());
+    graph.show_constants(&model, Some(&summary), ConstantDisplay::Nodes);
+
+    assert_eq!(graph.nodes.len(), nodes + 1);
+    assert_eq!(graph.edges.len(), edges + 1);
+    let constant = graph.nodes.last().unwrap();
+    assert_eq!(constant.op_type, "Constant");
+    assert_eq!(constant.label, "w: f32 4x3x3x3");
+    let edge = graph.edges.last().unwrap();
+    assert_eq!((edge.from, edge.to), (constant.id, conv_node(&graph).id));
+    assert_eq!(edge.tensor, "w");
+    assert_eq!(edge.bytes, Some(432));
+}
@@ -0,0 +1,57 @@
+use steelix::{to_dot, to_gexf, to_graphml, to_mermaid, RenderableGraph};
+
+use crate::mlp;
+
+fn graph() -> RenderableGraph {
+    let (model, summary) = mlp("fc", 4);
+    let mut graph = RenderableGraph::build_graph(&model, Some(&summary));
+    graph.highlight_path(&[2, 3]);
+    graph
+}
+
+#[test]
+fn test_graph_carries_ops_and_tensors() {
+    let graph = graph();
+    let types: Vec<&str> = graph.nodes.iter().map(|n| &*n.op_type).collect();
+    assert_eq!(types, vec!["Input", "MatMul", "Relu", "Output"]);
+    assert_eq!(graph.nodes[1].name, "fc");
+    assert!(graph.nodes[1].cost.unwrap().flops > 0);
+    let tensors: Vec<&str> = graph.edges.iter().map(|e| &*e.tensor).collect();
+    assert_eq!(tensors, vec!["x", "h", "y"]);
+}
+
+#[test]
+fn test_mermaid() {
+    let mermaid = to_mermaid(&graph());
+    assert!(mermaid.starts_with("flowchart TD\n"));
+    assert!(mermaid.contains("    N0[\"x\"]\n"));
+    assert!(mermaid.contains("    N0 -->|\"1x8\"| N1\n"));
+    assert!(mermaid.contains("    linkStyle 1 stroke:red,stroke-width:3px\n"));
+}
+
+#[test]
+fn test_graphml() {
+    let graphml = to_graphml(&graph());
+    assert!(
+        graphml.contains(r#"<key id="node_flops" for="node" attr.name="flops" attr.type="long"/>"#)
+    );
+    assert!(graphml.contains(r#"<data key="node_op_type">MatMul</data>"#));
+    assert!(graphml.contains(r#"<data key="edge_shape">1x4</data>"#));
+    assert_eq!(graphml.matches("<edge ").count(), 3);
+}
+
+#[test]
+fn test_gexf() {
+    let gexf = to_gexf(&graph());
+    assert!(gexf.contains(r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#));
+    assert!(gexf.contains(r#"<attvalue for="tensor" value="h"/>"#));
+    assert!(gexf.contains(r#"<edge id="0" source="N0" target="N1" label="1x8">"#));
+    assert!(gexf.trim_end().ends_with("</gexf>"));
+}
+
+#[test]
+fn test_dot() {
+    let dot = to_dot(&graph());
+    assert!(dot.starts_with("digraph"));
+    assert!(dot.contains("N1 -> N2"));
+}
@@ -0,0 +1,59 @@
+use steelix::{heat_color, render_svg, to_dot, CostMetric, RenderableGraph};
+
+use crate::mlp;
+
+fn graph(metric: &CostMetric) -> RenderableGraph {
+    let (model, summary) = mlp("fc", 64);
+    let mut graph = RenderableGraph::build_graph(&model, Some(&summary));
+    graph.color_by_cost(&model, &summary, metric).unwrap();
+    graph.scale_edges();
+    graph
+}
+
+#[test]
+fn test_heat_color() {
+    assert_eq!(heat_color(0.), "#ffffcc");
+    assert_eq!(heat_color(1.), "#b10026");
+    assert_eq!(heat_color(2.), heat_color(1.));
+}
+
+#[test]
+fn test_color_by_flops() {
+    let graph = graph(&CostMetric::Flops);
+    //MatMul is the most expensive node, Relu the cheapest
+    assert_eq!(graph.nodes[1].attribute("fillcolor"), Some("#b10026"));
+    assert_eq!(graph.nodes[2].attribute("fillcolor"), Some("#ffffcc"));
+    //Inputs keep their group color
+    assert_ne!(graph.nodes[0].attribute("fillcolor"), Some("#ffffcc"));
+    let legend = graph.legend.as_ref().unwrap();
+    assert_eq!(legend.title, "FLOPs (log scale)");
+}
+
+#[test]
+fn test_color_by_params() {
+    //Relu has no parameters, so it is left uncolored
+    let graph = graph(&CostMetric::Params);
+    assert_eq!(graph.nodes[1].attribute("fillcolor"), Some("#b10026"));
+    assert_eq!(graph.nodes[2].attribute("fillcolor"), Some("white"));
+}
+
+#[test]
+fn test_edges_scale_with_bytes() {
+    let graph = graph(&CostMetric::Bytes);
+    assert_eq!(graph.edges[0].bytes, Some(32));
+    assert_eq!(graph.edges[1].bytes, Some(256));
+    assert_eq!(graph.edges[1].width, 8.);
+    assert!(graph.edges[0].width > 1. && graph.edges[0].width < 4.);
+}
+
+#[test]
+fn test_legend_is_rendered() {
+    let graph = graph(&CostMetric::Flops);
+    let svg = render_svg(&graph);
+    assert!(svg.contains(r#"<linearGradient id="heat">"#));
+    assert!(svg.contains(r#"<g class="legend""#));
+    assert!(svg.contains(r#"stroke-width="8.00""#));
+    let dot = to_dot(&graph);
+    assert!(dot.contains("legend[shape=plaintext"));
+    assert!(dot.contains("penwidth=8.00"));
+}
@@ -0,0 +1,35 @@
+use steelix::{render_html, RenderableGraph};
+
+use crate::conv;
+
+const SVG: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+<svg width="100pt" height="100pt"><g class="node"><title>N0</title></g></svg>"#;
+
+fn render(conv_name: &str) -> String {
+    let (model, summary) = conv(conv_name);
+    let graph = RenderableGraph::build_graph(&model, Some(&summary));
+    render_html(SVG, "conv <net>", &graph, &model, Some(&summary))
+}
+
+#[test]
+fn test_embeds_svg_and_details() {
+    let html = render("conv");
+    assert!(!html.contains("<?xml"));
+    assert!(!html.contains("{{"));
+    assert!(html.contains("<svg width=\"100pt\""));
+    assert!(html.contains("conv &lt;net&gt;"));
+    assert!(html.contains(r#"["kernel_shape","[3, 3]"]"#));
+    assert!(html.contains(r#""op_type":"Conv""#));
+    //The initializer feeding the convolution is summarized with its size
+    assert!(
+        html.contains(r#""name":"w","dtype":"f32","shape":"4x3x3x3","elements":108,"bytes":432"#)
+    );
+}
+
+#[test]
+fn test_names_cannot_close_the_script() {
+    let html = render("</script><script>alert(1)");
+    assert!(!html.contains("</script><script>alert(1)"));
+    assert!(html.contains(r#"<\/script><script>alert(1)"#));
+}
@@ -0,0 +1,84 @@
+use steelix::{layout, render_svg, RenderableGraph};
+
+fn graph(n: usize, edges: &[(usize, usize)]) -> RenderableGraph {
+    let mut g = RenderableGraph::new();
+    for i in 0..n {
+        g.create_node(format!("Op{}", i));
+    }
+    for &(from, to) in edges {
+        g.create_edge("  1x8".to_string(), from, to);
+    }
+    g
+}
+
+fn assert_no_overlaps(layout: &steelix::Layout) {
+    for (i, a) in layout.nodes.iter().enumerate() {
+        for b in layout.nodes[i + 1..].iter() {
+            let apart_x = (a.x - b.x).abs() >= (a.width + b.width) / 2.;
+            let apart_y = (a.y - b.y).abs() >= (a.height + b.height) / 2.;
+            assert!(apart_x || apart_y, "{:?} overlaps {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn test_edges_point_down() {
+    //A residual block: 0 -> 1 -> 2 -> 3, with a skip from 1 to 3
+    let g = graph(4, &[(0, 1), (1, 2), (2, 3), (1, 3)]);
+    let layout = layout(&g);
+    for edge in g.edges.iter() {
+        assert!(layout.nodes[edge.from].y < layout.nodes[edge.to].y);
+    }
+    //The skip connection is routed through a bend in the layer of node 2
+    assert_eq!(layout.edges[3].points.len(), 3);
+    assert_no_overlaps(&layout);
+    assert!(layout.nodes.iter().all(|n| n.x - n.width / 2. >= 0.
+        && n.x + n.width / 2. <= layout.width
+        && n.y + n.height / 2. <= layout.height));
+}
+
+#[test]
+fn test_branches_side_by_side() {
+    //0 fans out to 1, 2 and 3, which merge into 4
+    let g = graph(5, &[(0, 1), (0, 2), (0, 3), (1, 4), (2, 4), (3, 4)]);
+    let layout = layout(&g);
+    let branches = &layout.nodes[1..4];
+    assert!(branches.iter().all(|b| b.y == branches[0].y));
+    assert_no_overlaps(&layout);
+    //The fan out is centered over its branches
+    let mean = branches.iter().map(|b| b.x).sum::<f64>() / 3.;
+    assert!((layout.nodes[0].x - mean).abs() < 1.);
+}
+
+#[test]
+fn test_crossings_removed() {
+    //Node 2 feeds node 4 and node 3 feeds node 5, which are listed in the opposite order
+    let g = graph(6, &[(0, 2), (0, 3), (2, 5), (3, 4), (2, 4)]);
+    let layout = layout(&g);
+    let (n2, n3) = (layout.nodes[2].x, layout.nodes[3].x);
+    let (n4, n5) = (layout.nodes[4].x, layout.nodes[5].x);
+    assert_eq!(n2 < n3, n5 < n4);
+    assert_no_overlaps(&layout);
+}
+
+#[test]
+fn test_cycles_are_laid_out() {
+    let g = graph(3, &[(0, 1), (1, 2), (2, 0)]);
+    let layout = layout(&g);
+    assert_eq!(layout.edges.len(), 3);
+    //The edge closing the cycle still starts at its source
+    let back = &layout.edges[2].points;
+    assert!(back[0].y > back[back.len() - 1].y);
+}
+
+#[test]
+fn test_render_svg() {
+    let mut g = graph(2, &[(0, 1)]);
+    g.nodes[1].label = "a < b".to_string();
+    let svg = render_svg(&g);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.contains(r#"<g class="node" id="node1"><title>N1</title>"#));
+    assert!(svg.contains("a &lt; b"));
+    assert!(svg.contains(">1x8</text>"));
+    assert_eq!(svg.matches(r#"<g class="edge">"#).count(), 1);
+}
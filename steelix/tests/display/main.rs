@@ -1 +1,60 @@
+mod constants;
+mod export;
+mod heatmap;
+mod html;
+mod layout;
+mod region;
+mod report;
+mod scope;
+mod theme;
 
+use steelix::{prelude::*, Model, ModelBuilder, ModelSummary, NodeSpec};
+
+///Builds the model and propagates shapes through it
+pub fn analyze(builder: ModelBuilder) -> (Model, ModelSummary) {
+    let mut model = builder.build().unwrap().build_traversal_order();
+    let summary = model.run().unwrap();
+    (model, summary)
+}
+
+///`y = Relu(MatMul(x, w))` with `x` of [1, 8] and `w` of [8, width], the MatMul named `name`
+pub fn mlp(name: &str, width: usize) -> (Model, ModelSummary) {
+    analyze(
+        ModelBuilder::new()
+            .input("x", DType::F32, shape![1, 8])
+            .constant("w", Tensor::zeros::<f32>(shape![8, width]))
+            .op(NodeSpec::new("MatMul", &["x", "w"], &["h"]).named(name))
+            .node("Relu", &["h"], &["y"])
+            .output("y"),
+    )
+}
+
+///`x -> MatMul -> Relu -> MatMul -> Relu -> y` with [1, 8] activations, the nodes given `names`
+pub fn two_layers(names: [&str; 4]) -> (Model, ModelSummary) {
+    analyze(
+        ModelBuilder::new()
+            .input("x", DType::F32, shape![1, 8])
+            .constant("w0", Tensor::zeros::<f32>(shape![8, 8]))
+            .constant("w1", Tensor::zeros::<f32>(shape![8, 8]))
+            .op(NodeSpec::new("MatMul", &["x", "w0"], &["h0"]).named(names[0]))
+            .op(NodeSpec::new("Relu", &["h0"], &["a0"]).named(names[1]))
+            .op(NodeSpec::new("MatMul", &["a0", "w1"], &["h1"]).named(names[2]))
+            .op(NodeSpec::new("Relu", &["h1"], &["y"]).named(names[3]))
+            .output("y"),
+    )
+}
+
+///`y = Conv(x, w)` with a 3x3 kernel, stride 2 and padding 1, the Conv named `name`
+pub fn conv(name: &str) -> (Model, ModelSummary) {
+    analyze(
+        ModelBuilder::new()
+            .input("x", DType::F32, shape![1, 3, 8, 8])
+            .constant("w", Tensor::zeros::<f32>(shape![4, 3, 3, 3]))
+            .op(NodeSpec::new("Conv", &["x", "w"], &["y"])
+                .named(name)
+                .attr("kernel_shape", vec![3, 3])
+                .attr("strides", vec![2, 2])
+                .attr("pads", vec![1, 1, 1, 1]))
+            .output("y"),
+    )
+}
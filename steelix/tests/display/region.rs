@@ -0,0 +1,58 @@
+use steelix::{render_svg, select_region, to_dot, Region, RenderableGraph};
+
+use crate::two_layers;
+
+#[test]
+fn test_restrict_draws_stubs() {
+    let (model, summary) = two_layers(["fc0", "relu0", "fc1", "relu1"]);
+    let region = Region::Around {
+        center: "fc1".to_string(),
+        depth: 0,
+    };
+    let ids = select_region(&model, &region).unwrap();
+    let mut graph = RenderableGraph::build_graph(&model, Some(&summary));
+    graph.restrict(&ids);
+
+    let nodes: Vec<(&str, &str)> = graph
+        .nodes
+        .iter()
+        .map(|n| (&*n.op_type, &*n.label))
+        .collect();
+    assert_eq!(
+        nodes,
+        vec![("MatMul", "MatMul"), ("Stub", "a0"), ("Stub", "h1")]
+    );
+    let edges: Vec<(usize, usize)> = graph.edges.iter().map(|e| (e.from, e.to)).collect();
+    assert_eq!(edges, vec![(1, 0), (0, 2)]);
+    assert_eq!(graph.node_ids.len(), 1);
+
+    assert!(to_dot(&graph).contains(r#"style="filled,dashed""#));
+    assert_eq!(
+        render_svg(&graph)
+            .matches("stroke-dasharray=\"5 3\"")
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn test_restrict_keeps_parallel_edges() {
+    //Two outputs of one node read by the same consumer
+    let mut graph = RenderableGraph::default();
+    for (id, name) in ["split", "concat", "relu"].iter().enumerate() {
+        graph.create_node(name.to_string());
+        graph.node_ids.insert(id, id);
+    }
+    for (from, to, tensor) in [(0, 1, "s0"), (0, 1, "s1"), (1, 2, "c")] {
+        graph.create_edge(String::new(), from, to);
+        graph.edges.last_mut().unwrap().tensor = tensor.to_string();
+    }
+    graph.restrict(&[0, 1]);
+
+    let edges: Vec<(usize, usize, &str)> = graph
+        .edges
+        .iter()
+        .map(|e| (e.from, e.to, &*e.tensor))
+        .collect();
+    assert_eq!(edges, vec![(0, 1, "s0"), (0, 1, "s1"), (1, 2, "c")]);
+}
@@ -0,0 +1,84 @@
+use steelix::{
+    fingerprint, graph_stats, Device, DeviceStats, DimReport, SummaryFormat, SummaryReport,
+    SCHEMA_VERSION,
+};
+
+use crate::mlp;
+
+fn report() -> SummaryReport {
+    let (model, summary) = mlp("fc, first", 4);
+    let fingerprint = fingerprint(&model, &summary, &graph_stats(&model, &[]).unwrap());
+    let device = Device {
+        name: "Test".to_string(),
+        stats: DeviceStats {
+            tops: 0,
+            half: 0,
+            single: 1_000_000,
+            double: 0,
+        },
+    };
+    SummaryReport::new("mlp", &model, &summary, &fingerprint, &[], &[device])
+}
+
+#[test]
+fn test_report_contents() {
+    let report = report();
+    assert_eq!(report.schema_version, SCHEMA_VERSION);
+    assert!(!report.partial);
+    assert_eq!(report.inputs.len(), 1);
+    assert_eq!(report.outputs[0].name, "y");
+    assert_eq!(
+        report.outputs[0].shape,
+        vec![DimReport::Static(1), DimReport::Static(4)]
+    );
+
+    let matmul = report.nodes.iter().find(|n| n.op_type == "MatMul").unwrap();
+    assert_eq!(matmul.name, "fc, first");
+    assert_eq!(matmul.outputs[0].dtype, "f32");
+    assert!(matmul.flops.unwrap() > 0);
+
+    let op_flops: usize = report.op_types.iter().map(|op| op.flops).sum();
+    assert_eq!(op_flops, report.total_flops);
+    let its = report.devices[0].iterations_per_second.unwrap();
+    assert!((its - 1_000_000. / report.total_flops as f64).abs() < 1e-9);
+}
+
+#[test]
+fn test_json_schema() {
+    let json: serde_json::Value = serde_json::from_str(&report().to_json()).unwrap();
+    assert_eq!(json["schema_version"], SCHEMA_VERSION);
+    assert_eq!(json["model"], "mlp");
+    assert!(json["total_flops"].as_u64().unwrap() > 0);
+    assert!(json["nodes"].as_array().unwrap().len() >= 4);
+    assert!(json["architecture"]["structure_hash"].is_string());
+}
+
+#[test]
+fn test_csv_quotes_fields() {
+    let csv = report().to_csv();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "id,name,op_type,flops,params,shapes_known,outputs,dtypes,shapes"
+    );
+    let matmul = lines.find(|l| l.contains("MatMul")).unwrap();
+    assert!(matmul.contains("\"fc, first\",MatMul,"));
+    assert!(matmul.ends_with(",h,f32,1x4"));
+}
+
+#[test]
+fn test_markdown() {
+    let markdown = report().to_markdown();
+    assert!(markdown.starts_with("## mlp Model Summary"));
+    assert!(markdown.contains("| Op | Count | FLOPS | Parameters |"));
+    assert!(!markdown.contains("### Diagnostics"));
+}
+
+#[test]
+fn test_parse_format() {
+    assert_eq!(
+        "json".parse::<SummaryFormat>().unwrap(),
+        SummaryFormat::Json
+    );
+    assert!("yaml".parse::<SummaryFormat>().is_err());
+}
@@ -0,0 +1,145 @@
+use steelix::{
+    name_scope, prelude::*, render_svg, repeated_subgraphs, to_dot, to_mermaid, Model,
+    ModelBuilder, ModelSummary, NodeSpec, RenderableGraph,
+};
+
+use crate::{analyze, two_layers};
+
+fn model() -> (Model, ModelSummary) {
+    two_layers([
+        "/encoder/layer.0/fc/MatMul",
+        "/encoder/layer.0/Relu",
+        "/encoder/layer.1/fc/MatMul",
+        "/encoder/layer.1/Relu",
+    ])
+}
+
+fn graph() -> RenderableGraph {
+    let (model, summary) = model();
+    let mut graph = RenderableGraph::build_graph(&model, Some(&summary));
+    graph.clustered = true;
+    graph
+}
+
+#[test]
+fn test_name_scope() {
+    assert_eq!(
+        name_scope("/encoder/layer.3/attention/MatMul"),
+        vec!["encoder", "layer.3", "attention"]
+    );
+    assert!(name_scope("MatMul_12").is_empty());
+}
+
+#[test]
+fn test_scope_tree() {
+    let scopes = graph().scopes();
+    //Inputs and outputs are unscoped
+    assert_eq!(scopes.nodes, vec![0, 5]);
+    let encoder = &scopes.children[0];
+    assert_eq!(encoder.path, "encoder");
+    let layers: Vec<&str> = encoder.children.iter().map(|c| &*c.path).collect();
+    assert_eq!(layers, vec!["encoder/layer.0", "encoder/layer.1"]);
+    //A scope holding a single node is not a cluster of its own
+    assert_eq!(encoder.children[0].nodes, vec![2, 1]);
+    assert!(encoder.children[0].children.is_empty());
+}
+
+#[test]
+fn test_clusters_are_rendered() {
+    let graph = graph();
+    let dot = to_dot(&graph);
+    assert_eq!(dot.matches("subgraph cluster_").count(), 3);
+    assert!(dot.contains(r#"label="layer.1"; tooltip="encoder/layer.1";"#));
+    let mermaid = to_mermaid(&graph);
+    assert!(mermaid.contains("    subgraph S0[\"encoder\"]\n"));
+    let svg = render_svg(&graph);
+    assert_eq!(svg.matches(r#"<g class="cluster">"#).count(), 3);
+}
+
+#[test]
+fn test_collapse_scopes() {
+    let mut graph = graph();
+    let flops: usize = graph
+        .nodes
+        .iter()
+        .filter_map(|n| n.cost)
+        .map(|c| c.flops)
+        .sum();
+    graph.collapse_scopes(2);
+    let types: Vec<&str> = graph.nodes.iter().map(|n| &*n.op_type).collect();
+    assert_eq!(types, vec!["Input", "Scope", "Scope", "Output"]);
+    assert_eq!(graph.nodes[1].name, "encoder/layer.0");
+    assert!(graph.nodes[1].label.starts_with("layer.0\n2 nodes\n"));
+    let collapsed: usize = graph
+        .nodes
+        .iter()
+        .filter_map(|n| n.cost)
+        .map(|c| c.flops)
+        .sum();
+    assert_eq!(collapsed, flops);
+
+    let edges: Vec<(usize, usize)> = graph.edges.iter().map(|e| (e.from, e.to)).collect();
+    assert_eq!(edges, vec![(0, 1), (1, 2), (2, 3)]);
+    assert_eq!(graph.node_ids[&3], 1);
+}
+
+#[test]
+fn test_collapse_scopes_lists_merged_tensors() {
+    let (model, summary) = analyze(
+        ModelBuilder::new()
+            .input("x", DType::F32, shape![1, 8])
+            .op(NodeSpec::new("Relu", &["x"], &["r"]).named("/a/Relu"))
+            .op(NodeSpec::new("Sigmoid", &["x"], &["s"]).named("/a/Sigmoid"))
+            .op(NodeSpec::new("Add", &["r", "s"], &["t"]).named("/b/Add"))
+            .op(NodeSpec::new("Relu", &["r"], &["u"]).named("/b/Relu"))
+            .output("t")
+            .output("u"),
+    );
+    let mut graph = RenderableGraph::build_graph(&model, Some(&summary));
+    graph.collapse_scopes(1);
+
+    let edge = |from: &str, to: &str| {
+        let nd = |name: &str| graph.nodes.iter().position(|n| n.name == name).unwrap();
+        graph
+            .edges
+            .iter()
+            .find(|e| (e.from, e.to) == (nd(from), nd(to)))
+            .unwrap()
+    };
+    //`x` is read twice inside `a`, but only sent once
+    let input = edge("x", "a");
+    assert_eq!(input.tensor, "x");
+    assert_eq!(input.bytes, Some(8 * 4));
+    assert!(!input.label.is_empty());
+
+    let merged = edge("a", "b");
+    assert_eq!(merged.tensor, "r, s");
+    assert_eq!(merged.bytes, Some(2 * 8 * 4));
+    assert!(merged.label.is_empty());
+}
+
+#[test]
+fn test_collapse_repeats() {
+    let (model, summary) = model();
+    let blocks = repeated_subgraphs(&model, &summary).unwrap();
+    let mut graph = RenderableGraph::build_graph(&model, Some(&summary));
+    graph.clustered = true;
+    graph.collapse_repeats(&blocks);
+
+    let types: Vec<&str> = graph.nodes.iter().map(|n| &*n.op_type).collect();
+    assert_eq!(types, vec!["Input", "MatMul", "Relu", "Output"]);
+    assert_eq!(graph.repeats[0].count, 2);
+    assert_eq!(graph.repeats[0].scope, vec!["encoder"]);
+    //The edge from the first layer into the second is dropped, the rest redirected
+    let edges: Vec<(usize, usize)> = graph.edges.iter().map(|e| (e.from, e.to)).collect();
+    assert_eq!(edges, vec![(0, 1), (1, 2), (2, 3)]);
+
+    let encoder = &graph.scopes().children[0];
+    assert_eq!(encoder.children[0].name, "×2");
+    assert_eq!(encoder.children[0].repeat, Some(2));
+    assert!(to_dot(&graph).contains(r#"label="×2";"#));
+    assert_eq!(
+        render_svg(&graph).matches(r#"<g class="repeat">"#).count(),
+        1
+    );
+}
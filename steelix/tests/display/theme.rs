@@ -0,0 +1,117 @@
+use std::io::Write;
+
+use steelix::{
+    ops::data::{build_initial, Unimplemented},
+    prelude::*,
+    render_svg, to_dot, Model, OpGroup, Relu, RenderableGraph, Theme, ThemeError, ValueInfo,
+};
+
+///`x -> Foo -> Relu -> y`, built by hand as `ModelBuilder` rejects unknown ops such as Foo
+fn model() -> Model {
+    let mut model = Model::new();
+    let x = model.add_node(
+        "x".to_string(),
+        build_initial(ValueInfo::new("x", DType::F32, shape![1, 4])).unwrap(),
+    );
+    let custom = model.add_node("Foo".to_string(), Box::new(Unimplemented));
+    let relu = model.add_node("Relu".to_string(), Box::new(Relu));
+    let y = model.add_node("y".to_string(), Box::new(Unimplemented));
+    model.add_edge(x, custom);
+    model.add_edge(custom, relu);
+    model.add_edge(relu, y);
+    model.inputs.push(x);
+    model.outputs.push(y);
+    model
+}
+
+#[test]
+fn test_presets_style_every_group() {
+    for name in Theme::presets() {
+        let theme = Theme::load(name).unwrap();
+        for group in OpGroup::ALL {
+            assert!(
+                theme.groups.contains_key(&group.name()),
+                "{} does not style {:?}",
+                name,
+                group
+            );
+        }
+    }
+}
+
+#[test]
+fn test_unimplemented_ops_are_flagged() {
+    let graph = RenderableGraph::build_graph(&model(), None);
+    let labels: Vec<&str> = graph.nodes.iter().map(|n| &*n.label).collect();
+    assert_eq!(labels, vec!["x", "⚠ Foo", "Relu", "y"]);
+    assert_eq!(graph.nodes[1].attribute("style"), Some("dashed"));
+    assert_ne!(graph.nodes[3].attribute("style"), Some("dashed"));
+
+    let dot = to_dot(&graph);
+    assert!(dot.starts_with("digraph model {\n"));
+    assert!(dot.contains(r##"color="#cf222e""##));
+}
+
+#[test]
+fn test_user_theme_overrides_ops() {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    write!(
+        file,
+        r##"
+extends = "dark"
+edge = "#00ff00"
+
+[ops.Relu]
+fill = "gold"
+shape = "diamond"
+"##
+    )
+    .unwrap();
+    let theme = Theme::load(file.path().to_str().unwrap()).unwrap();
+    let dark = Theme::load("dark").unwrap();
+    assert_eq!(theme.background(), dark.background());
+    assert_eq!(theme.edge(), "#00ff00");
+
+    let graph = RenderableGraph::build_graph_with(&model(), None, &theme);
+    assert_eq!(graph.nodes[2].attribute("fillcolor"), Some("gold"));
+    assert_eq!(graph.nodes[2].attribute("shape"), Some("diamond"));
+    //Only the given fields are overridden
+    let relu = dark.style(Some(OpGroup::Activation), "Relu");
+    assert_eq!(
+        graph.nodes[2].attribute("fontcolor"),
+        relu.font_color.as_deref()
+    );
+
+    let svg = render_svg(&graph);
+    assert!(svg.contains("<polygon "));
+    assert!(svg.contains(&format!(r#"fill="{}""#, dark.background())));
+}
+
+#[test]
+fn test_json_theme() {
+    let theme = Theme::from_json(r#"{"groups": {"activation": {"fill": "pink"}}}"#).unwrap();
+    assert_eq!(
+        theme
+            .style(Some(OpGroup::Activation), "Relu")
+            .fill
+            .as_deref(),
+        Some("pink")
+    );
+    assert_eq!(theme.background(), "white");
+}
+
+#[test]
+fn test_invalid_themes() {
+    assert!(matches!(
+        Theme::from_toml("[groups.activations]\nfill = \"pink\""),
+        Err(ThemeError::UnknownGroup(group)) if group == "activations"
+    ));
+    assert!(matches!(
+        Theme::from_toml("[node]\ncolour = \"pink\""),
+        Err(ThemeError::Toml(_))
+    ));
+    assert!(matches!(
+        Theme::load("solarized"),
+        Err(ThemeError::Unknown(_))
+    ));
+}
@@ -1,2 +1,5 @@
 pub mod helpers;
+mod model;
+mod model_builder;
 mod ops;
+mod tensor;
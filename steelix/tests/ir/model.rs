@@ -0,0 +1,265 @@
+use steelix::{
+    ops::{
+        binary::Add,
+        data::{build_initial, Unimplemented},
+    },
+    prelude::*,
+    Model, ModelBuilder, ModelError, NodeSpec, Relu, Severity, ValueInfo,
+};
+
+#[test]
+fn test_value_info_hints_and_mismatches() {
+    let mut model = Model::new();
+    let x = model.add_node(
+        "x".to_string(),
+        build_initial(ValueInfo::new("x", DType::F32, shape![1, 4])).unwrap(),
+    );
+    let foo = model.add_node("Foo".to_string(), Box::new(Unimplemented));
+    let relu = model.add_node("Relu".to_string(), Box::new(Relu));
+    let z = model.add_node("z".to_string(), Box::new(Unimplemented));
+    model.nodes[foo].outputs = vec!["y".to_string()];
+    model.nodes[relu].outputs = vec!["z".to_string()];
+    model.add_edge(x, foo);
+    model.add_edge(foo, relu);
+    model.add_edge(relu, z);
+    model.inputs.push(x);
+    model.outputs.push(z);
+
+    model.value_info.insert(
+        "y".to_string(),
+        ValueInfo::new("y", DType::F32, shape![1, 8]),
+    );
+    model.value_info.insert(
+        "z".to_string(),
+        ValueInfo::new("z", DType::F32, shape![1, 9]),
+    );
+
+    let summary = model.build_traversal_order().run().unwrap();
+
+    assert_eq!(summary.hinted_nodes, vec![foo]);
+    assert_eq!(summary.output_shapes[&relu], shape![1, 8]);
+    assert_eq!(summary.shape_mismatches.len(), 1);
+    assert_eq!(summary.shape_mismatches[0].tensor, "z");
+    assert_eq!(summary.shape_mismatches[0].annotated, shape![1, 9]);
+}
+
+#[test]
+fn test_unknown_shapes_propagate() {
+    let mut model = Model::new();
+    let x = model.add_node(
+        "x".to_string(),
+        build_initial(ValueInfo::new("x", DType::F32, shape![1, 4])).unwrap(),
+    );
+    let foo = model.add_node("Foo".to_string(), Box::new(Unimplemented));
+    let relu = model.add_node("Relu".to_string(), Box::new(Relu));
+    let z = model.add_node("z".to_string(), Box::new(Unimplemented));
+    model.nodes[foo].outputs = vec!["y".to_string()];
+    model.nodes[relu].outputs = vec!["z".to_string()];
+    model.add_edge(x, foo);
+    model.add_edge(foo, relu);
+    model.add_edge(relu, z);
+    model.inputs.push(x);
+    model.outputs.push(z);
+
+    let summary = model.build_traversal_order().run().unwrap();
+
+    assert_eq!(summary.unknown_nodes, vec![foo, relu]);
+    assert_eq!(summary.total_flops, 0);
+    assert!(!summary.inferred_tensors.contains_key("z"));
+}
+
+fn unbroadcastable_add() -> (Model, usize) {
+    let mut model = Model::new();
+    let a = model.add_node(
+        "a".to_string(),
+        build_initial(ValueInfo::new("a", DType::F32, shape![2, 3])).unwrap(),
+    );
+    let b = model.add_node(
+        "b".to_string(),
+        build_initial(ValueInfo::new("b", DType::F32, shape![4, 5])).unwrap(),
+    );
+    let add = model.add_node("Add".to_string(), Box::new(Add));
+    let relu = model.add_node("Relu".to_string(), Box::new(Relu));
+    let z = model.add_node("z".to_string(), Box::new(Unimplemented));
+    model.nodes[add].outputs = vec!["y".to_string()];
+    model.nodes[relu].outputs = vec!["z".to_string()];
+    model.add_edge(a, add);
+    model.add_edge(b, add);
+    model.add_edge(add, relu);
+    model.add_edge(relu, z);
+    model.inputs.extend([a, b]);
+    model.outputs.push(z);
+    (model.build_traversal_order(), add)
+}
+
+#[test]
+fn test_realize_error_stops_by_default() {
+    let (mut model, add) = unbroadcastable_add();
+    match model.run() {
+        Err(ModelError::Diagnostic(d)) => {
+            assert_eq!(d.node_id, Some(add));
+            assert_eq!(d.provider_shapes, vec![shape![2, 3], shape![4, 5]]);
+        }
+        other => panic!("Expected a diagnostic, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_keep_going_collects_diagnostics() {
+    let (mut model, add) = unbroadcastable_add();
+    let summary = model.run_keep_going().unwrap();
+
+    assert_eq!(summary.diagnostics.len(), 1);
+    assert_eq!(summary.diagnostics[0].severity, Severity::Error);
+    assert_eq!(summary.diagnostics[0].node_id, Some(add));
+    assert_eq!(summary.unknown_nodes.len(), 2);
+}
+
+#[test]
+fn test_malformed_shapes_are_errors() {
+    //A Conv on a matrix would index past its rank
+    let mut model = ModelBuilder::new()
+        .input("x", DType::F32, shape![8, 8])
+        .constant("w", Tensor::zeros::<f32>(shape![4, 3, 3, 3]))
+        .op(NodeSpec::new("Conv", &["x", "w"], &["y"])
+            .attr("kernel_shape", vec![3, 3])
+            .attr("strides", vec![1, 1]))
+        .output("y")
+        .build()
+        .unwrap()
+        .build_traversal_order();
+    let summary = model.run_keep_going().unwrap();
+
+    assert_eq!(summary.diagnostics.len(), 1);
+    assert!(summary.diagnostics[0]
+        .message
+        .contains("Expected a tensor of rank 4"));
+}
+
+fn producer(model: &Model, tensor: &str) -> usize {
+    model
+        .nodes
+        .iter()
+        .position(|node| node.outputs.iter().any(|o| o == tensor))
+        .unwrap()
+}
+
+///Swaps the producer of the tensor for an op we can't realize
+fn unimplemented(model: &mut Model, tensor: &str) {
+    let id = producer(model, tensor);
+    model.nodes[id].name = "Foo".to_string();
+    model.nodes[id].op = Box::new(Unimplemented);
+}
+
+#[test]
+fn test_shapes_recovered_past_unimplemented() {
+    let mut model = ModelBuilder::new()
+        .input("x", DType::F32, shape![1, 4])
+        .constant("target", Tensor::from_vec(shape![2], vec![2_i64, 2]))
+        .constant("b", Tensor::zeros::<f32>(shape![2, 2]))
+        .node("Relu", &["x"], &["y"])
+        .node("Relu", &["y"], &["r"])
+        .node("Reshape", &["r", "target"], &["z"])
+        .node("Add", &["z", "b"], &["out"])
+        .output("out")
+        .build()
+        .unwrap();
+    unimplemented(&mut model, "y");
+    let mut model = model.build_traversal_order();
+
+    let summary = model.run().unwrap();
+
+    let out = &summary.inferred_tensors["out"];
+    assert_eq!(out.dimensions, shape![2, 2]);
+    assert_eq!(out.dim_params, vec![None, None]);
+    assert_eq!(
+        summary.unknown_nodes,
+        vec![producer(&model, "y"), producer(&model, "r")]
+    );
+}
+
+#[test]
+fn test_partial_shapes_propagate() {
+    let mut model = ModelBuilder::new()
+        .input("x", DType::F32, shape![1, 4])
+        .constant("target", Tensor::from_vec(shape![2], vec![0_i64, -1]))
+        .constant("b", Tensor::zeros::<f32>(shape![3, 1]))
+        .node("Relu", &["x"], &["y"])
+        .node("Reshape", &["y", "target"], &["z"])
+        .op(NodeSpec::new("Transpose", &["z"], &["t"]).attr("perm", vec![1, 0]))
+        .node("Add", &["t", "b"], &["out"])
+        .node("Shape", &["out"], &["s"])
+        .output("s")
+        .build()
+        .unwrap();
+    unimplemented(&mut model, "y");
+    let mut model = model.build_traversal_order();
+
+    let summary = model.run().unwrap();
+
+    let out = &summary.inferred_tensors["out"];
+    assert_eq!(out.dimensions, shape![3, 1]);
+    assert_eq!(out.dim_params, vec![None, Some("?".to_string())]);
+    assert_eq!(summary.inferred_tensors["s"].dimensions, shape![2]);
+    assert_eq!(summary.inferred_tensors["s"].dim_params, vec![None]);
+}
+
+#[test]
+fn test_symbolic_batch_is_kept() {
+    let mut input = ValueInfo::new("x", DType::F32, shape![1, 4]);
+    input.dim_params[0] = Some("N".to_string());
+    let mut model = ModelBuilder::new()
+        .input_info(input)
+        .constant("w", Tensor::zeros::<f32>(shape![4, 8]))
+        .constant("target", Tensor::from_vec(shape![3], vec![0_i64, 2, -1]))
+        .node("Relu", &["x"], &["r"])
+        .node("MatMul", &["r", "w"], &["m"])
+        .node("Reshape", &["m", "target"], &["out"])
+        .output("out")
+        .build()
+        .unwrap()
+        .build_traversal_order();
+
+    let summary = model.run().unwrap();
+
+    let n = Some("N".to_string());
+    assert_eq!(
+        summary.inferred_tensors["r"].dim_params,
+        vec![n.clone(), None]
+    );
+    assert_eq!(
+        summary.inferred_tensors["m"].dim_params,
+        vec![n.clone(), None]
+    );
+    let out = &summary.inferred_tensors["out"];
+    assert_eq!(out.dimensions, shape![1, 2, 4]);
+    assert_eq!(out.dim_params, vec![n, None, None]);
+    assert!(summary.unknown_nodes.is_empty());
+}
+
+#[test]
+fn test_static_one_is_not_symbolic() {
+    let mut input = ValueInfo::new("x", DType::F32, shape![1, 4]);
+    input.dim_params[0] = Some("N".to_string());
+    let mut model = ModelBuilder::new()
+        .input_info(input)
+        .constant("first", Tensor::from_vec(shape![1], vec![0_i64]))
+        .node("Gather", &["x", "first"], &["g"])
+        .node("Relu", &["x"], &["r"])
+        .output("g")
+        .output("r")
+        .build()
+        .unwrap()
+        .build_traversal_order();
+
+    let summary = model.run().unwrap();
+
+    //A single row is gathered whatever the batch
+    let g = &summary.inferred_tensors["g"];
+    assert_eq!(g.dimensions, shape![1, 4]);
+    assert_eq!(g.dim_params, vec![None, None]);
+    assert_eq!(
+        summary.inferred_tensors["r"].dim_params,
+        vec![Some("N".to_string()), None]
+    );
+}
@@ -0,0 +1,81 @@
+use steelix::{prelude::*, BuilderError, ModelBuilder, NodeSpec};
+
+#[test]
+fn test_build_and_run() {
+    let mut model = ModelBuilder::new()
+        .input("x", DType::F32, shape![1, 8])
+        .constant("w", Tensor::zeros::<f32>(shape![8, 4]))
+        .op(NodeSpec::new("MatMul", &["x", "w"], &["h"]).named("fc"))
+        .node("Relu", &["h"], &["y"])
+        .output("y")
+        .build()
+        .unwrap()
+        .build_traversal_order();
+    let summary = model.run().unwrap();
+
+    assert_eq!(model.inputs.len(), 1);
+    assert_eq!(model.outputs.len(), 1);
+    assert!(model.nodes.iter().any(|n| n.onnx_name == "fc"));
+    assert_eq!(summary.inferred_tensors["y"].dimensions, shape![1, 4]);
+    assert!(summary.total_flops > 0);
+}
+
+#[test]
+fn test_attributes() {
+    let mut model = ModelBuilder::new()
+        .input("x", DType::F32, shape![1, 3, 8, 8])
+        .constant("w", Tensor::zeros::<f32>(shape![4, 3, 3, 3]))
+        .op(NodeSpec::new("Conv", &["x", "w"], &["y"])
+            .attr("kernel_shape", vec![3, 3])
+            .attr("strides", vec![2, 2])
+            .attr("pads", vec![1, 1, 1, 1]))
+        .output("y")
+        .build()
+        .unwrap()
+        .build_traversal_order();
+    let summary = model.run().unwrap();
+
+    assert_eq!(summary.inferred_tensors["y"].dimensions, shape![1, 4, 4, 4]);
+}
+
+#[test]
+fn test_nodes_in_any_order() {
+    let model = ModelBuilder::new()
+        .input("x", DType::F32, shape![1, 8])
+        .node("Relu", &["h"], &["y"])
+        .node("Relu", &["x"], &["h"])
+        .output("y")
+        .build()
+        .unwrap()
+        .build_traversal_order();
+
+    assert_eq!(model.traversal_order.unwrap().len(), 4);
+}
+
+#[test]
+fn test_errors() {
+    let unknown = ModelBuilder::new()
+        .input("x", DType::F32, shape![1, 8])
+        .node("Relu", &["z"], &["y"])
+        .output("y")
+        .build();
+    assert!(matches!(unknown, Err(BuilderError::UnknownTensor { tensor, .. }) if tensor == "z"));
+
+    let unsupported = ModelBuilder::new()
+        .input("x", DType::F32, shape![1, 8])
+        .node("Foo", &["x"], &["y"])
+        .build();
+    assert!(matches!(unsupported, Err(BuilderError::UnsupportedOp(op)) if op == "Foo"));
+
+    let duplicate = ModelBuilder::new()
+        .input("x", DType::F32, shape![1, 8])
+        .node("Relu", &["x"], &["x"])
+        .build();
+    assert!(matches!(duplicate, Err(BuilderError::DuplicateTensor(t)) if t == "x"));
+
+    let missing = ModelBuilder::new()
+        .input("x", DType::F32, shape![1, 8])
+        .output("y")
+        .build();
+    assert!(matches!(missing, Err(BuilderError::UnknownOutput(t)) if t == "y"));
+}
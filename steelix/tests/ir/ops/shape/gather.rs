@@ -17,3 +17,23 @@ fn test_gather() {
     let output = Op::realize(&gather, pvec!(data, indicies)).expect("Failed to realize squeeze.");
     assert_eq!(desired, output);
 }
+
+#[test]
+fn test_gather_from_skipped_weights() {
+    let data = Tensor::shape_only(DType::F32, shape![32000, 64]).into_arc_tensor();
+    let indices = array![[0_i64, 1, 2]].into_arc_tensor();
+    let gather = Gather { axis: 0 };
+
+    let output = Op::realize(&gather, pvec!(data, indices)).unwrap();
+    assert_eq!(output.outputs[0].shape, shape![1, 3, 64]);
+    assert!(!output.outputs[0].has_data());
+}
+
+#[test]
+fn test_gather_non_numeric_is_error() {
+    let data = array![[true, false], [false, true]].into_arc_tensor();
+    let indices = array![1_i64].into_arc_tensor();
+    let gather = Gather { axis: 0 };
+
+    assert!(Op::realize(&gather, pvec!(data, indices)).is_err());
+}
@@ -0,0 +1,121 @@
+use half::f16;
+use steelix::{prelude::*, LazyTensor, WeightLoading};
+use steelix_onnx::onnx_pb::{tensor_proto::DataType as ProtoDType, TensorProto};
+
+fn proto(dt: ProtoDType, dims: Vec<i64>) -> TensorProto {
+    TensorProto {
+        data_type: dt as i32,
+        dims,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_bool_from_int32_data() {
+    let tproto = TensorProto {
+        int32_data: vec![1, 0, 1],
+        ..proto(ProtoDType::Bool, vec![3])
+    };
+    let tensor: Tensor = tproto.try_into().unwrap();
+
+    assert_eq!(tensor, Tensor::from_vec(shape![3], vec![true, false, true]));
+    assert_eq!(tensor.size_in_bytes(), 3);
+}
+
+#[test]
+fn test_f16_from_packed_int32_data() {
+    let values = [f16::from_f32(1.5), f16::from_f32(-2.0)];
+    let tproto = TensorProto {
+        int32_data: values.iter().map(|v| v.to_bits() as i32).collect(),
+        ..proto(ProtoDType::Float16, vec![2])
+    };
+    let tensor: Tensor = tproto.try_into().unwrap();
+
+    assert_eq!(tensor, Tensor::from_vec(shape![2], values.to_vec()));
+    assert_eq!(tensor.size_in_bytes(), 4);
+}
+
+#[test]
+fn test_double_is_f64() {
+    let tproto = TensorProto {
+        double_data: vec![0.1, 0.2],
+        ..proto(ProtoDType::Double, vec![2])
+    };
+    let tensor: Tensor = tproto.try_into().unwrap();
+
+    assert_eq!(tensor.dt, DType::F64);
+    assert_eq!(tensor, Tensor::from_vec(shape![2], vec![0.1_f64, 0.2]));
+}
+
+#[test]
+fn test_string_data() {
+    let tproto = TensorProto {
+        string_data: vec![b"steel".to_vec(), b"ix".to_vec()],
+        ..proto(ProtoDType::String, vec![2])
+    };
+    let tensor: Tensor = tproto.try_into().unwrap();
+
+    assert_eq!(tensor.dt, DType::String);
+    assert_eq!(tensor.string_data, vec!["steel", "ix"]);
+    assert_eq!(tensor.size_in_bytes(), 7);
+}
+
+#[test]
+fn test_raw_data_length_mismatch() {
+    let tproto = TensorProto {
+        raw_data: vec![0; 3].into(),
+        ..proto(ProtoDType::Float, vec![1])
+    };
+    assert!(Tensor::try_from(tproto).is_err());
+}
+
+#[test]
+fn test_string_data_length_mismatch() {
+    let tproto = TensorProto {
+        string_data: vec![b"steel".to_vec()],
+        ..proto(ProtoDType::String, vec![2])
+    };
+    assert!(Tensor::try_from(tproto).is_err());
+}
+
+#[test]
+fn test_float_data_length_mismatch() {
+    let tproto = TensorProto {
+        name: "w".to_string(),
+        float_data: vec![1.0, 2.0],
+        ..proto(ProtoDType::Float, vec![3])
+    };
+    let error = Tensor::try_from(tproto).unwrap_err();
+    assert!(error.to_string().contains("w has 2 values in float_data"));
+
+    //Each complex element takes two values
+    let tproto = TensorProto {
+        float_data: vec![1.0, 2.0],
+        ..proto(ProtoDType::Complex64, vec![2])
+    };
+    assert!(Tensor::try_from(tproto).is_err());
+}
+
+#[test]
+fn test_shapes_only_skips_large_initializers() {
+    let small = TensorProto {
+        int64_data: vec![1, -1],
+        ..proto(ProtoDType::Int64, vec![2])
+    };
+    let large = proto(ProtoDType::Float, vec![1024, 1024]);
+
+    let small = LazyTensor::new(small, WeightLoading::ShapesOnly).unwrap();
+    let large = LazyTensor::new(large, WeightLoading::ShapesOnly).unwrap();
+
+    assert!(!small.is_skipped());
+    assert_eq!(
+        *small.materialize().unwrap(),
+        Tensor::from_vec(shape![2], vec![1i64, -1])
+    );
+    assert!(large.is_skipped());
+    let large = large.materialize().unwrap();
+    assert_eq!(large.shape, shape![1024, 1024]);
+    assert!(!large.has_data());
+    assert!(large.data.is_empty());
+    assert!(large.to_array_view::<f32>().is_err());
+}
@@ -0,0 +1,94 @@
+use std::io::Write;
+
+use steelix::{prelude::*, resolve_external_data, ExternalDataError};
+use steelix_onnx::onnx_pb::{
+    tensor_proto::{DataLocation, DataType as ProtoDType},
+    StringStringEntryProto, TensorProto,
+};
+
+fn external_proto(entries: &[(&str, &str)]) -> TensorProto {
+    TensorProto {
+        name: "weight".to_string(),
+        data_type: ProtoDType::Float as i32,
+        dims: vec![2],
+        data_location: DataLocation::External as i32,
+        external_data: entries
+            .iter()
+            .map(|(k, v)| StringStringEntryProto {
+                key: k.to_string(),
+                value: v.to_string(),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn write_weights(dir: &std::path::Path) {
+    let mut f = std::fs::File::create(dir.join("weights.bin")).unwrap();
+    f.write_all(&[0u8; 4]).unwrap(); //padding before the tensor
+    for v in [1.0f32, 2.0] {
+        f.write_all(&v.to_le_bytes()).unwrap();
+    }
+}
+
+#[test]
+fn test_resolve_offset_and_length() {
+    let dir = tempfile::tempdir().unwrap();
+    write_weights(dir.path());
+
+    let mut tproto = external_proto(&[
+        ("location", "weights.bin"),
+        ("offset", "4"),
+        ("length", "8"),
+    ]);
+    resolve_external_data(&mut tproto, dir.path()).unwrap();
+    let tensor: Tensor = tproto.try_into().unwrap();
+
+    assert_eq!(tensor, Tensor::from_vec(shape![2], vec![1.0f32, 2.0]));
+}
+
+#[test]
+fn test_checksum_mismatch() {
+    let dir = tempfile::tempdir().unwrap();
+    write_weights(dir.path());
+
+    let mut tproto = external_proto(&[
+        ("location", "weights.bin"),
+        ("offset", "4"),
+        ("checksum", "0000000000000000000000000000000000000000"),
+    ]);
+    let err = resolve_external_data(&mut tproto, dir.path()).unwrap_err();
+    assert!(matches!(err, ExternalDataError::ChecksumMismatch { .. }));
+}
+
+#[test]
+fn test_missing_file() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut tproto = external_proto(&[("location", "missing.bin")]);
+    let err = resolve_external_data(&mut tproto, dir.path()).unwrap_err();
+    assert!(matches!(err, ExternalDataError::MissingFile { .. }));
+}
+
+#[test]
+fn test_location_outside_model_dir() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut tproto = external_proto(&[("location", "../weights.bin")]);
+    let err = resolve_external_data(&mut tproto, dir.path()).unwrap_err();
+    assert!(matches!(err, ExternalDataError::InvalidLocation { .. }));
+}
+
+#[test]
+fn test_range_overflow() {
+    let dir = tempfile::tempdir().unwrap();
+    write_weights(dir.path());
+
+    let mut tproto = external_proto(&[
+        ("location", "weights.bin"),
+        ("offset", "4"),
+        ("length", &u64::MAX.to_string()),
+    ]);
+    let err = resolve_external_data(&mut tproto, dir.path()).unwrap_err();
+    assert!(matches!(err, ExternalDataError::RangeOverflow { .. }));
+}
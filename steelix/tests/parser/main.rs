@@ -0,0 +1,3 @@
+mod external_data;
+mod onnx_parser;
+mod onnx_text;
@@ -0,0 +1,44 @@
+use prost::Message;
+use steelix::{parse_model, prelude::*, ValueInfo};
+use steelix_onnx::onnx_pb::{self, tensor_proto::DataType as ProtoDType};
+
+#[test]
+fn test_initializer_listed_as_input_is_linked() {
+    let model = onnx_pb::ModelProto {
+        ir_version: 3,
+        graph: Some(onnx_pb::GraphProto {
+            node: vec![onnx_pb::NodeProto {
+                op_type: "MatMul".to_string(),
+                input: vec!["x".to_string(), "w".to_string()],
+                output: vec!["y".to_string()],
+                ..Default::default()
+            }],
+            initializer: vec![onnx_pb::TensorProto {
+                name: "w".to_string(),
+                data_type: ProtoDType::Float as i32,
+                dims: vec![4, 2],
+                float_data: vec![0.; 8],
+                ..Default::default()
+            }],
+            //Older exporters list initializers as graph inputs
+            input: vec![
+                (&ValueInfo::new("w", DType::F32, shape![4, 2])).into(),
+                (&ValueInfo::new("x", DType::F32, shape![1, 4])).into(),
+            ],
+            output: vec![(&ValueInfo::new("y", DType::F32, shape![1, 2])).into()],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("model.onnx");
+    std::fs::write(&path, model.encode_to_vec()).unwrap();
+
+    let model = parse_model(&path).unwrap();
+    let id = |name: &str| model.nodes.iter().position(|n| n.name == name).unwrap();
+    assert_eq!(model.nodes[id("MatMul")].providers, vec![id("x"), id("w")]);
+    assert_eq!(model.nodes[id("y")].providers, vec![id("MatMul")]);
+
+    let summary = model.build_traversal_order().run().unwrap();
+    assert_eq!(summary.inferred_tensors["y"].dimensions, shape![1, 2]);
+}
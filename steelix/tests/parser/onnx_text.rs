@@ -0,0 +1,179 @@
+use steelix::{parse_text, parse_text_model, prelude::*, to_text, TextWriteError};
+use steelix_onnx::onnx_pb::{
+    self, attribute_proto::AttributeType, tensor_proto::DataLocation,
+    tensor_proto::DataType as ProtoDType,
+};
+
+const MLP: &str = r#"
+<
+   ir_version: 8,
+   opset_import: ["" : 17, "com.microsoft" : 1],
+   producer_name: "steelix"
+>
+mlp (float[N, 4] X, float[4, 2] W = {1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0}) => (float[N, 2] Y)
+   <int64[2] shape = {-1, 2}, float[N, 2] "/fc/out:0">
+{
+   [fc] "/fc/out:0" = MatMul (X, W)
+   act = LeakyRelu <alpha = 0.1> ("/fc/out:0")
+   # reshaping is a no-op, but reads a constant
+   Y = Reshape (act, shape)
+}
+"#;
+
+#[test]
+fn test_parse_text() {
+    let model = parse_text(MLP).unwrap();
+    assert_eq!(model.ir_version, 8);
+    assert_eq!(model.opset_import.len(), 2);
+    assert_eq!(model.producer_name, "steelix");
+
+    let graph = model.graph.unwrap();
+    assert_eq!(graph.name, "mlp");
+    assert_eq!(graph.input.len(), 2);
+    assert_eq!(graph.output.len(), 1);
+    assert_eq!(graph.value_info[0].name, "/fc/out:0");
+
+    let names: Vec<&str> = graph.initializer.iter().map(|i| &*i.name).collect();
+    assert_eq!(names, vec!["W", "shape"]);
+    assert_eq!(graph.initializer[0].float_data.len(), 8);
+    assert_eq!(graph.initializer[1].int64_data, vec![-1, 2]);
+    assert_eq!(graph.initializer[1].data_type, ProtoDType::Int64 as i32);
+
+    assert_eq!(graph.node[0].name, "fc");
+    assert_eq!(graph.node[0].output, vec!["/fc/out:0"]);
+    let alpha = &graph.node[1].attribute[0];
+    assert_eq!(alpha.r#type, AttributeType::Float as i32);
+    assert_eq!(alpha.f, 0.1);
+}
+
+#[test]
+fn test_text_model_runs() {
+    let mut model = parse_text_model(MLP).unwrap().build_traversal_order();
+    let summary = model.run().unwrap();
+    assert_eq!(summary.inferred_tensors["Y"].dimensions, shape![1, 2]);
+}
+
+#[test]
+fn test_node_attributes() {
+    let model = parse_text_model(MLP).unwrap();
+    let relu = model.nodes.iter().find(|n| n.name == "LeakyRelu").unwrap();
+    assert_eq!(
+        relu.attributes,
+        vec![("alpha".to_string(), "0.1".to_string())]
+    );
+}
+
+#[test]
+fn test_print_round_trip() {
+    let model = parse_text(MLP).unwrap();
+    let printed = to_text(&model, false).unwrap();
+    assert_eq!(parse_text(&printed).unwrap(), model);
+}
+
+#[test]
+fn test_domains_optional_inputs_and_subgraphs() {
+    let model = parse_text(
+        r#"
+        main (bool cond, float[2] X) => (float[2] Y) {
+            A = com.microsoft.FusedGelu (X)
+            B = Clip (A, , X)
+            Y = If <then_branch = then (float[2] T) => (float[2] T) {}, else_branch = else () => (float[2] E) {
+                E = Identity (B)
+            }> (cond)
+        }
+        "#,
+    )
+    .unwrap();
+    let graph = model.graph.as_ref().unwrap();
+
+    assert_eq!(graph.node[0].domain, "com.microsoft");
+    assert_eq!(graph.node[0].op_type, "FusedGelu");
+    assert_eq!(graph.node[1].input, vec!["A", "", "X"]);
+    let else_branch = graph.node[2].attribute[1].g.as_ref().unwrap();
+    assert_eq!(else_branch.node[0].op_type, "Identity");
+
+    assert_eq!(parse_text(&to_text(&model, false).unwrap()).unwrap(), model);
+}
+
+#[test]
+fn test_large_initializers_are_elided() {
+    let model = parse_text(
+        "g (float[1, 32] X) => (float[1, 32] Y) <float[32] B = {0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0}> { Y = Add (X, B) }",
+    )
+    .unwrap();
+
+    let elided = parse_text(&to_text(&model, false).unwrap()).unwrap();
+    let graph = elided.graph.unwrap();
+    assert!(graph.initializer.is_empty());
+    assert_eq!(graph.value_info[0].name, "B");
+
+    let full = parse_text(&to_text(&model, true).unwrap()).unwrap();
+    assert_eq!(full, model);
+}
+
+#[test]
+fn test_errors_report_line() {
+    let error = parse_text("g (float[2] X) => (float[2] Y) {\n   Y = Relu (X\n}").unwrap_err();
+    assert_eq!(error.line, 3);
+    let error = parse_text("g (complex[2] X) => () {}").unwrap_err();
+    assert_eq!(error.line, 1);
+}
+
+#[test]
+fn test_value_count_must_match_shape() {
+    let error = parse_text(
+        "g (float[2] X) => (float[2] Y)\n   <float[3] B = {1.0, 2.0}>\n{ Y = Add (X, B) }",
+    )
+    .unwrap_err();
+    assert_eq!(error.line, 2);
+    assert!(error.message.contains("B has 2 values"));
+
+    //Without dims the initializer is a scalar
+    let error =
+        parse_text("g (float[2] X, float W = {1.0, 2.0}) => (float[2] Y) { Y = Add (X, W) }")
+            .unwrap_err();
+    assert!(error
+        .message
+        .contains("W has 2 values, but its shape holds 1"));
+    assert!(
+        parse_text("g (float[2] X, float W = {1.0}) => (float[2] Y) { Y = Add (X, W) }").is_ok()
+    );
+}
+
+#[test]
+fn test_unprintable_models_are_errors() {
+    let mut model = parse_text(MLP).unwrap();
+    let graph = model.graph.as_mut().unwrap();
+    graph.initializer[0].data_location = DataLocation::External as i32;
+    assert!(to_text(&model, false).is_ok());
+    assert!(matches!(
+        to_text(&model, true),
+        Err(TextWriteError::MissingValues(name)) if name == "W"
+    ));
+
+    let mut model = parse_text(MLP).unwrap();
+    let graph = model.graph.as_mut().unwrap();
+    graph.output[0].r#type = Some(onnx_pb::TypeProto {
+        value: Some(onnx_pb::type_proto::Value::SequenceType(Box::default())),
+        ..Default::default()
+    });
+    assert!(matches!(
+        to_text(&model, false),
+        Err(TextWriteError::NotATensor {
+            kind: "sequence",
+            ..
+        })
+    ));
+
+    let mut model = parse_text(MLP).unwrap();
+    let graph = model.graph.as_mut().unwrap();
+    graph.node[1].attribute.push(onnx_pb::AttributeProto {
+        name: "sparse".to_string(),
+        r#type: AttributeType::SparseTensor as i32,
+        ..Default::default()
+    });
+    assert!(matches!(
+        to_text(&model, false),
+        Err(TextWriteError::UnsupportedAttribute { attribute, .. }) if attribute == "sparse"
+    ));
+}
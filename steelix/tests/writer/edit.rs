@@ -0,0 +1,211 @@
+use std::{collections::HashMap, path::Path};
+
+use steelix::{
+    extract_subgraph, model_from_proto, parse_dims, parse_text, prelude::*, rename_node,
+    rename_tensor, set_batch_size, set_input_shape, write_edits, DimSpec, EditError, Model,
+    ValueInfo, WeightLoading,
+};
+use steelix_onnx::onnx_pb::{
+    self,
+    tensor_shape_proto::{dimension::Value as DimValue, Dimension},
+    type_proto,
+};
+
+const CHAIN: &str = r#"
+<ir_version: 8, opset_import: ["" : 17]>
+chain (float[1, 4] x) => (float[1, 4] y)
+   <float[4] b = {0.0, 0.0, 0.0, 0.0}, float[1, 4] a>
+{
+   [relu] a = Relu (x)
+   [add] c = Add (a, b)
+   [sigmoid] y = Sigmoid (c)
+}
+"#;
+
+///x -> Relu -> a -> Add(b) -> c -> Sigmoid -> y
+fn chain_model() -> (onnx_pb::ModelProto, Model) {
+    let proto = parse_text(CHAIN).unwrap();
+    let model = model_from_proto(proto.clone(), Path::new(""), WeightLoading::Eager).unwrap();
+    (proto, model)
+}
+
+fn dims(vi: &onnx_pb::ValueInfoProto) -> Vec<Option<DimValue>> {
+    match vi.r#type.as_ref().unwrap().value.as_ref().unwrap() {
+        type_proto::Value::TensorType(t) => t
+            .shape
+            .as_ref()
+            .unwrap()
+            .dim
+            .iter()
+            .map(|d: &Dimension| d.value.clone())
+            .collect(),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_parse_dims() {
+    assert_eq!(
+        parse_dims("N, 3,?").unwrap(),
+        vec![
+            DimSpec::Param("N".to_string()),
+            DimSpec::Value(3),
+            DimSpec::Unknown
+        ]
+    );
+    assert!(parse_dims("").unwrap().is_empty());
+    assert!(matches!(parse_dims("1,-2"), Err(EditError::InvalidDim(_))));
+}
+
+#[test]
+fn test_set_shapes() {
+    let (mut proto, mut model) = chain_model();
+    set_input_shape(&mut model, "x", &parse_dims("2,8").unwrap()).unwrap();
+    assert!(matches!(
+        set_input_shape(&mut model, "b", &[]),
+        Err(EditError::NotAnInput(_))
+    ));
+    assert_eq!(
+        set_batch_size(&mut model, &DimSpec::Param("N".to_string())).unwrap(),
+        2
+    );
+    write_edits(&model, &mut proto).unwrap();
+
+    let graph = proto.graph.unwrap();
+    assert_eq!(
+        dims(&graph.input[0]),
+        vec![
+            Some(DimValue::DimParam("N".to_string())),
+            Some(DimValue::DimValue(8))
+        ]
+    );
+    assert_eq!(
+        dims(&graph.output[0]),
+        vec![
+            Some(DimValue::DimParam("N".to_string())),
+            Some(DimValue::DimValue(4))
+        ]
+    );
+    assert!(graph.value_info.is_empty());
+}
+
+#[test]
+fn test_edited_model_runs() {
+    let (_, mut model) = chain_model();
+    set_input_shape(&mut model, "x", &parse_dims("3,4").unwrap()).unwrap();
+    let summary = model.build_traversal_order().run().unwrap();
+    assert_eq!(summary.inferred_tensors["y"].dimensions, shape![3, 4]);
+}
+
+#[test]
+fn test_rename() {
+    let (mut proto, mut model) = chain_model();
+    rename_tensor(&mut model, "a", "relu_out").unwrap();
+    rename_tensor(&mut model, "b", "bias_weight").unwrap();
+    rename_tensor(&mut model, "y", "out").unwrap();
+    rename_node(&mut model, "add", "bias").unwrap();
+    assert!(matches!(
+        rename_tensor(&mut model, "c", "x"),
+        Err(EditError::NameTaken(_))
+    ));
+    assert!(matches!(
+        rename_node(&mut model, "missing", "other"),
+        Err(EditError::UnknownNode(_))
+    ));
+    write_edits(&model, &mut proto).unwrap();
+
+    let graph = proto.graph.unwrap();
+    assert_eq!(graph.node[0].output, vec!["relu_out"]);
+    assert_eq!(graph.node[1].input, vec!["relu_out", "bias_weight"]);
+    assert_eq!(graph.node[1].name, "bias");
+    assert_eq!(graph.node[2].output, vec!["out"]);
+    assert_eq!(graph.initializer[0].name, "bias_weight");
+    assert_eq!(graph.output[0].name, "out");
+    assert_eq!(graph.value_info[0].name, "relu_out");
+}
+
+#[test]
+fn test_edits_need_the_parsed_model() {
+    let (mut proto, _) = chain_model();
+    let (_, other) = chain_model();
+    proto.graph.as_mut().unwrap().node.pop();
+    assert!(matches!(
+        write_edits(&other, &mut proto),
+        Err(EditError::ModelMismatch)
+    ));
+}
+
+#[test]
+fn test_extract_subgraph() {
+    let (model, _) = chain_model();
+    let mut inferred = HashMap::new();
+    inferred.insert(
+        "c".to_string(),
+        ValueInfo::new("c", DType::F32, shape![1, 4]),
+    );
+
+    let extracted =
+        extract_subgraph(&model, &["a".to_string()], &["c".to_string()], &inferred).unwrap();
+    let graph = extracted.graph.unwrap();
+    assert_eq!(extracted.ir_version, 8);
+    assert_eq!(extracted.opset_import, model.opset_import);
+    assert_eq!(graph.node.len(), 1);
+    assert_eq!(graph.node[0].name, "add");
+    assert_eq!(graph.initializer.len(), 1);
+    assert_eq!(graph.input[0].name, "a");
+    assert_eq!(
+        graph.output[0],
+        onnx_pb::ValueInfoProto::from(&inferred["c"])
+    );
+    assert!(graph.value_info.is_empty());
+
+    //Without an inferred shape the type of `c` is unknown
+    assert!(matches!(
+        extract_subgraph(
+            &model,
+            &["a".to_string()],
+            &["c".to_string()],
+            &HashMap::new()
+        ),
+        Err(EditError::MissingType(_))
+    ));
+}
+
+#[test]
+fn test_extract_keeps_symbolic_dimensions() {
+    let proto = parse_text(&CHAIN.replace("float[1, 4] x", "float[N, 4] x")).unwrap();
+    let model = model_from_proto(proto.clone(), Path::new(""), WeightLoading::Eager).unwrap();
+    let summary = model.build_traversal_order().run().unwrap();
+
+    let extracted = extract_subgraph(
+        &proto,
+        &["x".to_string()],
+        &["c".to_string()],
+        &summary.inferred_tensors,
+    )
+    .unwrap();
+    let graph = extracted.graph.unwrap();
+    assert_eq!(
+        dims(&graph.output[0]),
+        vec![
+            Some(DimValue::DimParam("N".to_string())),
+            Some(DimValue::DimValue(4))
+        ]
+    );
+}
+
+#[test]
+fn test_extract_keeps_required_graph_inputs() {
+    let (model, _) = chain_model();
+    let extracted = extract_subgraph(
+        &model,
+        &[],
+        &["a".to_string()],
+        &HashMap::<String, ValueInfo>::new(),
+    )
+    .unwrap();
+    let graph = extracted.graph.unwrap();
+    assert_eq!(graph.node.len(), 1);
+    assert_eq!(graph.input[0].name, "x");
+    assert_eq!(graph.output[0].name, "a");
+}
@@ -0,0 +1,80 @@
+use prost::Message;
+use steelix::{prelude::*, write_inferred_shapes, ModelSummary, ValueInfo};
+use steelix_onnx::onnx_pb;
+
+fn relu_model() -> Vec<u8> {
+    onnx_pb::ModelProto {
+        ir_version: 8,
+        producer_name: "steelix".to_string(),
+        graph: Some(onnx_pb::GraphProto {
+            name: "g".to_string(),
+            node: vec![onnx_pb::NodeProto {
+                op_type: "Relu".to_string(),
+                input: vec!["x".to_string()],
+                output: vec!["y".to_string()],
+                ..Default::default()
+            }],
+            input: vec![(&ValueInfo::new("x", DType::F32, shape![1, 4])).into()],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+    .encode_to_vec()
+}
+
+#[test]
+fn test_appends_only_unknown_tensors() {
+    let original = relu_model();
+
+    let mut summary = ModelSummary::default();
+    for vi in [
+        ValueInfo::new("x", DType::F32, shape![1, 4]),
+        ValueInfo::new("y", DType::F32, shape![1, 4]),
+    ] {
+        summary.inferred_tensors.insert(vi.name.clone(), vi);
+    }
+
+    let mut annotated = vec![];
+    let count = write_inferred_shapes(&original, &summary, &mut annotated).unwrap();
+    assert_eq!(count, 1);
+
+    let decoded = onnx_pb::ModelProto::decode(&annotated[..]).unwrap();
+    let graph = decoded.graph.unwrap();
+    assert_eq!(decoded.producer_name, "steelix");
+    assert_eq!(graph.value_info.len(), 1);
+    assert_eq!(
+        graph.value_info[0],
+        onnx_pb::ValueInfoProto::from(&ValueInfo::new("y", DType::F32, shape![1, 4]))
+    );
+    assert_eq!(graph.node.len(), 1);
+}
+
+#[test]
+fn test_symbolic_dimensions_are_kept() {
+    let mut y = ValueInfo::new("y", DType::F32, shape![1, 4]);
+    y.dim_params[0] = Some("N".to_string());
+    let mut summary = ModelSummary::default();
+    summary.inferred_tensors.insert("y".to_string(), y);
+
+    let mut annotated = vec![];
+    write_inferred_shapes(&relu_model(), &summary, &mut annotated).unwrap();
+
+    let graph = onnx_pb::ModelProto::decode(&annotated[..])
+        .unwrap()
+        .graph
+        .unwrap();
+    let vi: ValueInfo = graph.value_info[0].clone().try_into().unwrap();
+    assert_eq!(vi.dim_params, vec![Some("N".to_string()), None]);
+}
+
+#[test]
+fn test_truncated_model_is_error() {
+    let original = relu_model();
+    let mut annotated = vec![];
+    let result = write_inferred_shapes(
+        &original[..original.len() - 1],
+        &ModelSummary::default(),
+        &mut annotated,
+    );
+    assert!(result.is_err());
+}
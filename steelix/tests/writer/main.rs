@@ -0,0 +1,2 @@
+mod edit;
+mod inferred_shapes;